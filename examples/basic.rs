@@ -46,7 +46,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     run(&mut db, "DROP TABLE products;")?;
     run(&mut db, "DROP DATABASE demo_db;")?;
 
-    db.close();
+    db.close()?;
     println!("\nDone.");
     Ok(())
 }
\ No newline at end of file
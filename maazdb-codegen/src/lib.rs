@@ -0,0 +1,268 @@
+//! Generates Rust structs from a live MaazDB schema.
+//!
+//! Every table becomes a row struct deriving `maazdb_rs::FromRow`, plus a `New<Table>`
+//! struct (the same columns minus `SERIAL` ones) with typed `insert` helpers:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), maazdb_rs::MaazDBError> {
+//! use maazdb_codegen::CodegenOptions;
+//! use maazdb_rs::MaazDB;
+//!
+//! let mut db = MaazDB::connect("127.0.0.1", 8888, "admin", "admin")?;
+//! db.query("USE store_prod;")?;
+//! let code = maazdb_codegen::generate_from_db(&mut db, &CodegenOptions::new())?;
+//! std::fs::write("src/models.rs", code)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Column types map to `i64` (`SERIAL`, `INT`), `f64`, `bool`, `String`,
+//! `chrono::NaiveDateTime`, `uuid::Uuid` and `Vec<u8>`, wrapped in `Option` when the column
+//! is nullable. The generated code names `chrono` and `uuid` directly, so a crate that has
+//! `TIMESTAMP` or `UUID` columns needs those dependencies too.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use maazdb_rs::migrate::HISTORY_TABLE;
+use maazdb_rs::{identifier, ColumnInfo, DataType, MaazDB, MaazDBError, TableSchema};
+
+/// What to generate.
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    tables: Option<Vec<String>>,
+    insert_helpers: bool,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        CodegenOptions { tables: None, insert_helpers: true }
+    }
+}
+
+impl CodegenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only generate these tables. By default every table except the migration history is used.
+    pub fn tables<S: AsRef<str>>(mut self, tables: &[S]) -> Self {
+        self.tables = Some(tables.iter().map(|t| t.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Whether to emit the `New<Table>` structs and their `insert` helpers (default `true`).
+    pub fn insert_helpers(mut self, enabled: bool) -> Self {
+        self.insert_helpers = enabled;
+        self
+    }
+}
+
+/// Describes the selected tables of the current database.
+pub fn introspect(db: &mut MaazDB, opts: &CodegenOptions) -> Result<Vec<TableSchema>, MaazDBError> {
+    let tables = match &opts.tables {
+        Some(tables) => tables.clone(),
+        None => db.list_tables()?.into_iter().filter(|t| t != HISTORY_TABLE).collect(),
+    };
+    tables.iter().map(|t| db.describe(t)).collect()
+}
+
+/// [`introspect`] followed by [`generate`].
+pub fn generate_from_db(db: &mut MaazDB, opts: &CodegenOptions) -> Result<String, MaazDBError> {
+    generate(&introspect(db, opts)?, opts)
+}
+
+/// Renders the Rust source for `schemas`.
+///
+/// The `insert` helpers splice table and column names into their SQL, so with
+/// `insert_helpers` on, a name that is not a plain identifier is a `ParameterError`.
+/// So are names that would collide once converted: two columns that map to the same
+/// field (`userId` and `user_id`), or two tables that map to the same type.
+pub fn generate(schemas: &[TableSchema], opts: &CodegenOptions) -> Result<String, MaazDBError> {
+    check_collisions(schemas, opts)?;
+    let mut out = String::from("// Generated by maazdb-codegen. Do not edit; regenerate after changing the schema.\n");
+    for schema in schemas {
+        out.push('\n');
+        row_struct(&mut out, schema);
+        if opts.insert_helpers {
+            insert_struct(&mut out, schema)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Fails if two tables would generate the same type, or two columns of one table the same field.
+fn check_collisions(schemas: &[TableSchema], opts: &CodegenOptions) -> Result<(), MaazDBError> {
+    let mut types: HashMap<String, &str> = HashMap::new();
+    for schema in schemas {
+        let mut names = vec![struct_name(&schema.name)];
+        if opts.insert_helpers && schema.columns.iter().any(|c| c.data_type != DataType::Serial) {
+            names.push(format!("New{}", struct_name(&schema.name)));
+        }
+        for name in names {
+            if let Some(other) = types.insert(name.clone(), &schema.name) {
+                return Err(MaazDBError::ParameterError(format!(
+                    "Tables '{}' and '{}' would both generate the type {}", other, schema.name, name,
+                )));
+            }
+        }
+
+        let mut fields: HashMap<String, &str> = HashMap::new();
+        for column in &schema.columns {
+            let field = field_name(&column.name);
+            if let Some(other) = fields.insert(field.clone(), &column.name) {
+                return Err(MaazDBError::ParameterError(format!(
+                    "Columns '{}' and '{}' of table '{}' would both become the field {}", other, column.name, schema.name, field,
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The Rust type for a column, including `Option` for nullable ones.
+pub fn rust_type(column: &ColumnInfo) -> String {
+    let base = match &column.data_type {
+        DataType::Serial | DataType::Int => "i64",
+        DataType::Double => "f64",
+        DataType::Bool => "bool",
+        DataType::Timestamp => "chrono::NaiveDateTime",
+        DataType::Uuid => "uuid::Uuid",
+        DataType::Blob => "Vec<u8>",
+        DataType::Text | DataType::Other(_) => "String",
+    };
+    if column.nullable { format!("Option<{}>", base) } else { base.to_string() }
+}
+
+/// `order_items` -> `OrderItems`.
+pub fn struct_name(table: &str) -> String {
+    let mut name: String = table
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect::<String>()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'T');
+    }
+    name
+}
+
+/// A snake_case field name for a column, avoiding Rust keywords: `UserId` -> `user_id`,
+/// `HTTPStatus` -> `http_status`.
+pub fn field_name(column: &str) -> String {
+    let chars: Vec<char> = column.chars().collect();
+    let mut name = String::with_capacity(column.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            name.push('_');
+            continue;
+        }
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if prev.is_ascii_lowercase() || prev.is_ascii_digit() || (prev.is_ascii_uppercase() && next_lower) {
+                name.push('_');
+            }
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "abstract", "become", "box", "do", "final", "macro", "override",
+    "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+fn row_struct(out: &mut String, schema: &TableSchema) {
+    let name = struct_name(&schema.name);
+    let _ = writeln!(out, "/// A row of `{}`.", schema.name);
+    // f64 fields rule out Eq
+    let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq, maazdb_rs::FromRow)]");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    for column in &schema.columns {
+        field(out, column, true);
+    }
+    let _ = writeln!(out, "}}");
+}
+
+fn insert_struct(out: &mut String, schema: &TableSchema) -> Result<(), MaazDBError> {
+    let columns: Vec<&ColumnInfo> = schema.columns.iter().filter(|c| c.data_type != DataType::Serial).collect();
+    if columns.is_empty() {
+        return Ok(());
+    }
+    let name = format!("New{}", struct_name(&schema.name));
+    let table = sql_identifier(&schema.name, &schema.name, "table")?;
+    let names = columns.iter().map(|c| sql_identifier(&schema.name, &c.name, "column")).collect::<Result<Vec<_>, _>>()?;
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("${}", i)).collect();
+    let params: Vec<String> = columns.iter().map(|c| format!("&self.{}", field_name(&c.name))).collect();
+    let params = params.join(", ");
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "/// A new row for `{}`. `SERIAL` columns are left to the server.", schema.name);
+    let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq)]");
+    let _ = writeln!(out, "pub struct {} {{", name);
+    for column in &columns {
+        field(out, column, false);
+    }
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "impl {} {{", name);
+    let _ = writeln!(
+        out,
+        "    pub const INSERT: &'static str = \"INSERT INTO {} ({}) VALUES ({});\";",
+        table, names.join(", "), placeholders.join(", "),
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    pub fn insert(&self, db: &mut maazdb_rs::MaazDB) -> Result<String, maazdb_rs::MaazDBError> {{");
+    let _ = writeln!(out, "        db.query_with(Self::INSERT, &[{}])", params);
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    pub async fn insert_async(&self, db: &mut maazdb_rs::AsyncMaazDB) -> Result<String, maazdb_rs::MaazDBError> {{");
+    let _ = writeln!(out, "        db.query_with(Self::INSERT, &[{}]).await", params);
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}");
+    Ok(())
+}
+
+/// `name` if it can go into the generated SQL as is, by the same rule as the query builder.
+fn sql_identifier<'a>(table: &str, name: &'a str, what: &str) -> Result<&'a str, MaazDBError> {
+    identifier(name, what).map_err(|e| match e {
+        MaazDBError::ParameterError(msg) => {
+            MaazDBError::ParameterError(format!("Cannot generate an INSERT for table '{}': {}", table, msg))
+        }
+        other => other,
+    })
+}
+
+/// `rename` adds the `FromRow` attribute mapping the field back to its column.
+fn field(out: &mut String, column: &ColumnInfo, rename: bool) {
+    let mut doc = format!("`{}`", column.data_type);
+    if column.primary_key {
+        doc.push_str(", primary key");
+    }
+    if let Some(default) = &column.default {
+        let _ = write!(doc, ", default `{}`", default);
+    }
+    if let Some(fk) = &column.references {
+        let _ = write!(doc, ", references `{}`", fk);
+    }
+    let field = field_name(&column.name);
+    let _ = writeln!(out, "    /// {}", doc);
+    if rename && field != column.name {
+        let _ = writeln!(out, "    #[maazdb(rename = \"{}\")]", column.name.escape_default());
+    }
+    let _ = writeln!(out, "    pub {}: {},", field, rust_type(column));
+}
@@ -0,0 +1,81 @@
+use std::process::ExitCode;
+
+use maazdb_codegen::CodegenOptions;
+use maazdb_rs::{ConnectOptions, MaazDB, MAAZDB_URL_ENV};
+
+const USAGE: &str = "Usage: maazdb-codegen [--url URL] [--database NAME] [--table NAME]... [--no-insert] [--out FILE]
+
+Connects to MaazDB (--url, or the MAAZDB_URL environment variable), describes every
+table of the database and prints Rust structs for them, or writes them to --out.";
+
+struct Args {
+    url: Option<String>,
+    database: Option<String>,
+    tables: Vec<String>,
+    insert_helpers: bool,
+    out: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args { url: None, database: None, tables: Vec::new(), insert_helpers: true, out: None };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--url" => args.url = Some(value()?),
+            "--database" => args.database = Some(value()?),
+            "--table" => args.tables.push(value()?),
+            "--out" => args.out = Some(value()?),
+            "--no-insert" => args.insert_helpers = false,
+            "-h" | "--help" => return Err(String::new()),
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+    Ok(args)
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let mut opts = match &args.url {
+        Some(url) => ConnectOptions::from_url(url)?,
+        None if std::env::var_os(MAAZDB_URL_ENV).is_some() => ConnectOptions::from_env()?,
+        None => return Err(format!("pass --url or set {}", MAAZDB_URL_ENV).into()),
+    };
+    if let Some(database) = &args.database {
+        opts = opts.database(database)?;
+    }
+
+    let mut codegen = CodegenOptions::new().insert_helpers(args.insert_helpers);
+    if !args.tables.is_empty() {
+        codegen = codegen.tables(&args.tables);
+    }
+
+    let mut db = MaazDB::connect_with(&opts)?;
+    let code = maazdb_codegen::generate_from_db(&mut db, &codegen)?;
+    db.close()?;
+
+    match &args.out {
+        Some(path) => std::fs::write(path, code)?,
+        None => print!("{}", code),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("error: {}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Generated code for an engine-backed schema. tests/snapshot/models.rs is the expected
+// output and is compiled into this test, so it must also build and round-trip rows.
+// Regenerate it with `UPDATE_SNAPSHOT=1 cargo test -p maazdb-codegen`.
+
+// Not every helper is exercised.
+#[allow(dead_code)]
+#[path = "snapshot/models.rs"]
+mod models;
+
+use maazdb_codegen::CodegenOptions;
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ColumnInfo, DataType, MaazDB, MaazDBError, TableSchema};
+
+const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshot/models.rs");
+
+fn shop(server: &MockServer) -> MaazDB {
+    let mut db = server.connect().unwrap();
+    db.execute_script("
+        CREATE DATABASE shop;
+        USE shop;
+        CREATE TABLE users (
+            id SERIAL PRIMARY KEY,
+            name TEXT NOT NULL,
+            email TEXT UNIQUE,
+            active BOOL NOT NULL DEFAULT TRUE,
+            created TIMESTAMP,
+            uuid UUID NOT NULL
+        );
+        CREATE TABLE order_items (
+            id SERIAL PRIMARY KEY,
+            user_id INT NOT NULL,
+            price DOUBLE NOT NULL,
+            type TEXT,
+            receipt BLOB,
+            FOREIGN KEY (user_id) REFERENCES users(id)
+        );
+        CREATE TABLE counters (id SERIAL PRIMARY KEY);
+    ", maazdb_rs::OnError::Stop).into_result().unwrap();
+    db
+}
+
+#[test]
+fn matches_snapshot() {
+    let server = MockServer::with_engine();
+    let mut db = shop(&server);
+    let code = maazdb_codegen::generate_from_db(&mut db, &CodegenOptions::new()).unwrap();
+    if std::env::var_os("UPDATE_SNAPSHOT").is_some() {
+        std::fs::write(SNAPSHOT, &code).unwrap();
+    }
+    assert_eq!(code, std::fs::read_to_string(SNAPSHOT).unwrap_or_default(), "rerun with UPDATE_SNAPSHOT=1 if the change is intended");
+}
+
+#[test]
+fn generated_code_round_trips() {
+    let server = MockServer::with_engine();
+    let mut db = shop(&server);
+    let created = chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(12, 30, 0).unwrap();
+    let user = models::NewUsers {
+        name: "Ada".into(),
+        email: None,
+        active: true,
+        created: Some(created),
+        uuid: uuid::Uuid::from_u128(7),
+    };
+    user.insert(&mut db).unwrap();
+    let item = models::NewOrderItems { user_id: 1, price: 9.5, type_: Some("book".into()), receipt: Some(vec![0, 1, 2]) };
+    item.insert(&mut db).unwrap();
+
+    let users: Vec<models::Users> = db.query_as("SELECT * FROM users;").unwrap();
+    assert_eq!(users, [models::Users {
+        id: 1,
+        name: "Ada".into(),
+        email: None,
+        active: true,
+        created: Some(created),
+        uuid: uuid::Uuid::from_u128(7),
+    }]);
+    let items: Vec<models::OrderItems> = db.query_as("SELECT * FROM order_items;").unwrap();
+    assert_eq!(items, [models::OrderItems { id: 1, user_id: 1, price: 9.5, type_: Some("book".into()), receipt: Some(vec![0, 1, 2]) }]);
+}
+
+#[tokio::test]
+async fn async_insert_helper() {
+    let server = MockServer::with_engine();
+    drop(shop(&server));
+    let mut db = server.connect_async().await.unwrap();
+    db.query("USE shop;").await.unwrap();
+    let user = models::NewUsers { name: "Lin".into(), email: Some("lin@example.com".into()), active: false, created: None, uuid: uuid::Uuid::nil() };
+    user.insert_async(&mut db).await.unwrap();
+    let users: Vec<models::Users> = db.query_as("SELECT * FROM users;").await.unwrap();
+    assert_eq!(users[0].email.as_deref(), Some("lin@example.com"));
+    assert!(!users[0].active);
+    db.close().await;
+}
+
+#[test]
+fn options_select_tables_and_helpers() {
+    let server = MockServer::with_engine();
+    let mut db = shop(&server);
+    let code = maazdb_codegen::generate_from_db(&mut db, &CodegenOptions::new().tables(&["counters"]).insert_helpers(false)).unwrap();
+    assert!(code.contains("pub struct Counters {"));
+    assert!(!code.contains("Users") && !code.contains("impl "));
+
+    // A table with only SERIAL columns has nothing to insert.
+    let code = maazdb_codegen::generate_from_db(&mut db, &CodegenOptions::new().tables(&["counters"])).unwrap();
+    assert!(!code.contains("NewCounters"));
+}
+
+#[test]
+fn names_and_types() {
+    assert_eq!(maazdb_codegen::struct_name("order_items"), "OrderItems");
+    assert_eq!(maazdb_codegen::struct_name("2024_sales"), "T2024Sales");
+    assert_eq!(maazdb_codegen::field_name("UserId"), "user_id");
+    assert_eq!(maazdb_codegen::field_name("userID2"), "user_id2");
+    assert_eq!(maazdb_codegen::field_name("HTTPStatus"), "http_status");
+    assert_eq!(maazdb_codegen::field_name("Order2Total"), "order2_total");
+    assert_eq!(maazdb_codegen::field_name("user_id"), "user_id");
+    assert_eq!(maazdb_codegen::field_name("first name"), "first_name");
+    assert_eq!(maazdb_codegen::field_name("match"), "match_");
+    assert_eq!(maazdb_codegen::field_name("1st"), "_1st");
+
+    let column = |data_type, nullable| ColumnInfo { name: "c".into(), data_type, nullable, primary_key: false, default: None, references: None };
+    assert_eq!(maazdb_codegen::rust_type(&column(DataType::Timestamp, false)), "chrono::NaiveDateTime");
+    assert_eq!(maazdb_codegen::rust_type(&column(DataType::Uuid, true)), "Option<uuid::Uuid>");
+    assert_eq!(maazdb_codegen::rust_type(&column(DataType::Other("JSON".into()), false)), "String");
+}
+
+#[test]
+fn insert_helpers_need_plain_identifiers() {
+    let column = |name: &str| ColumnInfo { name: name.into(), data_type: DataType::Text, nullable: true, primary_key: false, default: None, references: None };
+    let table = |name: &str, columns: &[&str]| [TableSchema { name: name.into(), columns: columns.iter().map(|c| column(c)).collect() }];
+
+    let code = maazdb_codegen::generate(&table("people", &["FirstName"]), &CodegenOptions::new()).unwrap();
+    assert!(code.contains("#[maazdb(rename = \"FirstName\")]\n    pub first_name: Option<String>,"), "{}", code);
+    assert!(code.contains("\"INSERT INTO people (FirstName) VALUES ($1);\""), "{}", code);
+
+    for (name, columns) in [("people", &["name\"); DROP TABLE people; --"][..]), ("people", &["first name"]), ("my people", &["name"])] {
+        match maazdb_codegen::generate(&table(name, columns), &CodegenOptions::new()) {
+            Err(MaazDBError::ParameterError(msg)) => assert!(msg.contains("is not a valid"), "{}", msg),
+            other => panic!("{:?}: expected a ParameterError, got {:?}", columns, other),
+        }
+    }
+    // Without insert helpers the names only appear in `rename` attributes, which are escaped.
+    let code = maazdb_codegen::generate(&table("people", &["first name"]), &CodegenOptions::new().insert_helpers(false)).unwrap();
+    assert!(code.contains("#[maazdb(rename = \"first name\")]\n    pub first_name: Option<String>,"), "{}", code);
+}
+
+#[test]
+fn colliding_names_are_rejected() {
+    let column = |name: &str| ColumnInfo { name: name.into(), data_type: DataType::Int, nullable: false, primary_key: false, default: None, references: None };
+    let table = |name: &str, columns: &[&str]| TableSchema { name: name.into(), columns: columns.iter().map(|c| column(c)).collect() };
+    let error = |schemas: &[TableSchema], opts: CodegenOptions| match maazdb_codegen::generate(schemas, &opts) {
+        Err(MaazDBError::ParameterError(msg)) => msg,
+        other => panic!("expected a ParameterError, got {:?}", other),
+    };
+
+    let msg = error(&[table("users", &["userId", "user_id"])], CodegenOptions::new());
+    assert_eq!(msg, "Columns 'userId' and 'user_id' of table 'users' would both become the field user_id");
+    // Non-ASCII characters all become `_`, even without insert helpers.
+    let msg = error(&[table("users", &["é", "ü"])], CodegenOptions::new().insert_helpers(false));
+    assert!(msg.ends_with("would both become the field _"), "{}", msg);
+
+    let msg = error(&[table("order_items", &["id"]), table("OrderItems", &["id"])], CodegenOptions::new());
+    assert_eq!(msg, "Tables 'order_items' and 'OrderItems' would both generate the type OrderItems");
+    // `New<Table>` can clash with a table whose name starts with "new".
+    let schemas = [table("users", &["id"]), table("new_users", &["id"])];
+    let msg = error(&schemas, CodegenOptions::new());
+    assert_eq!(msg, "Tables 'users' and 'new_users' would both generate the type NewUsers");
+    assert!(maazdb_codegen::generate(&schemas, &CodegenOptions::new().insert_helpers(false)).is_ok());
+}
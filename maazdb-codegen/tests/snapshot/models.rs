@@ -0,0 +1,93 @@
+// Generated by maazdb-codegen. Do not edit; regenerate after changing the schema.
+
+/// A row of `counters`.
+#[derive(Debug, Clone, PartialEq, maazdb_rs::FromRow)]
+pub struct Counters {
+    /// `SERIAL`, primary key
+    pub id: i64,
+}
+
+/// A row of `order_items`.
+#[derive(Debug, Clone, PartialEq, maazdb_rs::FromRow)]
+pub struct OrderItems {
+    /// `SERIAL`, primary key
+    pub id: i64,
+    /// `INT`, references `users(id)`
+    pub user_id: i64,
+    /// `DOUBLE`
+    pub price: f64,
+    /// `TEXT`
+    #[maazdb(rename = "type")]
+    pub type_: Option<String>,
+    /// `BLOB`
+    pub receipt: Option<Vec<u8>>,
+}
+
+/// A new row for `order_items`. `SERIAL` columns are left to the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewOrderItems {
+    /// `INT`, references `users(id)`
+    pub user_id: i64,
+    /// `DOUBLE`
+    pub price: f64,
+    /// `TEXT`
+    pub type_: Option<String>,
+    /// `BLOB`
+    pub receipt: Option<Vec<u8>>,
+}
+
+impl NewOrderItems {
+    pub const INSERT: &'static str = "INSERT INTO order_items (user_id, price, type, receipt) VALUES ($1, $2, $3, $4);";
+
+    pub fn insert(&self, db: &mut maazdb_rs::MaazDB) -> Result<String, maazdb_rs::MaazDBError> {
+        db.query_with(Self::INSERT, &[&self.user_id, &self.price, &self.type_, &self.receipt])
+    }
+
+    pub async fn insert_async(&self, db: &mut maazdb_rs::AsyncMaazDB) -> Result<String, maazdb_rs::MaazDBError> {
+        db.query_with(Self::INSERT, &[&self.user_id, &self.price, &self.type_, &self.receipt]).await
+    }
+}
+
+/// A row of `users`.
+#[derive(Debug, Clone, PartialEq, maazdb_rs::FromRow)]
+pub struct Users {
+    /// `SERIAL`, primary key
+    pub id: i64,
+    /// `TEXT`
+    pub name: String,
+    /// `TEXT`
+    pub email: Option<String>,
+    /// `BOOL`, default `TRUE`
+    pub active: bool,
+    /// `TIMESTAMP`
+    pub created: Option<chrono::NaiveDateTime>,
+    /// `UUID`
+    pub uuid: uuid::Uuid,
+}
+
+/// A new row for `users`. `SERIAL` columns are left to the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewUsers {
+    /// `TEXT`
+    pub name: String,
+    /// `TEXT`
+    pub email: Option<String>,
+    /// `BOOL`, default `TRUE`
+    pub active: bool,
+    /// `TIMESTAMP`
+    pub created: Option<chrono::NaiveDateTime>,
+    /// `UUID`
+    pub uuid: uuid::Uuid,
+}
+
+impl NewUsers {
+    pub const INSERT: &'static str = "INSERT INTO users (name, email, active, created, uuid) VALUES ($1, $2, $3, $4, $5);";
+
+    pub fn insert(&self, db: &mut maazdb_rs::MaazDB) -> Result<String, maazdb_rs::MaazDBError> {
+        db.query_with(Self::INSERT, &[&self.name, &self.email, &self.active, &self.created, &self.uuid])
+    }
+
+    pub async fn insert_async(&self, db: &mut maazdb_rs::AsyncMaazDB) -> Result<String, maazdb_rs::MaazDBError> {
+        db.query_with(Self::INSERT, &[&self.name, &self.email, &self.active, &self.created, &self.uuid]).await
+    }
+}
@@ -0,0 +1,134 @@
+//! Procedural macros for `maazdb-rs`. Use them through the re-exports in `maazdb_rs`.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements `maazdb_rs::FromRow` by looking up each field by column name.
+///
+/// Use `#[maazdb(rename = "col")]` on a field when the column name differs.
+#[proc_macro_derive(FromRow, attributes(maazdb))]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_from_row(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_from_row(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new_spanned(input, "FromRow can only be derived for structs")),
+    };
+
+    let body = match fields {
+        Fields::Named(named) => {
+            let mut inits = Vec::new();
+            for field in &named.named {
+                let ident = field.ident.as_ref().unwrap();
+                let column = column_name(field)?.unwrap_or_else(|| ident.to_string());
+                inits.push(quote! { #ident: row.try_get(#column)? });
+            }
+            quote! { Self { #(#inits),* } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let inits = (0..unnamed.unnamed.len()).map(|i| quote! { row.try_get(#i)? });
+            quote! { Self(#(#inits),*) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::maazdb_rs::FromRow for #name #ty_generics #where_clause {
+            fn from_row(row: &::maazdb_rs::Row) -> ::std::result::Result<Self, ::maazdb_rs::MaazDBError> {
+                ::std::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+fn column_name(field: &syn::Field) -> syn::Result<Option<String>> {
+    let mut rename = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("maazdb") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let lit: LitStr = meta.value()?.parse()?;
+                rename = Some(lit.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported maazdb attribute"))
+            }
+        })?;
+    }
+    Ok(rename)
+}
+
+/// Embeds the `V<version>__<name>.sql` / `U<version>__<name>.sql` files of a directory as a
+/// `maazdb_rs::migrate::Migrator`. The path is relative to the crate's `Cargo.toml`.
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    let dir = parse_macro_input!(input as LitStr);
+    match expand_embed_migrations(&dir) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_embed_migrations(dir: &LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let root = std::env::var("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+    let path = root.join(dir.value());
+    let error = |msg: String| syn::Error::new_spanned(dir, msg);
+
+    let entries = std::fs::read_dir(&path).map_err(|e| error(format!("cannot read {}: {}", path.display(), e)))?;
+    let mut ups = BTreeMap::new();
+    let mut downs = BTreeMap::new();
+    for entry in entries {
+        let file = entry.map_err(|e| error(e.to_string()))?.path();
+        let Some(file_name) = file.file_name().and_then(|n| n.to_str()) else { continue };
+        if !file.is_file() || !file_name.ends_with(".sql") {
+            continue;
+        }
+        let (kind, version, name) = parse_migration_name(file_name)
+            .ok_or_else(|| error(format!("'{}' is not named V<version>__<name>.sql or U<version>__<name>.sql", file_name)))?;
+        let target = if kind == 'V' { &mut ups } else { &mut downs };
+        if target.insert(version, (name.to_string(), file.display().to_string())).is_some() {
+            return Err(error(format!("more than one {}{:03} script", kind, version)));
+        }
+    }
+
+    let mut migrations = Vec::new();
+    for (version, (name, up)) in ups {
+        let down = downs.remove(&version).map(|(_, down)| quote! { .down(::std::include_str!(#down)) });
+        migrations.push(quote! {
+            ::maazdb_rs::migrate::Migration::new(#version, #name, ::std::include_str!(#up)) #down
+        });
+    }
+    if let Some((version, (name, _))) = downs.into_iter().next() {
+        return Err(error(format!("U{:03}__{}.sql has no matching V{:03} migration", version, name, version)));
+    }
+
+    Ok(quote! {
+        ::maazdb_rs::migrate::Migrator::new([#(#migrations),*])
+    })
+}
+
+/// Same naming rules as `maazdb_rs::migrate::Migrator::from_dir`.
+fn parse_migration_name(file_name: &str) -> Option<(char, u32, &str)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    let kind = stem.chars().next().filter(|c| *c == 'V' || *c == 'U')?;
+    let (version, name) = stem[1..].split_once("__")?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) || name.is_empty() {
+        return None;
+    }
+    Some((kind, version.parse().ok()?, name))
+}
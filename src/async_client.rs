@@ -0,0 +1,282 @@
+use std::future::Future;
+use std::task::{Context, Waker};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+use crate::reconnect::{self, is_read_only_bytes};
+use crate::result::no_rows_error;
+use crate::stream::{self, RowStream};
+use crate::protocol::{self, PACKET_AUTH_OK, PACKET_HANDSHAKE, PACKET_QUERY, PING_SQL};
+use crate::tls::{self, TlsMode};
+use crate::{bind, check_response, decode_utf8, state_error, transaction_error, ConnectionState, ConnectOptions, FromRow, MaazDBError, QueryResult, ToSql};
+
+/// The asynchronous MaazDB client, built on `tokio` and `tokio-rustls`.
+pub struct AsyncMaazDB {
+    pub(crate) stream: TlsStream<TcpStream>,
+    pub(crate) read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    pub(crate) state: ConnectionState,
+    pub(crate) options: ConnectOptions,
+    /// Database most recently selected with `USE`, restored after a reconnect.
+    pub(crate) database: Option<String>,
+    /// Set between a `BEGIN` and the `COMMIT`/`ROLLBACK` sent on this connection, when
+    /// reconnecting would lose the transaction.
+    pub(crate) in_transaction: bool,
+}
+
+impl AsyncMaazDB {
+    /// Connects to a MaazDB server instance without verifying its certificate, like [`crate::MaazDB::connect`].
+    pub async fn connect(host: &str, port: u16, user: &str, pass: &str) -> Result<Self, MaazDBError> {
+        let opts = ConnectOptions::new(host, port, user, pass).tls_mode(TlsMode::NoVerify);
+        Self::connect_with(&opts).await
+    }
+
+    /// Connects using the given [`ConnectOptions`].
+    pub async fn connect_with(opts: &ConnectOptions) -> Result<Self, MaazDBError> {
+        let connect_timeout = opts.connect_timeout;
+        let sock = with_timeout(connect_timeout, TcpStream::connect(opts.addr())).await?;
+
+        let server_name = tls::server_name(&opts.host)?;
+        let connector = TlsConnector::from(opts.tls.client_config(&opts.host, opts.port)?);
+        let mut stream = with_timeout(connect_timeout, connector.connect(server_name, sock)).await
+            .map_err(handshake_error)?;
+
+        let payload = protocol::handshake_payload(&opts.user, &opts.password, opts.application_name.as_deref());
+        // Read even if the write failed, so a TLS alert (e.g. a rejected client certificate)
+        // is reported instead of a broken pipe. See `MaazDB::connect_with`.
+        let (ptype, msg) = with_timeout(connect_timeout, async {
+            let sent = protocol::send_packet_async(&mut stream, PACKET_HANDSHAKE, payload.as_bytes()).await;
+            let reply = protocol::read_packet_async(&mut stream, opts.max_packet_size).await;
+            match (sent, reply) {
+                (Err(send_err), Err(read_err)) if !tls::is_tls_error(&read_err) => Err(send_err),
+                (Err(send_err), Ok(_)) => Err(send_err),
+                (_, reply) => reply,
+            }
+        }).await.map_err(handshake_error)?;
+
+        if ptype != PACKET_AUTH_OK {
+            return Err(MaazDBError::AuthError(decode_utf8(msg)?));
+        }
+
+        let mut db = AsyncMaazDB {
+            stream,
+            read_timeout: opts.read_timeout,
+            write_timeout: opts.write_timeout,
+            state: ConnectionState::Connecting,
+            options: opts.clone(),
+            database: None,
+            in_transaction: false,
+        };
+        if let Some(database) = &opts.database {
+            db.send_query(format!("USE {};", database).as_bytes()).await?;
+        }
+        db.state = ConnectionState::Ready;
+        Ok(db)
+    }
+
+    /// Replaces the stream with a fresh connection, re-selecting the current database.
+    ///
+    /// Like [`crate::MaazDB::reconnect`], fails while a transaction is open on a working
+    /// connection. After the connection breaks, this is the way out of the transaction,
+    /// since it is never reconnected automatically.
+    pub async fn reconnect(&mut self) -> Result<(), MaazDBError> {
+        if self.in_transaction && self.state.can_send() {
+            return Err(transaction_error("reconnect"));
+        }
+        let database = self.database.clone();
+        let mut fresh = Self::connect_with(&self.options).await?;
+        if let Some(database) = database.filter(|db| fresh.database.as_ref() != Some(db)) {
+            fresh.send_query(format!("USE {};", database).as_bytes()).await?;
+        }
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Runs a statement and returns the raw server response.
+    pub async fn query(&mut self, sql: &str) -> Result<String, MaazDBError> {
+        let (_, payload) = self.round_trip(sql.as_bytes()).await?;
+        decode_utf8(payload)
+    }
+
+    /// Runs a statement given as raw bytes and returns the raw response payload, like
+    /// [`crate::MaazDB::query_bytes`].
+    pub async fn query_bytes(&mut self, sql: &[u8]) -> Result<Bytes, MaazDBError> {
+        let (_, payload) = self.round_trip(sql).await?;
+        Ok(payload)
+    }
+
+    /// Runs a statement and returns a typed [`QueryResult`].
+    pub async fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError> {
+        let (ptype, payload) = self.round_trip(sql.as_bytes()).await?;
+        QueryResult::from_packet(ptype, decode_utf8(payload)?)
+    }
+
+    /// Binds `params` into the `$1`/`?` placeholders of `sql`, then runs it like [`AsyncMaazDB::query`].
+    pub async fn query_with(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<String, MaazDBError> {
+        let sql = bind_sync(sql, params)?;
+        self.query(&sql).await
+    }
+
+    /// Binds `params` into the placeholders of `sql`, then runs it like [`AsyncMaazDB::execute`].
+    pub async fn execute_with(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<QueryResult, MaazDBError> {
+        let sql = bind_sync(sql, params)?;
+        self.execute(&sql).await
+    }
+
+    /// Runs a query and decodes every returned row into `T`.
+    pub async fn query_as<T: FromRow>(&mut self, sql: &str) -> Result<Vec<T>, MaazDBError> {
+        self.execute(sql).await?.expect_rows()?.decode()
+    }
+
+    /// Round-trips a trivial query to check that the server is responding.
+    pub async fn ping(&mut self) -> Result<(), MaazDBError> {
+        self.query(PING_SQL).await.map(|_| ())
+    }
+
+    /// Whether the connection is ready for the next statement, like [`crate::MaazDB::is_alive`].
+    ///
+    /// A closed socket is only noticed if the runtime has already seen it become readable.
+    pub fn is_alive(&mut self) -> bool {
+        if self.state == ConnectionState::Ready {
+            let mut buf = [0u8; 1];
+            let mut cx = Context::from_waker(Waker::noop());
+            if self.stream.get_ref().0.poll_peek(&mut cx, &mut ReadBuf::new(&mut buf)).is_ready() {
+                self.state = ConnectionState::Broken;
+            }
+        }
+        self.state == ConnectionState::Ready
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Runs a query and returns its rows as a [`futures_util::Stream`], decoded as they arrive,
+    /// like [`crate::MaazDB::query_iter`].
+    pub async fn query_stream(&mut self, sql: &str) -> Result<RowStream<'_>, MaazDBError> {
+        if self.state == ConnectionState::InQuery {
+            self.state = ConnectionState::Broken;
+        }
+        if self.state == ConnectionState::Broken && self.options.reconnect.is_some() && !self.in_transaction {
+            self.reconnect().await?;
+        }
+        if !self.state.can_send() {
+            return Err(state_error(self.state));
+        }
+
+        self.state = ConnectionState::InQuery;
+        let max = self.options.max_packet_size;
+        let header = async {
+            with_timeout(self.write_timeout, protocol::send_packet_async(&mut self.stream, PACKET_QUERY, sql.as_bytes())).await?;
+            with_timeout(self.read_timeout, protocol::read_header_async(&mut self.stream, usize::MAX)).await
+        }.await;
+        let (ptype, len) = header.inspect_err(|_| self.state = ConnectionState::Broken)?;
+        if stream::is_data(ptype) {
+            return Ok(RowStream::new(self, ptype, len));
+        }
+
+        // A status message or an error: small, so read it whole.
+        if len > max {
+            self.state = ConnectionState::Broken;
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Packet too large").into());
+        }
+        let mut payload = vec![0u8; len];
+        with_timeout(self.read_timeout, self.stream.read_exact(&mut payload)).await
+            .inspect_err(|_| self.state = ConnectionState::Broken)?;
+        self.state = ConnectionState::Ready;
+        let (_, msg) = check_response(ptype, payload.into(), sql.as_bytes())?;
+        Err(no_rows_error(&decode_utf8(msg)?))
+    }
+
+    pub async fn close(&mut self) {
+        let _ = self.stream.shutdown().await;
+        self.state = ConnectionState::Closed;
+    }
+
+    /// Sends `sql`, applying the reconnect policy like [`crate::MaazDB`] does.
+    async fn round_trip(&mut self, sql: &[u8]) -> Result<(u8, Bytes), MaazDBError> {
+        // A query future dropped mid-flight leaves its reply unread.
+        if self.state == ConnectionState::InQuery {
+            self.state = ConnectionState::Broken;
+        }
+        let Some(policy) = self.options.reconnect.clone()
+            .filter(|_| self.state != ConnectionState::Closed && !self.in_transaction) else {
+            return self.send_query(sql).await;
+        };
+        let mut attempt = 0;
+        loop {
+            if self.state == ConnectionState::Broken {
+                match self.reconnect().await {
+                    Err(MaazDBError::IoError(_)) if attempt < policy.max_retries => {
+                        tokio::time::sleep(policy.backoff(attempt)).await;
+                        attempt += 1;
+                        continue;
+                    }
+                    reconnected => reconnected?,
+                }
+            }
+            match self.send_query(sql).await {
+                Err(MaazDBError::IoError(_)) if attempt < policy.max_retries && is_read_only_bytes(sql) => {
+                    tokio::time::sleep(policy.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends `sql` once on the current stream.
+    async fn send_query(&mut self, sql: &[u8]) -> Result<(u8, Bytes), MaazDBError> {
+        let previous = self.state;
+        if !previous.can_send() {
+            return Err(state_error(previous));
+        }
+        self.state = ConnectionState::InQuery;
+        let reply = async {
+            with_timeout(self.write_timeout, protocol::send_packet_async(&mut self.stream, PACKET_QUERY, sql)).await?;
+            with_timeout(self.read_timeout, protocol::read_packet_async(&mut self.stream, self.options.max_packet_size)).await
+        }.await;
+        let (ptype, payload) = reply.inspect_err(|_| self.state = ConnectionState::Broken)?;
+        self.state = previous;
+        let response = check_response(ptype, payload.into(), sql)?;
+        if let Ok(sql) = std::str::from_utf8(sql) {
+            if let Some(database) = reconnect::use_target(sql) {
+                self.database = Some(database.to_string());
+            }
+            if let Some(open) = reconnect::transaction_change(sql) {
+                self.in_transaction = open;
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Binds parameters that are `Sync`, so the calling future stays `Send`.
+fn bind_sync(sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<String, MaazDBError> {
+    let params: Vec<&dyn ToSql> = params.iter().map(|p| *p as &dyn ToSql).collect();
+    bind(sql, &params)
+}
+
+/// Runs `fut` with an optional time limit, reporting expiry as a `TimedOut` IO error.
+pub(crate) async fn with_timeout<T>(limit: Option<Duration>, fut: impl Future<Output = std::io::Result<T>>) -> Result<T, MaazDBError> {
+    let Some(limit) = limit else {
+        return Ok(fut.await?);
+    };
+    match tokio::time::timeout(limit, fut).await {
+        Ok(res) => Ok(res?),
+        Err(_) => Err(MaazDBError::IoError(std::io::Error::new(std::io::ErrorKind::TimedOut, "Operation timed out"))),
+    }
+}
+
+/// Re-maps an IO error from the TLS/auth handshake to the TLS error that caused it.
+fn handshake_error(err: MaazDBError) -> MaazDBError {
+    match err {
+        MaazDBError::IoError(e) => tls::handshake_error(e),
+        other => other,
+    }
+}
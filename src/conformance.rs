@@ -0,0 +1,337 @@
+//! A SQL conformance suite for MaazDB servers.
+//!
+//! [`run`] walks through the statements the example SQL suites relied on (databases, tables,
+//! `SERIAL` keys, filtering, ordering, paging, updates, deletes, foreign keys, aggregates and
+//! catalog commands) and checks the typed results. It stops at the first mismatch, since
+//! every case builds on the state left by the previous ones.
+//!
+//! [`run_extended`] is opt-in: it checks `BEGIN`/`ROLLBACK`/`COMMIT` and error replies,
+//! which the examples never exercised.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use maazdb_rs::{conformance, MaazDB};
+//!
+//! let mut db = MaazDB::connect("127.0.0.1", 8888, "admin", "admin")?;
+//! conformance::run(&mut db)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Both suites work in their own database, [`DATABASE`], which they drop before and after.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::{MaazDB, MaazDBError, PooledConnection, QueryResult, ResultSet, Transaction, Value};
+
+/// The scratch database the suite creates, and drops again when it finishes.
+pub const DATABASE: &str = "maazdb_rs_conformance";
+
+/// A connection the suite can run statements on.
+pub trait Client {
+    fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError>;
+}
+
+impl Client for MaazDB {
+    fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError> {
+        MaazDB::execute(self, sql)
+    }
+}
+
+impl Client for Transaction<'_> {
+    fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError> {
+        MaazDB::execute(self, sql)
+    }
+}
+
+impl Client for PooledConnection {
+    fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError> {
+        MaazDB::execute(self, sql)
+    }
+}
+
+/// The first case that did not behave as expected.
+#[derive(Debug)]
+pub struct Failure {
+    /// Name of the failing case, e.g. `"ORDER BY"`.
+    pub case: &'static str,
+    /// The statement whose result was wrong.
+    pub statement: String,
+    /// What was expected and what came back.
+    pub reason: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}\n  statement: {}", self.case, self.reason, self.statement)
+    }
+}
+
+impl Error for Failure {}
+
+/// Runs the whole suite, returning the number of statements checked.
+pub fn run<C: Client + ?Sized>(client: &mut C) -> Result<usize, Failure> {
+    let mut s = Suite { client, case: "setup", statements: 0 };
+    s.create_database()?;
+
+    s.case("CREATE TABLE with SERIAL primary key");
+    s.command("CREATE TABLE users (
+        id SERIAL PRIMARY KEY,
+        name TEXT,
+        age INT,
+        salary DOUBLE,
+        active BOOL,
+        created TIMESTAMP,
+        uuid UUID
+    );")?;
+
+    s.case("INSERT");
+    for insert in [
+        "INSERT INTO users (name, age, salary, active, created, uuid) VALUES ('Alice', 30, 50000.50, TRUE, '2024-01-15 10:30:00', '550e8400-e29b-41d4-a716-446655440000');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Bob', 25, 45000.75, FALSE, '550e8400-e29b-41d4-a716-446655440001');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Charlie', 35, 60000.00, TRUE, '550e8400-e29b-41d4-a716-446655440002');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('David', 40, 70000.00, FALSE, '550e8400-e29b-41d4-a716-446655440003');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Eve', 20, 30000.00, TRUE, '550e8400-e29b-41d4-a716-446655440004');",
+    ] {
+        s.command(insert)?;
+    }
+
+    s.case("SELECT *");
+    let sql = "SELECT * FROM users;";
+    let all = s.rows(sql)?;
+    s.check(sql, all.columns() == ["id", "name", "age", "salary", "active", "created", "uuid"],
+        || format!("columns {:?}", all.columns()))?;
+    s.check(sql, all.len() == 5, || format!("{} rows, expected 5", all.len()))?;
+
+    s.case("SERIAL values");
+    s.column("SELECT id FROM users ORDER BY id;", &[Value::Int(1), Value::Int(2), Value::Int(3), Value::Int(4), Value::Int(5)])?;
+
+    s.case("SELECT columns");
+    let sql = "SELECT name, age FROM users;";
+    let rs = s.rows(sql)?;
+    s.check(sql, rs.columns() == ["name", "age"], || format!("columns {:?}", rs.columns()))?;
+
+    s.case("WHERE =");
+    s.column("SELECT name FROM users WHERE name = 'Alice';", &[text("Alice")])?;
+    s.case("WHERE >");
+    s.count("SELECT * FROM users WHERE age > 25;", 3)?;
+    s.case("WHERE AND");
+    s.count("SELECT * FROM users WHERE age > 25 AND active = TRUE;", 2)?;
+    s.case("WHERE with parentheses and OR");
+    s.count("SELECT * FROM users WHERE (age > 20 AND active = TRUE) OR salary > 50000;", 3)?;
+    s.case("primary key lookup");
+    s.column("SELECT name FROM users WHERE id = 1;", &[text("Alice")])?;
+
+    s.case("ORDER BY");
+    s.column("SELECT name FROM users ORDER BY age DESC;",
+        &[text("David"), text("Charlie"), text("Alice"), text("Bob"), text("Eve")])?;
+    s.case("LIMIT and OFFSET");
+    s.column("SELECT id FROM users ORDER BY id LIMIT 2 OFFSET 1;", &[Value::Int(2), Value::Int(3)])?;
+
+    s.case("expression SELECT");
+    let sql = "SELECT 1 + 1, 'Hello', TRUE;";
+    let rs = s.rows(sql)?;
+    let row = rs.get(0).map(|row| row.values());
+    s.check(sql, row == Some(&[Value::Int(2), text("Hello"), Value::Bool(true)][..]),
+        || format!("got {:?}", row))?;
+
+    s.case("UPDATE");
+    s.command("UPDATE users SET salary = 55000.00 WHERE name = 'Alice';")?;
+    s.number("SELECT salary FROM users WHERE name = 'Alice';", 55000.0)?;
+
+    s.case("DELETE");
+    s.command("DELETE FROM users WHERE name = 'Charlie';")?;
+    s.number("SELECT COUNT(*) FROM users;", 4.0)?;
+
+    s.case("FOREIGN KEY");
+    s.command("CREATE TABLE orders (
+        order_id SERIAL PRIMARY KEY,
+        user_id INT,
+        amount DOUBLE,
+        FOREIGN KEY (user_id) REFERENCES users(id)
+    );")?;
+    s.command("INSERT INTO orders (user_id, amount) VALUES (1, 100.50);")?;
+    s.rejected("INSERT INTO orders (user_id, amount) VALUES (999, 200.00);", MaazDBError::is_constraint_violation)?;
+
+    s.case("SHOW TABLES");
+    let sql = "SHOW TABLES;";
+    let rs = s.rows(sql)?;
+    let tables: Vec<&Value> = rs.iter().filter_map(|row| row.get(0)).collect();
+    s.check(sql, tables.contains(&&text("users")) && tables.contains(&&text("orders")),
+        || format!("got {:?}", tables))?;
+
+    s.case("DESCRIBE");
+    s.count("DESCRIBE users;", 7)?;
+
+    s.case("CREATE USER");
+    s.command_or_exists("CREATE USER conformance_user PASSWORD 'secret123';")?;
+
+    s.case("parameterized INSERT");
+    s.command("CREATE TABLE perf_test (id SERIAL PRIMARY KEY, data TEXT);")?;
+    for i in 1..=10 {
+        let template = "INSERT INTO perf_test (data) VALUES ($1);";
+        let sql = crate::bind(template, &[&format!("O'Brien {}", i)]).map_err(|e| s.fail(template, e.to_string()))?;
+        s.command(&sql)?;
+    }
+    s.column("SELECT data FROM perf_test WHERE id = 10;", &[text("O'Brien 10")])?;
+
+    s.case("aggregates");
+    s.number("SELECT COUNT(*) FROM users;", 4.0)?;
+    s.number("SELECT SUM(salary) FROM users;", 200000.75)?;
+    s.number("SELECT AVG(age) FROM users;", 28.75)?;
+
+    s.case("DROP TABLE");
+    s.command("DROP TABLE perf_test;")?;
+    let sql = "SHOW TABLES;";
+    let rs = s.rows(sql)?;
+    s.check(sql, rs.iter().all(|row| row.get(0) != Some(&text("perf_test"))), || "perf_test still listed".to_string())?;
+
+    s.case("SHOW DATABASES");
+    let sql = "SHOW DATABASES;";
+    let rs = s.rows(sql)?;
+    s.check(sql, rs.iter().any(|row| row.get(0) == Some(&text(DATABASE))), || format!("{} not listed", DATABASE))?;
+
+    s.case("BACKUP");
+    s.command("BACKUP 'test_backup';")?;
+
+    s.case("multiple row INSERT");
+    s.command("CREATE TABLE batch_test (id SERIAL PRIMARY KEY, name TEXT, score INT);")?;
+    s.command("INSERT INTO batch_test (name, score) VALUES ('Player1', 100), ('Player2', 200), ('Player3', 300);")?;
+    s.count("SELECT * FROM batch_test;", 3)?;
+
+    s.case("INSERT INTO ... SELECT");
+    s.command("CREATE TABLE archive_test (id SERIAL PRIMARY KEY, name TEXT, score INT);")?;
+    s.command("INSERT INTO archive_test (name, score) SELECT name, score FROM batch_test WHERE score > 150;")?;
+    s.column("SELECT name FROM archive_test ORDER BY id;", &[text("Player2"), text("Player3")])?;
+
+    s.drop_database()?;
+    Ok(s.statements)
+}
+
+/// Runs the opt-in cases: transactions and error replies. Returns the number of statements checked.
+pub fn run_extended<C: Client + ?Sized>(client: &mut C) -> Result<usize, Failure> {
+    let mut s = Suite { client, case: "setup", statements: 0 };
+    s.create_database()?;
+    s.command("CREATE TABLE batch_test (id SERIAL PRIMARY KEY, name TEXT, score INT);")?;
+    s.command("INSERT INTO batch_test (name, score) VALUES ('Player1', 100), ('Player2', 200), ('Player3', 300);")?;
+
+    s.case("ROLLBACK");
+    s.command("BEGIN;")?;
+    s.command("DELETE FROM batch_test;")?;
+    s.command("ROLLBACK;")?;
+    s.count("SELECT * FROM batch_test;", 3)?;
+
+    s.case("COMMIT");
+    s.command("BEGIN;")?;
+    s.command("DELETE FROM batch_test WHERE score = 100;")?;
+    s.command("COMMIT;")?;
+    s.count("SELECT * FROM batch_test;", 2)?;
+
+    s.case("errors");
+    s.rejected("SELECT * FROM no_such_table;", |e| e.as_server_error().is_some())?;
+
+    s.drop_database()?;
+    Ok(s.statements)
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+struct Suite<'c, C: Client + ?Sized> {
+    client: &'c mut C,
+    case: &'static str,
+    statements: usize,
+}
+
+impl<C: Client + ?Sized> Suite<'_, C> {
+    fn case(&mut self, name: &'static str) {
+        self.case = name;
+    }
+
+    /// Starts over in an empty [`DATABASE`].
+    fn create_database(&mut self) -> Result<(), Failure> {
+        self.case("CREATE DATABASE");
+        self.command(&format!("DROP DATABASE IF EXISTS {};", DATABASE))?;
+        self.command(&format!("CREATE DATABASE {};", DATABASE))?;
+        self.command(&format!("USE {};", DATABASE))
+    }
+
+    fn drop_database(&mut self) -> Result<(), Failure> {
+        self.case("DROP DATABASE");
+        self.command("USE system;")?;
+        self.command(&format!("DROP DATABASE {};", DATABASE))?;
+        let sql = "SHOW DATABASES;";
+        let rs = self.rows(sql)?;
+        self.check(sql, rs.iter().all(|row| row.get(0) != Some(&text(DATABASE))), || format!("{} still listed", DATABASE))
+    }
+
+    fn fail(&self, sql: &str, reason: String) -> Failure {
+        Failure { case: self.case, statement: sql.to_string(), reason }
+    }
+
+    fn check(&self, sql: &str, ok: bool, reason: impl FnOnce() -> String) -> Result<(), Failure> {
+        if ok { Ok(()) } else { Err(self.fail(sql, reason())) }
+    }
+
+    fn execute(&mut self, sql: &str) -> Result<QueryResult, Failure> {
+        self.statements += 1;
+        self.client.execute(sql).map_err(|e| self.fail(sql, format!("failed: {}", e)))
+    }
+
+    /// A statement that must succeed; any reply is accepted.
+    fn command(&mut self, sql: &str) -> Result<(), Failure> {
+        self.execute(sql).map(|_| ())
+    }
+
+    /// Like [`Suite::command`], but tolerates the object being left over from an earlier run.
+    fn command_or_exists(&mut self, sql: &str) -> Result<(), Failure> {
+        self.statements += 1;
+        match self.client.execute(sql) {
+            Ok(_) => Ok(()),
+            Err(e) if e.as_server_error().is_some_and(|e| e.category == crate::ErrorCategory::AlreadyExists) => Ok(()),
+            Err(e) => Err(self.fail(sql, format!("failed: {}", e))),
+        }
+    }
+
+    fn rows(&mut self, sql: &str) -> Result<ResultSet, Failure> {
+        match self.execute(sql)? {
+            QueryResult::Rows(rs) => Ok(rs),
+            QueryResult::CommandComplete(msg) => Err(self.fail(sql, format!("expected rows, got message '{}'", msg))),
+        }
+    }
+
+    fn count(&mut self, sql: &str, expected: usize) -> Result<(), Failure> {
+        let rs = self.rows(sql)?;
+        self.check(sql, rs.len() == expected, || format!("{} rows, expected {}", rs.len(), expected))
+    }
+
+    /// Checks the first column of every row, in order.
+    fn column(&mut self, sql: &str, expected: &[Value]) -> Result<(), Failure> {
+        let rs = self.rows(sql)?;
+        let got: Vec<Value> = rs.iter().map(|row| row.get(0).cloned().unwrap_or(Value::Null)).collect();
+        self.check(sql, got == expected, || format!("got {:?}, expected {:?}", got, expected))
+    }
+
+    /// Checks a single numeric cell. Integers, doubles and numeric text all count.
+    fn number(&mut self, sql: &str, expected: f64) -> Result<(), Failure> {
+        let rs = self.rows(sql)?;
+        let got = rs.get(0).map(|row| row.try_get::<f64, _>(0));
+        match got {
+            Some(Ok(n)) => self.check(sql, (n - expected).abs() < 0.001, || format!("got {}, expected {}", n, expected)),
+            Some(Err(e)) => Err(self.fail(sql, e.to_string())),
+            None => Err(self.fail(sql, "no rows".to_string())),
+        }
+    }
+
+    /// A statement that must fail with an error matching `expected`.
+    fn rejected(&mut self, sql: &str, expected: fn(&MaazDBError) -> bool) -> Result<(), Failure> {
+        self.statements += 1;
+        match self.client.execute(sql) {
+            Err(e) if expected(&e) => Ok(()),
+            Err(e) => Err(self.fail(sql, format!("failed for the wrong reason: {}", e))),
+            Ok(reply) => Err(self.fail(sql, format!("succeeded, expected an error: {:?}", reply))),
+        }
+    }
+}
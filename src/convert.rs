@@ -0,0 +1,241 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{MaazDBError, ResultSet, Row, Value};
+
+/// Accepted layouts for `TIMESTAMP` columns.
+const TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+];
+
+/// Conversion from a single server cell into a Rust value.
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError>;
+}
+
+/// Conversion from a whole row into a Rust value.
+///
+/// Usually derived with `#[derive(FromRow)]`, which looks up every field by column name.
+pub trait FromRow: Sized {
+    fn from_row(row: &Row) -> Result<Self, MaazDBError>;
+}
+
+/// Something that can locate a column in a [`Row`]: a position or a column name.
+pub trait RowIndex {
+    fn index_in(&self, row: &Row) -> Result<usize, MaazDBError>;
+}
+
+impl RowIndex for usize {
+    fn index_in(&self, row: &Row) -> Result<usize, MaazDBError> {
+        if *self < row.len() {
+            Ok(*self)
+        } else {
+            Err(MaazDBError::ColumnNotFound(format!("index {} (row has {} columns)", self, row.len())))
+        }
+    }
+}
+
+impl RowIndex for &str {
+    fn index_in(&self, row: &Row) -> Result<usize, MaazDBError> {
+        row.column_index(self).ok_or_else(|| MaazDBError::ColumnNotFound(self.to_string()))
+    }
+}
+
+impl Row {
+    /// Decodes the cell at `idx` (a position or a column name) into `T`.
+    pub fn try_get<T: FromValue, I: RowIndex>(&self, idx: I) -> Result<T, MaazDBError> {
+        let i = idx.index_in(self)?;
+        T::from_value(&self.values()[i]).map_err(|e| match e {
+            MaazDBError::UnexpectedNull(_) => MaazDBError::UnexpectedNull(self.columns()[i].clone()),
+            MaazDBError::TypeMismatch(msg) => {
+                MaazDBError::TypeMismatch(format!("column '{}': {}", self.columns()[i], msg))
+            }
+            other => other,
+        })
+    }
+}
+
+impl ResultSet {
+    /// Decodes every row into `T`.
+    pub fn decode<T: FromRow>(&self) -> Result<Vec<T>, MaazDBError> {
+        self.iter().map(T::from_row).collect()
+    }
+}
+
+fn mismatch(expected: &str, value: &Value) -> MaazDBError {
+    MaazDBError::TypeMismatch(format!("expected {}, got {:?}", expected, value))
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        Ok(value.clone())
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+// INT and SERIAL columns
+macro_rules! impl_from_value_int {
+    ($($t:ty),*) => {$(
+        impl FromValue for $t {
+            fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+                let wide: i128 = match value {
+                    Value::Null => return Err(MaazDBError::UnexpectedNull(String::new())),
+                    Value::Int(i) => *i as i128,
+                    Value::Text(s) => s.trim().parse().map_err(|_| mismatch("an integer", value))?,
+                    _ => return Err(mismatch("an integer", value)),
+                };
+                <$t>::try_from(wide).map_err(|_| MaazDBError::TypeMismatch(
+                    format!("{} is out of range for {}", wide, stringify!($t))
+                ))
+            }
+        }
+    )*};
+}
+
+impl_from_value_int!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+// DOUBLE columns
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        match value {
+            Value::Null => Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Double(d) => Ok(*d),
+            Value::Int(i) => Ok(*i as f64),
+            Value::Text(s) => s.trim().parse().map_err(|_| mismatch("a number", value)),
+            _ => Err(mismatch("a number", value)),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        f64::from_value(value).map(|d| d as f32)
+    }
+}
+
+// BOOL columns
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        match value {
+            Value::Null => Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Bool(b) => Ok(*b),
+            Value::Int(0) => Ok(false),
+            Value::Int(1) => Ok(true),
+            Value::Text(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "t" | "1" => Ok(true),
+                "false" | "f" | "0" => Ok(false),
+                _ => Err(mismatch("a boolean", value)),
+            },
+            _ => Err(mismatch("a boolean", value)),
+        }
+    }
+}
+
+// TEXT columns (any non-NULL cell has a textual form)
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        match value {
+            Value::Null => Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Text(s) => Ok(s.clone()),
+            other => Ok(other.to_string()),
+        }
+    }
+}
+
+// TIMESTAMP columns
+impl FromValue for NaiveDateTime {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        let s = match value {
+            Value::Null => return Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Text(s) => s.trim(),
+            _ => return Err(mismatch("a timestamp", value)),
+        };
+        TIMESTAMP_FORMATS.iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+            .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+            .ok_or_else(|| mismatch("a timestamp", value))
+    }
+}
+
+impl FromValue for DateTime<Utc> {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        if let Value::Text(s) = value
+            && let Ok(dt) = DateTime::parse_from_rfc3339(s.trim())
+        {
+            return Ok(dt.with_timezone(&Utc));
+        }
+        NaiveDateTime::from_value(value).map(|dt| dt.and_utc())
+    }
+}
+
+// UUID columns
+impl FromValue for Uuid {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        match value {
+            Value::Null => Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Text(s) => Uuid::parse_str(s.trim()).map_err(|_| mismatch("a UUID", value)),
+            _ => Err(mismatch("a UUID", value)),
+        }
+    }
+}
+
+// BLOB/BYTEA columns, sent back as hex text (`DEADBEEF`, `\xDEADBEEF`, `0xDEADBEEF` or `X'DEADBEEF'`)
+impl FromValue for Vec<u8> {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        let s = match value {
+            Value::Null => return Err(MaazDBError::UnexpectedNull(String::new())),
+            Value::Text(s) => s.trim(),
+            _ => return Err(mismatch("hex-encoded bytes", value)),
+        };
+        decode_hex(s).ok_or_else(|| mismatch("hex-encoded bytes", value))
+    }
+}
+
+impl FromValue for bytes::Bytes {
+    fn from_value(value: &Value) -> Result<Self, MaazDBError> {
+        Vec::<u8>::from_value(value).map(bytes::Bytes::from)
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = if let Some(rest) = s.strip_prefix("X'").or_else(|| s.strip_prefix("x'")) {
+        rest.strip_suffix('\'')?
+    } else {
+        s.strip_prefix("\\x").or_else(|| s.strip_prefix("0x")).unwrap_or(s)
+    };
+    if digits.len() % 2 != 0 || !digits.is_ascii() {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+macro_rules! impl_from_row_tuple {
+    ($($t:ident : $i:tt),+) => {
+        impl<$($t: FromValue),+> FromRow for ($($t,)+) {
+            fn from_row(row: &Row) -> Result<Self, MaazDBError> {
+                Ok(($(row.try_get::<$t, usize>($i)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_row_tuple!(A: 0);
+impl_from_row_tuple!(A: 0, B: 1);
+impl_from_row_tuple!(A: 0, B: 1, C: 2);
+impl_from_row_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_from_row_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_from_row_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_from_row_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_from_row_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
@@ -1,21 +1,52 @@
-use std::io::{self, Read, Write};
-use std::net::TcpStream;
-use std::sync::Arc;
-use std::time::Duration;
+use std::io::{self, Read};
+use std::net::{TcpStream, ToSocketAddrs};
 use std::fmt; // <--- NEW IMPORT
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use rustls::ClientConfig;
 
-// --- Protocol Constants ---
-const PACKET_HANDSHAKE: u8 = 0x10;
-const PACKET_AUTH_OK: u8   = 0x11;
-#[allow(dead_code)] // Suppress warning for unused constant
-const PACKET_AUTH_ERR: u8  = 0x12;
-const PACKET_QUERY: u8     = 0x20;
-const PACKET_MSG: u8       = 0x02;
-const PACKET_DATA: u8      = 0x03;
+use bytes::Bytes;
 
-const DRIVER_SIG: &str = "maazdb-rust-driver-v1";
+mod async_client;
+pub mod conformance;
+mod convert;
+pub mod migrate;
+mod options;
+mod paginate;
+mod params;
+mod pin;
+mod pool;
+pub mod protocol;
+pub mod query;
+mod reconnect;
+mod result;
+mod schema;
+mod script;
+mod server_error;
+mod state;
+mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tls;
+mod transaction;
+
+use reconnect::is_read_only_bytes;
+use protocol::{PACKET_AUTH_OK, PACKET_DATA, PACKET_HANDSHAKE, PACKET_MSG, PACKET_QUERY, PING_SQL};
+
+pub use async_client::AsyncMaazDB;
+pub use options::{ConnectOptions, MAAZDB_URL_ENV};
+pub use paginate::{AsyncPaginator, PageMode, Paginator};
+pub use pool::{AsyncPool, AsyncPooledConnection, Pool, PoolOptions, PooledConnection};
+pub use pin::{Fingerprint, FingerprintMismatch};
+pub use tls::{ClientIdentity, TlsMode, TlsOptions, TrustRoots};
+pub use convert::{FromRow, FromValue, RowIndex};
+pub use maazdb_derive::FromRow;
+pub use params::{bind, identifier, ToSql};
+pub use reconnect::ReconnectPolicy;
+pub use result::{QueryResult, ResultSet, Row, Value};
+pub use schema::{ColumnInfo, DataType, ForeignKey, TableSchema};
+pub use script::{split_statements, OnError, ScriptReport, ScriptStatement, StatementOutcome};
+pub use server_error::{ErrorCategory, ServerError};
+pub use state::ConnectionState;
+pub use stream::{RowIter, RowStream};
+pub use transaction::Transaction;
 
 #[derive(Debug)]
 pub enum MaazDBError {
@@ -23,6 +54,37 @@ pub enum MaazDBError {
     AuthError(String),
     ProtocolError(String),
     TlsError(rustls::Error),
+    CertificateError(rustls::Error),
+    ColumnNotFound(String),
+    UnexpectedNull(String),
+    TypeMismatch(String),
+    ParameterError(String),
+    ConfigError(String),
+    PoolTimeout,
+    ServerError(ServerError),
+    /// A text payload was not valid UTF-8. Use `query_bytes` to read it raw.
+    Utf8Error(std::str::Utf8Error),
+    /// Migrations could not be loaded, or disagree with the history in the database.
+    MigrationError(String),
+}
+
+impl MaazDBError {
+    /// Returns the server-reported error, if that is what this is.
+    pub fn as_server_error(&self) -> Option<&ServerError> {
+        match self {
+            MaazDBError::ServerError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a server error that may succeed if the statement is retried.
+    pub fn is_retryable(&self) -> bool {
+        self.as_server_error().is_some_and(ServerError::is_retryable)
+    }
+
+    pub fn is_constraint_violation(&self) -> bool {
+        self.as_server_error().is_some_and(ServerError::is_constraint_violation)
+    }
 }
 
 // --- NEW: Implement Display (Required for std::error::Error) ---
@@ -33,6 +95,16 @@ impl fmt::Display for MaazDBError {
             MaazDBError::AuthError(s) => write!(f, "Authentication Error: {}", s),
             MaazDBError::ProtocolError(s) => write!(f, "Protocol Error: {}", s),
             MaazDBError::TlsError(e) => write!(f, "TLS Error: {}", e),
+            MaazDBError::CertificateError(e) => write!(f, "Certificate Verification Failed: {}", e),
+            MaazDBError::ColumnNotFound(s) => write!(f, "Column Not Found: {}", s),
+            MaazDBError::UnexpectedNull(s) => write!(f, "Unexpected NULL in column '{}'", s),
+            MaazDBError::TypeMismatch(s) => write!(f, "Type Mismatch: {}", s),
+            MaazDBError::ParameterError(s) => write!(f, "Parameter Error: {}", s),
+            MaazDBError::ConfigError(s) => write!(f, "Configuration Error: {}", s),
+            MaazDBError::PoolTimeout => write!(f, "Timed out waiting for a pooled connection"),
+            MaazDBError::ServerError(e) => write!(f, "Server Error: {}", e),
+            MaazDBError::Utf8Error(e) => write!(f, "UTF-8 Error: {}", e),
+            MaazDBError::MigrationError(s) => write!(f, "Migration Error: {}", s),
         }
     }
 }
@@ -49,85 +121,310 @@ impl From<rustls::Error> for MaazDBError {
     fn from(err: rustls::Error) -> Self { MaazDBError::TlsError(err) }
 }
 
-// Helper for self-signed certs
-struct NoCertificateVerification;
-impl rustls::client::ServerCertVerifier for NoCertificateVerification {
-    fn verify_server_cert(&self, _: &rustls::Certificate, _: &[rustls::Certificate], _: &rustls::ServerName, _: &mut dyn Iterator<Item = &[u8]>, _: &[u8], _: std::time::SystemTime) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
-        Ok(rustls::client::ServerCertVerified::assertion())
+/// Maps a query response to `(packet type, payload)`.
+///
+/// Handshake packets are protocol violations; any other reply type carries a server error for `sql`.
+pub(crate) fn check_response(ptype: u8, payload: Bytes, sql: &[u8]) -> Result<(u8, Bytes), MaazDBError> {
+    match ptype {
+        PACKET_MSG | PACKET_DATA => Ok((ptype, payload)),
+        PACKET_HANDSHAKE | PACKET_AUTH_OK => Err(MaazDBError::ProtocolError(format!(
+            "Unexpected packet type 0x{:02X} in reply to a query", ptype
+        ))),
+        // The protocol has no dedicated error type; AUTH_ERR after login, for example,
+        // means the session is not allowed to run the statement.
+        _ => Err(MaazDBError::ServerError(ServerError::new(ptype, decode_utf8(payload)?, &String::from_utf8_lossy(sql)))),
     }
 }
 
+/// Decodes a text payload, rejecting invalid UTF-8 instead of replacing it.
+pub(crate) fn decode_utf8(payload: impl Into<Vec<u8>>) -> Result<String, MaazDBError> {
+    String::from_utf8(payload.into()).map_err(|e| MaazDBError::Utf8Error(e.utf8_error()))
+}
+
 /// The official MaazDB Client.
 pub struct MaazDB {
     stream: rustls::StreamOwned<rustls::ClientConnection, TcpStream>,
-    pub connected: bool,
+    state: ConnectionState,
+    /// Set between `BEGIN` and `COMMIT`/`ROLLBACK`, when reconnecting would lose the transaction.
+    pub(crate) in_transaction: bool,
+    options: ConnectOptions,
+    /// Database most recently selected with `USE`, restored after a reconnect.
+    pub(crate) database: Option<String>,
 }
 
 impl MaazDB {
     /// Connects to a MaazDB server instance.
+    ///
+    /// The server certificate is not verified, which suits local servers with self-signed
+    /// certificates. Use [`MaazDB::connect_with`] to enable verification.
     pub fn connect(host: &str, port: u16, user: &str, pass: &str) -> Result<Self, MaazDBError> {
-        let addr = format!("{}:{}", host, port);
-        
-        let sock = TcpStream::connect(&addr)?;
-        sock.set_read_timeout(Some(Duration::from_secs(10)))?;
-        sock.set_write_timeout(Some(Duration::from_secs(10)))?;
-
-        let config = ClientConfig::builder()
-            .with_safe_defaults()
-            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
-            .with_no_client_auth();
-        
-        let server_name = rustls::ServerName::try_from("localhost").unwrap();
-        let conn = rustls::ClientConnection::new(Arc::new(config), server_name)?;
+        let opts = ConnectOptions::new(host, port, user, pass).tls_mode(TlsMode::NoVerify);
+        Self::connect_with(&opts)
+    }
+
+    /// Connects using the given [`ConnectOptions`].
+    pub fn connect_with(opts: &ConnectOptions) -> Result<Self, MaazDBError> {
+        let sock = Self::open_socket(opts)?;
+        sock.set_read_timeout(opts.connect_timeout)?;
+        sock.set_write_timeout(opts.connect_timeout)?;
+
+        let server_name = tls::server_name(&opts.host)?;
+        let conn = rustls::ClientConnection::new(opts.tls.client_config(&opts.host, opts.port)?, server_name)?;
         let mut stream = rustls::StreamOwned::new(conn, sock);
+        while stream.conn.is_handshaking() {
+            stream.conn.complete_io(&mut stream.sock).map_err(tls::handshake_error)?;
+        }
 
-        let payload = format!("{}\0{}\0{}", user, pass, DRIVER_SIG);
-        Self::send_packet(&mut stream, PACKET_HANDSHAKE, payload.as_bytes())?;
+        // With TLS 1.3 a rejected client certificate only surfaces after the handshake, as an
+        // alert on the first read. If the server already closed the socket, the write fails
+        // first, so read anyway to report the alert instead of a broken pipe.
+        let payload = protocol::handshake_payload(&opts.user, &opts.password, opts.application_name.as_deref());
+        let sent = protocol::send_packet(&mut stream, PACKET_HANDSHAKE, payload.as_bytes());
+        let reply = protocol::read_packet(&mut stream, opts.max_packet_size);
+        let (ptype, msg) = match (sent, reply) {
+            (Err(send_err), Err(read_err)) if !tls::is_tls_error(&read_err) => Err(send_err),
+            (Err(send_err), Ok(_)) => Err(send_err),
+            (_, reply) => reply,
+        }.map_err(tls::handshake_error)?;
 
-        let (ptype, msg) = Self::read_packet(&mut stream)?;
+        if ptype != PACKET_AUTH_OK {
+            return Err(MaazDBError::AuthError(decode_utf8(msg)?));
+        }
 
-        if ptype == PACKET_AUTH_OK {
-            Ok(MaazDB { stream, connected: true })
-        } else {
-            Err(MaazDBError::AuthError(msg))
+        stream.sock.set_read_timeout(opts.read_timeout)?;
+        stream.sock.set_write_timeout(opts.write_timeout)?;
+        let mut db = MaazDB {
+            stream,
+            state: ConnectionState::Connecting,
+            in_transaction: false,
+            options: opts.clone(),
+            database: None,
+        };
+        if let Some(database) = &opts.database {
+            db.send_query(format!("USE {};", database).as_bytes())?;
         }
+        db.state = ConnectionState::Ready;
+        Ok(db)
     }
 
-    pub fn query(&mut self, sql: &str) -> Result<String, MaazDBError> {
-        if !self.connected {
-            return Err(MaazDBError::ProtocolError("Not connected".into()));
+    /// Replaces the stream with a fresh connection, re-selecting the current database.
+    ///
+    /// Fails while a transaction (a [`Transaction`] or a raw `BEGIN`) is open on a working
+    /// connection, since it would be lost along with the old one. Once the connection has
+    /// broken the server has discarded the transaction anyway, and reconnecting is the way out.
+    pub fn reconnect(&mut self) -> Result<(), MaazDBError> {
+        if self.in_transaction && self.state.can_send() {
+            return Err(transaction_error("reconnect"));
+        }
+        // Cloned, not taken: if this attempt fails, the next one must still restore it.
+        let database = self.database.clone();
+        let mut fresh = Self::connect_with(&self.options)?;
+        if let Some(database) = database.filter(|db| fresh.database.as_ref() != Some(db)) {
+            fresh.send_query(format!("USE {};", database).as_bytes())?;
+        }
+        *self = fresh;
+        Ok(())
+    }
+
+    fn open_socket(opts: &ConnectOptions) -> Result<TcpStream, MaazDBError> {
+        let Some(timeout) = opts.connect_timeout else {
+            return Ok(TcpStream::connect(opts.addr())?);
+        };
+        let mut last_err = None;
+        for addr in opts.addr().to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(sock) => return Ok(sock),
+                Err(e) => last_err = Some(e),
+            }
         }
-        Self::send_packet(&mut self.stream, PACKET_QUERY, sql.as_bytes())?;
-        let (ptype, msg) = Self::read_packet(&mut self.stream)?;
-        match ptype {
-            PACKET_MSG | PACKET_DATA => Ok(msg),
-            _ => Err(MaazDBError::ProtocolError(msg)),
+        Err(last_err
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Host did not resolve to any address"))
+            .into())
+    }
+
+    /// Runs a statement and returns the raw server response.
+    pub fn query(&mut self, sql: &str) -> Result<String, MaazDBError> {
+        let (_, payload) = self.round_trip(sql.as_bytes())?;
+        decode_utf8(payload)
+    }
+
+    /// Runs a statement given as raw bytes and returns the raw response payload, without
+    /// any UTF-8 checks.
+    pub fn query_bytes(&mut self, sql: &[u8]) -> Result<Bytes, MaazDBError> {
+        let (_, payload) = self.round_trip(sql)?;
+        Ok(payload)
+    }
+
+    /// Runs a statement and returns a typed [`QueryResult`].
+    pub fn execute(&mut self, sql: &str) -> Result<QueryResult, MaazDBError> {
+        let (ptype, payload) = self.round_trip(sql.as_bytes())?;
+        QueryResult::from_packet(ptype, decode_utf8(payload)?)
+    }
+
+    /// Binds `params` into the `$1`/`?` placeholders of `sql`, then runs it like [`MaazDB::query`].
+    ///
+    /// Parameter errors are reported before anything is sent to the server.
+    pub fn query_with(&mut self, sql: &str, params: &[&dyn ToSql]) -> Result<String, MaazDBError> {
+        let sql = bind(sql, params)?;
+        self.query(&sql)
+    }
+
+    /// Binds `params` into the placeholders of `sql`, then runs it like [`MaazDB::execute`].
+    pub fn execute_with(&mut self, sql: &str, params: &[&dyn ToSql]) -> Result<QueryResult, MaazDBError> {
+        let sql = bind(sql, params)?;
+        self.execute(&sql)
+    }
+
+    /// Runs a query and decodes every returned row into `T`.
+    pub fn query_as<T: FromRow>(&mut self, sql: &str) -> Result<Vec<T>, MaazDBError> {
+        self.execute(sql)?.expect_rows()?.decode()
+    }
+
+    /// Round-trips a trivial query to check that the server is responding.
+    pub fn ping(&mut self) -> Result<(), MaazDBError> {
+        self.query(PING_SQL).map(|_| ())
+    }
+
+    /// Whether the connection is ready for the next statement.
+    ///
+    /// Unlike [`MaazDB::ping`] nothing is sent: this checks the tracked state, and whether the
+    /// server has closed the socket since the last reply.
+    pub fn is_alive(&mut self) -> bool {
+        if self.state == ConnectionState::Ready && socket_closed(&self.stream.sock) {
+            self.state = ConnectionState::Broken;
         }
+        self.state == ConnectionState::Ready
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
     }
 
-    pub fn close(&mut self) {
-        let _ = self.stream.conn.send_close_notify();
-        self.connected = false;
+    /// Runs a query and returns its rows as they arrive, without holding the whole result in memory.
+    ///
+    /// Large results may be split by the server into several frames; only one row at a time is
+    /// buffered, up to `max_packet_size`. A stream that fails part-way is not retried.
+    /// Dropping the iterator early reads the rest of the reply first; see [`RowIter`].
+    pub fn query_iter(&mut self, sql: &str) -> Result<RowIter<'_>, MaazDBError> {
+        if self.state == ConnectionState::InQuery {
+            self.state = ConnectionState::Broken;
+        }
+        if self.state == ConnectionState::Broken && self.options.reconnect.is_some() && !self.in_transaction {
+            self.reconnect()?;
+        }
+        if !self.state.can_send() {
+            return Err(state_error(self.state));
+        }
+
+        self.state = ConnectionState::InQuery;
+        let max = self.options.max_packet_size;
+        let (ptype, len) = protocol::send_packet(&mut self.stream, PACKET_QUERY, sql.as_bytes())
+            .and_then(|_| protocol::read_header(&mut self.stream, usize::MAX))
+            .inspect_err(|_| self.state = ConnectionState::Broken)?;
+        if stream::is_data(ptype) {
+            return Ok(RowIter::new(self, ptype, len));
+        }
+
+        // A status message or an error: small, so read it whole.
+        if len > max {
+            self.state = ConnectionState::Broken;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Packet too large").into());
+        }
+        let mut payload = vec![0u8; len];
+        self.stream.read_exact(&mut payload).inspect_err(|_| self.state = ConnectionState::Broken)?;
+        self.state = ConnectionState::Ready;
+        let (_, msg) = check_response(ptype, payload.into(), sql.as_bytes())?;
+        Err(result::no_rows_error(&decode_utf8(msg)?))
     }
 
-    fn send_packet(stream: &mut rustls::StreamOwned<rustls::ClientConnection, TcpStream>, ptype: u8, payload: &[u8]) -> io::Result<()> {
-        stream.write_u8(ptype)?;
-        stream.write_u32::<BigEndian>(payload.len() as u32)?;
-        stream.write_all(payload)?;
-        stream.flush()?;
+    /// Closes the connection. Fails inside a transaction that is still open on a working
+    /// connection: commit or roll it back first.
+    pub fn close(&mut self) -> Result<(), MaazDBError> {
+        if self.in_transaction && self.state.can_send() {
+            return Err(transaction_error("close"));
+        }
+        self.stream.conn.send_close_notify();
+        self.state = ConnectionState::Closed;
         Ok(())
     }
 
-    fn read_packet(stream: &mut rustls::StreamOwned<rustls::ClientConnection, TcpStream>) -> io::Result<(u8, String)> {
-        let ptype = stream.read_u8()?;
-        let len = stream.read_u32::<BigEndian>()? as usize;
-        
-        if len > 10 * 1024 * 1024 {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "Packet too large"));
+    /// Sends `sql`, applying the reconnect policy (if any) when the stream is or becomes broken.
+    fn round_trip(&mut self, sql: &[u8]) -> Result<(u8, Bytes), MaazDBError> {
+        if self.state == ConnectionState::InQuery {
+            self.state = ConnectionState::Broken;
+        }
+        let Some(policy) = self.options.reconnect.clone()
+            .filter(|_| self.state != ConnectionState::Closed && !self.in_transaction) else {
+            return self.send_query(sql);
+        };
+        let mut attempt = 0;
+        loop {
+            if self.state == ConnectionState::Broken {
+                // Nothing has been sent yet, so a failed reconnect can be retried for any statement.
+                match self.reconnect() {
+                    Err(MaazDBError::IoError(_)) if attempt < policy.max_retries => {
+                        std::thread::sleep(policy.backoff(attempt));
+                        attempt += 1;
+                        continue;
+                    }
+                    reconnected => reconnected?,
+                }
+            }
+            // A statement that failed mid-flight may already have run, so only read-only ones are re-sent.
+            match self.send_query(sql) {
+                Err(MaazDBError::IoError(_)) if attempt < policy.max_retries && is_read_only_bytes(sql) => {
+                    std::thread::sleep(policy.backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends `sql` once on the current stream.
+    fn send_query(&mut self, sql: &[u8]) -> Result<(u8, Bytes), MaazDBError> {
+        let previous = self.state;
+        if !previous.can_send() {
+            return Err(state_error(previous));
+        }
+        self.state = ConnectionState::InQuery;
+        let reply = protocol::send_packet(&mut self.stream, PACKET_QUERY, sql)
+            .and_then(|_| protocol::read_packet(&mut self.stream, self.options.max_packet_size));
+        let (ptype, payload) = reply.inspect_err(|_| self.state = ConnectionState::Broken)?;
+        self.state = previous;
+        let response = check_response(ptype, payload.into(), sql)?;
+        if let Ok(sql) = std::str::from_utf8(sql) {
+            if let Some(database) = reconnect::use_target(sql) {
+                self.database = Some(database.to_string());
+            }
+            if let Some(open) = reconnect::transaction_change(sql) {
+                self.in_transaction = open;
+            }
         }
+        Ok(response)
+    }
+}
+
+/// The error for a statement attempted in a state that cannot send one.
+pub(crate) fn state_error(state: ConnectionState) -> MaazDBError {
+    match state {
+        ConnectionState::Closed => MaazDBError::ProtocolError("Not connected".into()),
+        _ => MaazDBError::IoError(io::Error::new(io::ErrorKind::NotConnected, "Connection is broken")),
+    }
+}
+
+/// The error for `reconnect` or `close` called while a transaction is open.
+pub(crate) fn transaction_error(action: &str) -> MaazDBError {
+    MaazDBError::ProtocolError(format!("Cannot {} inside a transaction; commit or roll back first", action))
+}
 
-        let mut buf = vec![0u8; len];
-        stream.read_exact(&mut buf)?;
-        Ok((ptype, String::from_utf8_lossy(&buf).to_string()))
+/// Whether the peer has closed `sock` (or sent something unprompted, which this
+/// request/response protocol never does on an idle connection).
+fn socket_closed(sock: &TcpStream) -> bool {
+    if sock.set_nonblocking(true).is_err() {
+        return true;
     }
-}
\ No newline at end of file
+    let peeked = sock.peek(&mut [0u8; 1]);
+    let restored = sock.set_nonblocking(false).is_ok();
+    !restored || !matches!(peeked, Err(ref e) if e.kind() == io::ErrorKind::WouldBlock)
+}
@@ -0,0 +1,311 @@
+//! Versioned schema migrations.
+//!
+//! Migrations are SQL scripts named `V<version>__<name>.sql`, e.g. `V001__create_users.sql`.
+//! An optional `U<version>__<name>.sql` undoes the migration with the same version. Load
+//! them from a directory at runtime with [`Migrator::from_dir`], or compile them into the
+//! binary with [`embed_migrations!`]:
+//!
+//! ```no_run
+//! # fn main() -> Result<(), maazdb_rs::MaazDBError> {
+//! use maazdb_rs::MaazDB;
+//! use maazdb_rs::migrate::Migrator;
+//!
+//! let mut db = MaazDB::connect("127.0.0.1", 8888, "admin", "admin")?;
+//! db.query("USE store_prod;")?;
+//!
+//! // or `embed_migrations!("migrations")`
+//! let migrator = Migrator::from_dir("migrations")?;
+//! for version in migrator.run(&mut db)? {
+//!     println!("applied V{:03}", version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Applied versions are recorded with a checksum of their script in [`HISTORY_TABLE`], in
+//! the current database. Each migration runs in its own transaction, together with its
+//! history row. [`Migrator::run`] refuses to start if an applied script has been edited
+//! since, if an applied version no longer exists, or if a pending version is older than
+//! one already applied.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use chrono::{NaiveDateTime, Utc};
+use ring::digest::{digest, SHA256};
+
+use crate::{ErrorCategory, MaazDB, MaazDBError, OnError};
+
+/// Embeds the migrations of a directory (relative to the crate's `Cargo.toml`) and
+/// evaluates to a [`Migrator`].
+///
+/// Files are read at compile time. Cargo does not notice newly added files on its own, so
+/// touch a source file (or add a `build.rs` with `cargo:rerun-if-changed=migrations`)
+/// after adding one.
+pub use maazdb_derive::embed_migrations;
+
+/// Name of the table that records applied migrations.
+pub const HISTORY_TABLE: &str = "_maazdb_migrations";
+
+/// One versioned migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub version: u32,
+    pub name: String,
+    /// The script that applies the migration.
+    pub up: String,
+    /// The script that reverts it, if there is one.
+    pub down: Option<String>,
+}
+
+impl Migration {
+    pub fn new(version: u32, name: &str, up: &str) -> Self {
+        Migration { version, name: name.to_string(), up: up.to_string(), down: None }
+    }
+
+    /// Sets the script that reverts this migration.
+    pub fn down(mut self, sql: &str) -> Self {
+        self.down = Some(sql.to_string());
+        self
+    }
+
+    /// SHA-256 of the up script, as hex. Line endings are normalized first, so a checkout
+    /// with CRLF line endings has the same checksum.
+    pub fn checksum(&self) -> String {
+        let normalized = self.up.replace("\r\n", "\n");
+        digest(&SHA256, normalized.as_bytes()).as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "V{:03}__{}", self.version, self.name)
+    }
+}
+
+/// A row of [`HISTORY_TABLE`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: Option<NaiveDateTime>,
+}
+
+/// Where a migration stands in a particular database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    /// Not applied yet.
+    Pending,
+    /// Applied, and the script is unchanged.
+    Applied,
+    /// Applied, but the script has been edited since.
+    Modified,
+    /// Applied, but there is no migration with this version any more.
+    Missing,
+}
+
+/// One line of [`Migrator::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// An ordered set of migrations.
+#[derive(Debug, Clone, Default)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Sorts the migrations by version. Duplicate versions are reported by [`Migrator::run`].
+    pub fn new(migrations: impl IntoIterator<Item = Migration>) -> Self {
+        let mut migrations: Vec<Migration> = migrations.into_iter().collect();
+        migrations.sort_by_key(|m| m.version);
+        Migrator { migrations }
+    }
+
+    /// Loads `V<version>__<name>.sql` and `U<version>__<name>.sql` files from `dir`.
+    ///
+    /// Other `.sql` file names are rejected, so a typo does not silently skip a migration.
+    /// Files with other extensions are ignored.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, MaazDBError> {
+        let mut ups = BTreeMap::new();
+        let mut downs = BTreeMap::new();
+
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            if !path.is_file() || !file_name.ends_with(".sql") {
+                continue;
+            }
+            let (kind, version, name) = parse_file_name(file_name).ok_or_else(|| {
+                MaazDBError::MigrationError(format!("'{}' is not named V<version>__<name>.sql or U<version>__<name>.sql", file_name))
+            })?;
+            let sql = fs::read_to_string(&path)?;
+            let target = if kind == 'V' { &mut ups } else { &mut downs };
+            if target.insert(version, (name.to_string(), sql)).is_some() {
+                return Err(MaazDBError::MigrationError(format!("more than one {}{:03} script in {}", kind, version, dir.as_ref().display())));
+            }
+        }
+
+        let mut migrations = Vec::with_capacity(ups.len());
+        for (version, (name, up)) in ups {
+            let mut migration = Migration::new(version, &name, &up);
+            if let Some((_, down)) = downs.remove(&version) {
+                migration = migration.down(&down);
+            }
+            migrations.push(migration);
+        }
+        if let Some((version, (name, _))) = downs.into_iter().next() {
+            return Err(MaazDBError::MigrationError(format!("U{:03}__{}.sql has no matching V{:03} migration", version, name, version)));
+        }
+        Ok(Migrator { migrations })
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// Reads [`HISTORY_TABLE`]. Empty if no migration has run in this database yet.
+    pub fn applied(&self, db: &mut MaazDB) -> Result<Vec<AppliedMigration>, MaazDBError> {
+        let sql = format!("SELECT version, name, checksum, applied_at FROM {} ORDER BY version;", HISTORY_TABLE);
+        let rows: Vec<(u32, String, String, Option<NaiveDateTime>)> = match db.query_as(&sql) {
+            Ok(rows) => rows,
+            Err(e) if e.as_server_error().is_some_and(|e| e.category == ErrorCategory::UnknownTable) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(rows.into_iter()
+            .map(|(version, name, checksum, applied_at)| AppliedMigration { version, name, checksum, applied_at })
+            .collect())
+    }
+
+    /// Every known or applied migration, by version.
+    pub fn status(&self, db: &mut MaazDB) -> Result<Vec<MigrationStatus>, MaazDBError> {
+        let mut applied: BTreeMap<u32, AppliedMigration> = self.applied(db)?.into_iter().map(|a| (a.version, a)).collect();
+        let mut status = Vec::new();
+        for m in &self.migrations {
+            let state = match applied.remove(&m.version) {
+                None => MigrationState::Pending,
+                Some(a) if a.checksum == m.checksum() => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+            };
+            status.push(MigrationStatus { version: m.version, name: m.name.clone(), state });
+        }
+        status.extend(applied.into_values().map(|a| MigrationStatus { version: a.version, name: a.name, state: MigrationState::Missing }));
+        status.sort_by_key(|s| s.version);
+        Ok(status)
+    }
+
+    /// Applies every pending migration in version order and returns their versions.
+    pub fn run(&self, db: &mut MaazDB) -> Result<Vec<u32>, MaazDBError> {
+        self.check_versions()?;
+        let status = self.status(db)?;
+        let mut newest_applied = None;
+        for s in &status {
+            match s.state {
+                MigrationState::Applied => newest_applied = Some(s.version),
+                MigrationState::Modified => return Err(MaazDBError::MigrationError(format!(
+                    "V{:03}__{} was edited after it was applied", s.version, s.name
+                ))),
+                MigrationState::Missing => return Err(MaazDBError::MigrationError(format!(
+                    "V{:03}__{} is applied but no longer exists", s.version, s.name
+                ))),
+                MigrationState::Pending => {}
+            }
+        }
+        if let Some(newest) = newest_applied
+            && let Some(s) = status.iter().find(|s| s.state == MigrationState::Pending && s.version < newest)
+        {
+            return Err(MaazDBError::MigrationError(format!(
+                "V{:03}__{} is pending, but V{:03} is already applied", s.version, s.name, newest
+            )));
+        }
+
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {} (version INT PRIMARY KEY, name TEXT NOT NULL, checksum TEXT NOT NULL, applied_at TIMESTAMP);",
+            HISTORY_TABLE,
+        );
+        db.query(&create)?;
+
+        let mut applied = Vec::new();
+        for s in status.iter().filter(|s| s.state == MigrationState::Pending) {
+            let m = self.get(s.version);
+            let insert = crate::bind(
+                &format!("INSERT INTO {} (version, name, checksum, applied_at) VALUES ($1, $2, $3, $4);", HISTORY_TABLE),
+                &[&m.version, &m.name, &m.checksum(), &Utc::now().naive_utc()],
+            )?;
+            apply(db, m, &m.up, &insert)?;
+            applied.push(m.version);
+        }
+        Ok(applied)
+    }
+
+    /// Reverts every applied migration newer than `target`, newest first, with their down
+    /// scripts. `revert(db, 0)` reverts everything. Returns the reverted versions.
+    pub fn revert(&self, db: &mut MaazDB, target: u32) -> Result<Vec<u32>, MaazDBError> {
+        self.check_versions()?;
+        let mut to_revert = Vec::new();
+        for s in self.status(db)?.into_iter().rev().filter(|s| s.version > target) {
+            match s.state {
+                MigrationState::Pending => continue,
+                MigrationState::Applied => {}
+                MigrationState::Modified => return Err(MaazDBError::MigrationError(format!(
+                    "V{:03}__{} was edited after it was applied", s.version, s.name
+                ))),
+                MigrationState::Missing => return Err(MaazDBError::MigrationError(format!(
+                    "V{:03}__{} is applied but no longer exists", s.version, s.name
+                ))),
+            }
+            let m = self.get(s.version);
+            let Some(down) = &m.down else {
+                return Err(MaazDBError::MigrationError(format!("{} has no down migration (U{:03}__{}.sql)", m, m.version, m.name)));
+            };
+            to_revert.push((m, down));
+        }
+
+        let mut reverted = Vec::new();
+        for (m, down) in to_revert {
+            let delete = format!("DELETE FROM {} WHERE version = {};", HISTORY_TABLE, m.version);
+            apply(db, m, down, &delete)?;
+            reverted.push(m.version);
+        }
+        Ok(reverted)
+    }
+
+    fn get(&self, version: u32) -> &Migration {
+        self.migrations.iter().find(|m| m.version == version).expect("status only lists known versions")
+    }
+
+    fn check_versions(&self) -> Result<(), MaazDBError> {
+        match self.migrations.windows(2).find(|pair| pair[0].version == pair[1].version) {
+            Some(pair) => Err(MaazDBError::MigrationError(format!("{} and {} share a version", pair[0], pair[1]))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Runs `script` and then the history update in one transaction.
+fn apply(db: &mut MaazDB, m: &Migration, script: &str, history: &str) -> Result<(), MaazDBError> {
+    let mut tx = db.transaction()?;
+    tx.execute_script(script, OnError::Stop).into_result().map_err(|e| {
+        MaazDBError::MigrationError(format!("{} failed: {}", m, e))
+    })?;
+    tx.query(history)?;
+    tx.commit()
+}
+
+/// Splits `V001__create_users.sql` into `('V', 1, "create_users")`.
+fn parse_file_name(file_name: &str) -> Option<(char, u32, &str)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    let kind = stem.chars().next().filter(|c| *c == 'V' || *c == 'U')?;
+    let (version, name) = stem[1..].split_once("__")?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) || name.is_empty() {
+        return None;
+    }
+    Some((kind, version.parse().ok()?, name))
+}
@@ -0,0 +1,269 @@
+use crate::params;
+use crate::{AsyncMaazDB, MaazDB, MaazDBError, QueryResult, ResultSet};
+
+/// How a paginator moves from one page to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageMode {
+    /// `LIMIT n OFFSET m`. Works with any `ORDER BY`, but the server skips more rows for every page.
+    Offset,
+    /// `WHERE key > last ORDER BY key LIMIT n` on an integer key column, usually the `SERIAL`
+    /// primary key. Every page costs the same, however deep into the table it is.
+    Keyset(String),
+}
+
+/// Builds the statement for each page and tracks the position between pages.
+#[derive(Debug, Clone)]
+struct PageQuery {
+    base: String,
+    page_size: usize,
+    mode: PageMode,
+    offset: usize,
+    last_key: Option<i64>,
+    done: bool,
+}
+
+impl PageQuery {
+    fn new(sql: &str, page_size: usize) -> Self {
+        PageQuery {
+            base: strip_comments(sql).trim().trim_end_matches(';').trim_end().to_string(),
+            page_size: page_size.max(1),
+            mode: PageMode::Offset,
+            offset: 0,
+            last_key: None,
+            done: false,
+        }
+    }
+
+    fn next_sql(&self) -> Result<String, MaazDBError> {
+        let base = &self.base;
+        for keyword in ["LIMIT", "OFFSET"] {
+            if find_top_level(base, keyword).is_some() {
+                return Err(usage_error(&format!("The paginated query must not have its own {}", keyword)));
+            }
+        }
+
+        let column = match &self.mode {
+            PageMode::Offset => return Ok(format!("{} LIMIT {} OFFSET {};", base, self.page_size, self.offset)),
+            PageMode::Keyset(column) => params::identifier(column, "keyset column")?,
+        };
+        for (keyword, clause) in [("ORDER", "ORDER BY"), ("GROUP", "GROUP BY"), ("HAVING", "HAVING")] {
+            if find_top_level(base, keyword).is_some() {
+                return Err(usage_error(&format!("Keyset pagination does not support {} in the base query", clause)));
+            }
+        }
+
+        let filter = match (self.last_key, find_top_level(base, "WHERE")) {
+            (None, _) => base.clone(),
+            (Some(last), None) => format!("{} WHERE {} > {}", base, column, last),
+            (Some(last), Some(at)) => format!(
+                "{} WHERE ({}) AND {} > {}",
+                base[..at].trim_end(), base[at + "WHERE".len()..].trim(), column, last
+            ),
+        };
+        Ok(format!("{} ORDER BY {} LIMIT {};", filter, column, self.page_size))
+    }
+
+    /// Records a fetched page. Returns it, or `None` once there are no more rows.
+    fn advance(&mut self, page: ResultSet) -> Result<Option<ResultSet>, MaazDBError> {
+        if page.len() < self.page_size {
+            self.done = true;
+        }
+        let Some(last) = page.rows().last() else { return Ok(None) };
+        match &self.mode {
+            PageMode::Offset => self.offset += page.len(),
+            PageMode::Keyset(column) => {
+                // The key is read back by its bare name, e.g. `id` for `users.id`.
+                let name = column.rsplit('.').next().unwrap_or(column);
+                if last.column_index(name).is_none() {
+                    self.done = true;
+                    return Err(usage_error(&format!("Keyset column '{}' must be in the SELECT list", column)));
+                }
+                let key: i64 = last.try_get(name)?;
+                if self.last_key.is_some_and(|prev| key <= prev) {
+                    self.done = true;
+                    return Err(usage_error(&format!("Keyset column '{}' did not increase between pages", column)));
+                }
+                self.last_key = Some(key);
+            }
+        }
+        Ok(Some(page))
+    }
+}
+
+fn usage_error(msg: &str) -> MaazDBError {
+    MaazDBError::ParameterError(msg.to_string())
+}
+
+/// Length of the quoted literal or identifier starting at `start`, with doubled quotes as escapes.
+fn quoted_len(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) != Some(&quote) {
+                return i + 1 - start;
+            }
+            i += 1;
+        }
+        i += 1;
+    }
+    bytes.len() - start
+}
+
+/// Replaces `--` and `/* */` comments with spaces, so clauses can be appended safely.
+fn strip_comments(sql: &str) -> String {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let len = match (bytes[i], bytes.get(i + 1)) {
+            (b'\'' | b'"', _) => {
+                let len = quoted_len(bytes, i);
+                out.push_str(&sql[i..i + len]);
+                i += len;
+                continue;
+            }
+            (b'-', Some(b'-')) => sql[i..].find('\n').unwrap_or(sql.len() - i),
+            (b'/', Some(b'*')) => sql[i + 2..].find("*/").map_or(sql.len() - i, |end| end + 4),
+            _ => 0,
+        };
+        if len > 0 {
+            out.push(' ');
+            i += len;
+        } else {
+            let ch = sql[i..].chars().next().unwrap_or(' ');
+            out.push(ch);
+            i += ch.len_utf8();
+        }
+    }
+    out
+}
+
+/// Position of `keyword` in comment-free `sql`, ignoring quoted text and anything in parentheses.
+fn find_top_level(sql: &str, keyword: &str) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' => {
+                i += quoted_len(bytes, i);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            _ if depth == 0
+                && (i == 0 || !is_ident(bytes[i - 1]))
+                && bytes[i..].len() >= keyword.len()
+                && bytes[i..i + keyword.len()].eq_ignore_ascii_case(keyword.as_bytes())
+                && bytes.get(i + keyword.len()).is_none_or(|&b| !is_ident(b)) =>
+            {
+                return Some(i);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// --- Blocking ---
+
+impl MaazDB {
+    /// Pages through the rows of a `SELECT`, `page_size` rows at a time, using `LIMIT`/`OFFSET`.
+    ///
+    /// Give the query an `ORDER BY` so pages are stable, or switch to [`Paginator::keyset`].
+    pub fn paginate(&mut self, sql: &str, page_size: usize) -> Paginator<'_> {
+        Paginator { conn: self, query: PageQuery::new(sql, page_size) }
+    }
+}
+
+/// Fetches a query one page at a time. Created by [`MaazDB::paginate`]; iterating yields pages.
+pub struct Paginator<'a> {
+    conn: &'a mut MaazDB,
+    query: PageQuery,
+}
+
+impl Paginator<'_> {
+    /// Pages by the integer `column` (e.g. `id` or `users.id`) instead of `OFFSET`. The base
+    /// query must not have its own `ORDER BY`; its `WHERE` clause is kept.
+    ///
+    /// Each page's last key is read back from the rows, so `column` must be in the SELECT list
+    /// (`SELECT *` includes it). Otherwise, or if `column` is not a plain identifier, the first
+    /// page fails with a `ParameterError`.
+    pub fn keyset(mut self, column: &str) -> Self {
+        self.query.mode = PageMode::Keyset(column.to_string());
+        self
+    }
+
+    pub fn mode(&self) -> &PageMode {
+        &self.query.mode
+    }
+
+    /// Fetches the next page, or `None` after the last one.
+    pub fn next_page(&mut self) -> Result<Option<ResultSet>, MaazDBError> {
+        if self.query.done {
+            return Ok(None);
+        }
+        let page = self.query.next_sql()
+            .and_then(|sql| self.conn.execute(&sql))
+            .and_then(QueryResult::expect_rows)
+            .inspect_err(|_| self.query.done = true)?;
+        self.query.advance(page)
+    }
+}
+
+impl Iterator for Paginator<'_> {
+    type Item = Result<ResultSet, MaazDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_page().transpose()
+    }
+}
+
+// --- Async ---
+
+impl AsyncMaazDB {
+    /// Pages through the rows of a `SELECT`, like [`MaazDB::paginate`].
+    pub fn paginate(&mut self, sql: &str, page_size: usize) -> AsyncPaginator<'_> {
+        AsyncPaginator { conn: self, query: PageQuery::new(sql, page_size) }
+    }
+}
+
+/// Fetches a query one page at a time. Created by [`AsyncMaazDB::paginate`].
+pub struct AsyncPaginator<'a> {
+    conn: &'a mut AsyncMaazDB,
+    query: PageQuery,
+}
+
+impl AsyncPaginator<'_> {
+    /// Pages by the integer `column` instead of `OFFSET`, like [`Paginator::keyset`].
+    pub fn keyset(mut self, column: &str) -> Self {
+        self.query.mode = PageMode::Keyset(column.to_string());
+        self
+    }
+
+    pub fn mode(&self) -> &PageMode {
+        &self.query.mode
+    }
+
+    /// Fetches the next page, or `None` after the last one.
+    pub async fn next_page(&mut self) -> Result<Option<ResultSet>, MaazDBError> {
+        if self.query.done {
+            return Ok(None);
+        }
+        let page = match self.query.next_sql() {
+            Ok(sql) => self.conn.execute(&sql).await.and_then(QueryResult::expect_rows),
+            Err(e) => Err(e),
+        };
+        let page = match page {
+            Ok(page) => page,
+            Err(e) => {
+                self.query.done = true;
+                return Err(e);
+            }
+        };
+        self.query.advance(page)
+    }
+}
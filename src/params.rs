@@ -0,0 +1,288 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use uuid::Uuid;
+
+use crate::{MaazDBError, Value};
+
+/// A Rust value that can be rendered as a MaazDB SQL literal.
+pub trait ToSql {
+    /// Returns the literal exactly as it should appear in the statement.
+    fn to_sql(&self) -> Result<String, MaazDBError>;
+}
+
+/// Replaces `$1`/`$2`/... or `?` placeholders in `sql` with escaped literals.
+///
+/// Placeholders inside string literals, quoted identifiers and comments are left alone.
+/// The two placeholder styles cannot be mixed, and every parameter must be used.
+pub fn bind(sql: &str, params: &[&dyn ToSql]) -> Result<String, MaazDBError> {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len() + params.len() * 8);
+    let mut used = vec![false; params.len()];
+    let mut positional = 0usize;
+    let mut numbered = false;
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // A doubled quote is an escaped quote, not the end of the literal
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b'?' => {
+                if numbered {
+                    return Err(mixed_placeholders());
+                }
+                let param = params.get(positional).ok_or_else(|| count_mismatch(params.len()))?;
+                used[positional] = true;
+                positional += 1;
+                out.push_str(&sql[last..i]);
+                push_literal(&mut out, &param.to_sql()?);
+                i += 1;
+                last = i;
+            }
+            b'$' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                if positional > 0 {
+                    return Err(mixed_placeholders());
+                }
+                numbered = true;
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+                let n: usize = sql[start..end].parse()
+                    .map_err(|_| MaazDBError::ParameterError(format!("Invalid placeholder '{}'", &sql[i..end])))?;
+                if n == 0 || n > params.len() {
+                    return Err(MaazDBError::ParameterError(format!(
+                        "Placeholder ${} has no matching parameter ({} given)", n, params.len()
+                    )));
+                }
+                used[n - 1] = true;
+                out.push_str(&sql[last..i]);
+                push_literal(&mut out, &params[n - 1].to_sql()?);
+                i = end;
+                last = i;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if let Some(unused) = used.iter().position(|u| !u) {
+        return Err(MaazDBError::ParameterError(format!(
+            "Parameter {} is never used ({} given)", unused + 1, params.len()
+        )));
+    }
+
+    out.push_str(&sql[last.min(sql.len())..]);
+    Ok(out)
+}
+
+/// Appends a bound literal. A negative number right after a `-` would otherwise turn
+/// `1 -$1` into the comment `1 --1`.
+fn push_literal(out: &mut String, literal: &str) {
+    if out.ends_with('-') && literal.starts_with('-') {
+        out.push(' ');
+    }
+    out.push_str(literal);
+}
+
+fn mixed_placeholders() -> MaazDBError {
+    MaazDBError::ParameterError("Cannot mix '?' and '$n' placeholders".into())
+}
+
+fn count_mismatch(given: usize) -> MaazDBError {
+    MaazDBError::ParameterError(format!("Statement has more placeholders than the {} parameters given", given))
+}
+
+/// Rejects anything but a plain (optionally `db.`-qualified) identifier, since the name is
+/// spliced into the statement. `what` names it in the error, e.g. "table".
+///
+/// The query builder, the paginator and generated `INSERT`s all check names with this.
+pub fn identifier<'a>(name: &'a str, what: &str) -> Result<&'a str, MaazDBError> {
+    let valid = !name.is_empty()
+        && name.split('.').all(|part| {
+            part.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+    if !valid {
+        return Err(MaazDBError::ParameterError(format!("'{}' is not a valid {} name", name, what)));
+    }
+    Ok(name)
+}
+
+/// Quotes a string literal, doubling any embedded single quotes.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('\'');
+    for c in s.chars() {
+        if c == '\'' {
+            out.push('\'');
+        }
+        out.push(c);
+    }
+    out.push('\'');
+    out
+}
+
+/// Renders bytes as a hex `BLOB` literal, e.g. `X'DEADBEEF'`.
+fn hex_literal(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2 + 3);
+    out.push_str("X'");
+    for b in bytes {
+        out.push_str(&format!("{:02X}", b));
+    }
+    out.push('\'');
+    out
+}
+
+/// Formats `d` in its own precision: widening an `f32` first would print `0.1` as
+/// `0.10000000149011612`.
+fn format_double<F: Into<f64> + Copy + fmt::Display>(d: F) -> Result<String, MaazDBError> {
+    if !d.into().is_finite() {
+        return Err(MaazDBError::ParameterError(format!("{} cannot be stored in a DOUBLE column", d)));
+    }
+    let mut s = d.to_string();
+    if !s.contains('.') {
+        s.push_str(".0");
+    }
+    Ok(s)
+}
+
+fn format_timestamp(dt: &NaiveDateTime) -> String {
+    if dt.nanosecond() == 0 {
+        quote(&dt.format("%Y-%m-%d %H:%M:%S").to_string())
+    } else {
+        quote(&dt.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for &T {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        (**self).to_sql()
+    }
+}
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        match self {
+            Some(v) => v.to_sql(),
+            None => Ok("NULL".into()),
+        }
+    }
+}
+
+macro_rules! impl_to_sql_int {
+    ($($t:ty),*) => {$(
+        impl ToSql for $t {
+            fn to_sql(&self) -> Result<String, MaazDBError> {
+                Ok(self.to_string())
+            }
+        }
+    )*};
+}
+
+impl_to_sql_int!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+impl ToSql for f64 {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        format_double(*self)
+    }
+}
+
+impl ToSql for f32 {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        format_double(*self)
+    }
+}
+
+impl ToSql for bool {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(if *self { "TRUE" } else { "FALSE" }.into())
+    }
+}
+
+impl ToSql for str {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(quote(self))
+    }
+}
+
+impl ToSql for String {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(quote(self))
+    }
+}
+
+// BLOB/BYTEA columns
+impl ToSql for [u8] {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(hex_literal(self))
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(hex_literal(self))
+    }
+}
+
+impl ToSql for bytes::Bytes {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(hex_literal(self))
+    }
+}
+
+impl ToSql for NaiveDateTime {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(format_timestamp(self))
+    }
+}
+
+impl ToSql for DateTime<Utc> {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(format_timestamp(&self.naive_utc()))
+    }
+}
+
+impl ToSql for Uuid {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        Ok(quote(&self.hyphenated().to_string()))
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> Result<String, MaazDBError> {
+        match self {
+            Value::Null => Ok("NULL".into()),
+            Value::Bool(b) => b.to_sql(),
+            Value::Int(i) => i.to_sql(),
+            Value::Double(d) => d.to_sql(),
+            Value::Text(s) => s.to_sql(),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use ring::digest::{digest, SHA256};
+use rustls::client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, CertificateError, DigitallySignedStruct, ServerName};
+
+use crate::tls::NoCertificateVerification;
+use crate::MaazDBError;
+
+/// A SHA-256 fingerprint of either the whole leaf certificate or just its public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fingerprint {
+    /// SHA-256 of the DER-encoded leaf certificate.
+    Certificate([u8; 32]),
+    /// SHA-256 of the leaf's DER-encoded SubjectPublicKeyInfo. Survives certificate renewal with the same key.
+    PublicKey([u8; 32]),
+}
+
+impl Fingerprint {
+    /// Parses a certificate fingerprint from hex, with or without `:` separators.
+    pub fn certificate(hex: &str) -> Result<Self, MaazDBError> {
+        parse_hex(hex).map(Fingerprint::Certificate)
+    }
+
+    /// Parses a public key (SPKI) fingerprint from hex, with or without `:` separators.
+    pub fn public_key(hex: &str) -> Result<Self, MaazDBError> {
+        parse_hex(hex).map(Fingerprint::PublicKey)
+    }
+
+    /// Computes the certificate fingerprint of a DER certificate.
+    pub fn of_certificate(der: &[u8]) -> Self {
+        Fingerprint::Certificate(sha256(der))
+    }
+
+    /// Computes the public key fingerprint of a DER certificate.
+    pub fn of_public_key(der: &[u8]) -> Result<Self, MaazDBError> {
+        let spki = spki_der(der).ok_or_else(|| {
+            MaazDBError::TlsError(rustls::Error::General("Could not find the certificate's public key".into()))
+        })?;
+        Ok(Fingerprint::PublicKey(sha256(spki)))
+    }
+
+    /// Checks whether `der` (the server's leaf certificate) has this fingerprint.
+    pub fn matches(&self, der: &[u8]) -> bool {
+        match self {
+            Fingerprint::Certificate(_) => Fingerprint::of_certificate(der) == *self,
+            Fingerprint::PublicKey(_) => Fingerprint::of_public_key(der).is_ok_and(|fp| fp == *self),
+        }
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, bytes) = match self {
+            Fingerprint::Certificate(b) => ("cert-sha256", b),
+            Fingerprint::PublicKey(b) => ("spki-sha256", b),
+        };
+        write!(f, "{}:", prefix)?;
+        for (i, b) in bytes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = MaazDBError;
+
+    /// Parses the `cert-sha256:AB:CD:...` / `spki-sha256:AB:CD:...` form produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix("cert-sha256:") {
+            Fingerprint::certificate(hex)
+        } else if let Some(hex) = s.strip_prefix("spki-sha256:") {
+            Fingerprint::public_key(hex)
+        } else {
+            Err(MaazDBError::TlsError(rustls::Error::General(format!(
+                "Fingerprint '{}' must start with 'cert-sha256:' or 'spki-sha256:'", s
+            ))))
+        }
+    }
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(digest(&SHA256, data).as_ref());
+    out
+}
+
+fn parse_hex(hex: &str) -> Result<[u8; 32], MaazDBError> {
+    let digits: String = hex.chars().filter(|c| *c != ':' && !c.is_whitespace()).collect();
+    let invalid = || MaazDBError::TlsError(rustls::Error::General(format!("Invalid SHA-256 fingerprint '{}'", hex)));
+    if digits.len() != 64 || !digits.is_ascii() {
+        return Err(invalid());
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(out)
+}
+
+/// One DER element: its tag, its content, the whole encoded element and what follows it.
+struct Tlv<'a> {
+    tag: u8,
+    element: &'a [u8],
+    content: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn der_next(data: &[u8]) -> Option<Tlv<'_>> {
+    let tag = *data.first()?;
+    let first = *data.get(1)? as usize;
+    let (len, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let n = first & 0x7f;
+        if n == 0 || n > 4 {
+            return None;
+        }
+        let len = data.get(2..2 + n)?.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, 2 + n)
+    };
+    let end = header.checked_add(len)?;
+    Some(Tlv {
+        tag,
+        element: data.get(..end)?,
+        content: data.get(header..end)?,
+        rest: data.get(end..)?,
+    })
+}
+
+/// Finds the DER SubjectPublicKeyInfo inside an X.509 certificate.
+fn spki_der(cert: &[u8]) -> Option<&[u8]> {
+    let cert_body = der_next(cert)?.content;
+    let mut tbs = der_next(cert_body)?.content;
+
+    // Optional explicit version tag [0]
+    if tbs.first() == Some(&0xA0) {
+        tbs = der_next(tbs)?.rest;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        tbs = der_next(tbs)?.rest;
+    }
+    let spki = der_next(tbs)?;
+    (spki.tag == 0x30).then_some(spki.element)
+}
+
+fn mismatch_error(expected: &Fingerprint, der: &[u8]) -> rustls::Error {
+    let actual = match expected {
+        Fingerprint::Certificate(_) => Ok(Fingerprint::of_certificate(der)),
+        Fingerprint::PublicKey(_) => Fingerprint::of_public_key(der),
+    };
+    let actual = actual.map(|fp| fp.to_string()).unwrap_or_else(|_| "unknown".into());
+    rustls::Error::InvalidCertificate(CertificateError::Other(Arc::new(FingerprintMismatch {
+        expected: expected.to_string(),
+        actual,
+    })))
+}
+
+/// The server presented a certificate whose fingerprint does not match the pin.
+#[derive(Debug)]
+pub struct FingerprintMismatch {
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for FingerprintMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "certificate fingerprint {} does not match pinned {}", self.actual, self.expected)
+    }
+}
+
+impl std::error::Error for FingerprintMismatch {}
+
+/// Accepts only a leaf certificate matching a configured fingerprint.
+pub(crate) struct PinnedVerifier(pub(crate) Fingerprint);
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(&self, end_entity: &Certificate, _: &[Certificate], _: &ServerName, _: &mut dyn Iterator<Item = &[u8]>, _: &[u8], _: SystemTime) -> Result<ServerCertVerified, rustls::Error> {
+        if self.0.matches(&end_entity.0) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(mismatch_error(&self.0, &end_entity.0))
+        }
+    }
+}
+
+/// Trust-on-first-use: records the public key fingerprint of an unknown host in a
+/// known_hosts-style file and refuses any different key for that host afterwards.
+pub(crate) struct TofuVerifier {
+    pub(crate) path: PathBuf,
+    pub(crate) host: String,
+    lock: Mutex<()>,
+}
+
+impl TofuVerifier {
+    pub(crate) fn new(path: PathBuf, host: String) -> Self {
+        TofuVerifier { path, host, lock: Mutex::new(()) }
+    }
+
+    fn known_fingerprint(&self) -> std::io::Result<Option<Fingerprint>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            if let (Some(host), Some(fp)) = (parts.next(), parts.next())
+                && host == self.host
+            {
+                return fp.parse::<Fingerprint>().map(Some).map_err(|e| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+                });
+            }
+        }
+        Ok(None)
+    }
+
+    fn record(&self, fp: &Fingerprint) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{} {}", self.host, fp)
+    }
+}
+
+impl TofuVerifier {
+    fn io_error(&self, e: std::io::Error) -> rustls::Error {
+        rustls::Error::General(format!("known hosts file {}: {}", self.path.display(), e))
+    }
+
+    /// Records the key of an unknown host. Only called once the handshake signature has
+    /// shown that the server holds the key, so an impostor replaying a certificate is not pinned.
+    fn trust(&self, cert: &Certificate) -> Result<(), rustls::Error> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        match self.known_fingerprint().map_err(|e| self.io_error(e))? {
+            Some(known) if known.matches(&cert.0) => Ok(()),
+            Some(known) => Err(mismatch_error(&known, &cert.0)),
+            None => {
+                let fp = Fingerprint::of_public_key(&cert.0)
+                    .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+                self.record(&fp).map_err(|e| self.io_error(e))
+            }
+        }
+    }
+}
+
+impl ServerCertVerifier for TofuVerifier {
+    fn verify_server_cert(&self, end_entity: &Certificate, _: &[Certificate], _: &ServerName, _: &mut dyn Iterator<Item = &[u8]>, _: &[u8], _: SystemTime) -> Result<ServerCertVerified, rustls::Error> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        match self.known_fingerprint().map_err(|e| self.io_error(e))? {
+            Some(known) if known.matches(&end_entity.0) => Ok(ServerCertVerified::assertion()),
+            Some(known) => Err(mismatch_error(&known, &end_entity.0)),
+            // Recorded in `trust` after the signature check
+            None => Fingerprint::of_public_key(&end_entity.0)
+                .map(|_| ServerCertVerified::assertion())
+                .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadEncoding)),
+        }
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &Certificate, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let valid = NoCertificateVerification.verify_tls12_signature(message, cert, dss)?;
+        self.trust(cert)?;
+        Ok(valid)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &Certificate, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let valid = NoCertificateVerification.verify_tls13_signature(message, cert, dss)?;
+        self.trust(cert)?;
+        Ok(valid)
+    }
+}
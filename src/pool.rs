@@ -0,0 +1,380 @@
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{AsyncMaazDB, ConnectOptions, MaazDB, MaazDBError};
+
+/// Sizing and recycling rules shared by [`Pool`] and [`AsyncPool`].
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    pub(crate) min_size: usize,
+    pub(crate) max_size: usize,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) max_lifetime: Option<Duration>,
+    pub(crate) checkout_timeout: Duration,
+    pub(crate) health_check: bool,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            min_size: 0,
+            max_size: 10,
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+            checkout_timeout: Duration::from_secs(30),
+            health_check: true,
+        }
+    }
+}
+
+impl PoolOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connections opened when the pool is created.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Upper limit on open connections, idle or checked out.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.max(1);
+        self
+    }
+
+    /// Idle connections older than this are closed instead of reused.
+    pub fn idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Connections older than this are closed instead of reused, however busy they are.
+    pub fn max_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.max_lifetime = lifetime;
+        self
+    }
+
+    /// How long `get` waits for a free connection when the pool is at `max_size`.
+    pub fn checkout_timeout(mut self, timeout: Duration) -> Self {
+        self.checkout_timeout = timeout;
+        self
+    }
+
+    /// Whether idle connections are pinged before being handed out (default `true`).
+    pub fn health_check(mut self, enabled: bool) -> Self {
+        self.health_check = enabled;
+        self
+    }
+
+    fn expired(&self, created: Instant, idle_since: Instant) -> bool {
+        self.max_lifetime.is_some_and(|max| created.elapsed() >= max)
+            || self.idle_timeout.is_some_and(|max| idle_since.elapsed() >= max)
+    }
+}
+
+/// The statement that puts a reused connection back on the configured database, after the
+/// previous holder switched to another one with `USE`. Without a configured database there
+/// is nothing to switch back to, so such a connection is closed instead of reused.
+fn reset_sql(connect: &ConnectOptions) -> Option<String> {
+    connect.database.as_ref().map(|db| format!("USE {};", db))
+}
+
+struct Idle<C> {
+    conn: C,
+    created: Instant,
+    idle_since: Instant,
+}
+
+// --- Blocking pool ---
+
+struct PoolState {
+    idle: VecDeque<Idle<MaazDB>>,
+    total: usize,
+}
+
+struct PoolInner {
+    connect: ConnectOptions,
+    options: PoolOptions,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+impl PoolInner {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn release_slot(&self) {
+        self.lock().total -= 1;
+        self.available.notify_one();
+    }
+}
+
+/// A thread-safe pool of blocking [`MaazDB`] connections. Cloning shares the same pool.
+///
+/// A connection is handed out on the database configured in its [`ConnectOptions`], even if
+/// the previous holder ran `USE`. Other session state is not reset.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<PoolInner>,
+}
+
+impl Pool {
+    /// Creates the pool and opens `min_size` connections up front.
+    pub fn new(connect: ConnectOptions, options: PoolOptions) -> Result<Self, MaazDBError> {
+        let mut idle = VecDeque::new();
+        for _ in 0..options.min_size.min(options.max_size) {
+            let now = Instant::now();
+            idle.push_back(Idle { conn: MaazDB::connect_with(&connect)?, created: now, idle_since: now });
+        }
+        let total = idle.len();
+        Ok(Pool {
+            inner: Arc::new(PoolInner {
+                connect,
+                options,
+                state: Mutex::new(PoolState { idle, total }),
+                available: Condvar::new(),
+            }),
+        })
+    }
+
+    /// Checks out a connection, reusing a healthy idle one or opening a new one.
+    pub fn get(&self) -> Result<PooledConnection, MaazDBError> {
+        let inner = &self.inner;
+        let deadline = Instant::now() + inner.options.checkout_timeout;
+        let mut state = inner.lock();
+
+        loop {
+            if let Some(idle) = state.idle.pop_back() {
+                drop(state);
+                if let Some(conn) = self.validate(idle) {
+                    return Ok(conn);
+                }
+                state = inner.lock();
+                state.total -= 1;
+                continue;
+            }
+
+            if state.total < inner.options.max_size {
+                state.total += 1;
+                drop(state);
+                return match MaazDB::connect_with(&inner.connect) {
+                    Ok(conn) => Ok(self.wrap(conn, Instant::now())),
+                    Err(e) => {
+                        inner.release_slot();
+                        Err(e)
+                    }
+                };
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(MaazDBError::PoolTimeout);
+            }
+            state = inner.available.wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// Connections currently open, idle or checked out.
+    pub fn size(&self) -> usize {
+        self.inner.lock().total
+    }
+
+    /// Connections currently waiting in the pool.
+    pub fn idle(&self) -> usize {
+        self.inner.lock().idle.len()
+    }
+
+    fn validate(&self, idle: Idle<MaazDB>) -> Option<PooledConnection> {
+        let Idle { mut conn, created, idle_since } = idle;
+        if !conn.is_alive() || self.inner.options.expired(created, idle_since) {
+            let _ = conn.close();
+            return None;
+        }
+        if self.inner.options.health_check && conn.ping().is_err() {
+            return None;
+        }
+        if conn.database != self.inner.connect.database {
+            let reset = match reset_sql(&self.inner.connect) {
+                Some(sql) => conn.query(&sql).is_ok(),
+                None => false,
+            };
+            if !reset {
+                let _ = conn.close();
+                return None;
+            }
+        }
+        Some(self.wrap(conn, created))
+    }
+
+    fn wrap(&self, conn: MaazDB, created: Instant) -> PooledConnection {
+        PooledConnection { conn: Some(conn), created, pool: self.inner.clone() }
+    }
+}
+
+/// A connection checked out of a [`Pool`]. Returned to the pool on drop, unless it broke.
+pub struct PooledConnection {
+    conn: Option<MaazDB>,
+    created: Instant,
+    pool: Arc<PoolInner>,
+}
+
+impl Deref for PooledConnection {
+    type Target = MaazDB;
+
+    fn deref(&self) -> &MaazDB {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut MaazDB {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(mut conn) = self.conn.take() else { return };
+        let pool = &self.pool;
+        // A transaction left open would leak into the next checkout: drop the connection
+        // instead, and the server rolls it back when the socket closes.
+        if conn.in_transaction {
+            pool.release_slot();
+            return;
+        }
+        if !conn.is_alive() || pool.options.max_lifetime.is_some_and(|max| self.created.elapsed() >= max) {
+            let _ = conn.close();
+            pool.release_slot();
+            return;
+        }
+        pool.lock().idle.push_back(Idle { conn, created: self.created, idle_since: Instant::now() });
+        pool.available.notify_one();
+    }
+}
+
+// --- Async pool ---
+
+struct AsyncPoolInner {
+    connect: ConnectOptions,
+    options: PoolOptions,
+    idle: Mutex<VecDeque<Idle<AsyncMaazDB>>>,
+    permits: Arc<Semaphore>,
+}
+
+impl AsyncPoolInner {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Idle<AsyncMaazDB>>> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A pool of [`AsyncMaazDB`] connections for tokio services. Cloning shares the same pool.
+///
+/// Connections are reset to the configured database on checkout, like [`Pool`].
+#[derive(Clone)]
+pub struct AsyncPool {
+    inner: Arc<AsyncPoolInner>,
+}
+
+impl AsyncPool {
+    /// Creates the pool and opens `min_size` connections up front.
+    pub async fn new(connect: ConnectOptions, options: PoolOptions) -> Result<Self, MaazDBError> {
+        let mut idle = VecDeque::new();
+        for _ in 0..options.min_size.min(options.max_size) {
+            let now = Instant::now();
+            idle.push_back(Idle { conn: AsyncMaazDB::connect_with(&connect).await?, created: now, idle_since: now });
+        }
+        let permits = Arc::new(Semaphore::new(options.max_size));
+        Ok(AsyncPool {
+            inner: Arc::new(AsyncPoolInner { connect, options, idle: Mutex::new(idle), permits }),
+        })
+    }
+
+    /// Checks out a connection, reusing a healthy idle one or opening a new one.
+    pub async fn get(&self) -> Result<AsyncPooledConnection, MaazDBError> {
+        let inner = &self.inner;
+        let permit = tokio::time::timeout(inner.options.checkout_timeout, inner.permits.clone().acquire_owned())
+            .await
+            .map_err(|_| MaazDBError::PoolTimeout)?
+            .map_err(|_| MaazDBError::PoolTimeout)?;
+
+        loop {
+            let Some(Idle { mut conn, created, idle_since }) = inner.lock().pop_back() else { break };
+            if !conn.is_alive() || inner.options.expired(created, idle_since) {
+                conn.close().await;
+                continue;
+            }
+            if inner.options.health_check && conn.ping().await.is_err() {
+                continue;
+            }
+            if conn.database != inner.connect.database {
+                let reset = match reset_sql(&inner.connect) {
+                    Some(sql) => conn.query(&sql).await.is_ok(),
+                    None => false,
+                };
+                if !reset {
+                    conn.close().await;
+                    continue;
+                }
+            }
+            return Ok(AsyncPooledConnection { conn: Some(conn), created, pool: inner.clone(), _permit: permit });
+        }
+
+        let conn = AsyncMaazDB::connect_with(&inner.connect).await?;
+        Ok(AsyncPooledConnection { conn: Some(conn), created: Instant::now(), pool: inner.clone(), _permit: permit })
+    }
+
+    /// Connections currently open, idle or checked out.
+    pub fn size(&self) -> usize {
+        let checked_out = self.inner.options.max_size - self.inner.permits.available_permits();
+        checked_out + self.idle()
+    }
+
+    /// Connections currently waiting in the pool.
+    pub fn idle(&self) -> usize {
+        self.inner.lock().len()
+    }
+}
+
+/// A connection checked out of an [`AsyncPool`]. Returned to the pool on drop, unless it broke.
+pub struct AsyncPooledConnection {
+    conn: Option<AsyncMaazDB>,
+    created: Instant,
+    pool: Arc<AsyncPoolInner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for AsyncPooledConnection {
+    type Target = AsyncMaazDB;
+
+    fn deref(&self) -> &AsyncMaazDB {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for AsyncPooledConnection {
+    fn deref_mut(&mut self) -> &mut AsyncMaazDB {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Drop for AsyncPooledConnection {
+    fn drop(&mut self) {
+        let Some(mut conn) = self.conn.take() else { return };
+        let pool = &self.pool;
+        // Broken connections, and those left inside a transaction, are simply dropped:
+        // there is no runtime to await a clean close or a rollback here.
+        if conn.in_transaction || !conn.is_alive() || pool.options.max_lifetime.is_some_and(|max| self.created.elapsed() >= max) {
+            return;
+        }
+        pool.lock().push_back(Idle { conn, created: self.created, idle_since: Instant::now() });
+    }
+}
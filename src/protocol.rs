@@ -0,0 +1,310 @@
+//! The MaazDB wire format, for proxies, test fakes and other tooling.
+//!
+//! Every frame is a one-byte packet type, a big-endian `u32` payload length and the payload.
+//! [`PacketCodec`] reads and writes [`Packet`]s on any `Read`/`Write`, and is also a
+//! `tokio_util::codec` `Decoder`/`Encoder` for use with `Framed`.
+
+use std::io::{self, Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Decoder, Encoder};
+
+// --- Protocol Constants ---
+/// Client → server: `user\0pass\0driver[\0app]`.
+pub const PACKET_HANDSHAKE: u8 = 0x10;
+/// Server → client: the handshake was accepted.
+pub const PACKET_AUTH_OK: u8   = 0x11;
+/// Server → client: the handshake was rejected. The payload is the reason.
+pub const PACKET_AUTH_ERR: u8  = 0x12;
+/// Client → server: one SQL statement.
+pub const PACKET_QUERY: u8     = 0x20;
+/// Server → client: a status message, e.g. `Table created`.
+pub const PACKET_MSG: u8       = 0x02;
+/// Server → client: a JSON result set, `{"headers":[..],"data":[[..]]}`.
+pub const PACKET_DATA: u8      = 0x03;
+/// A leading part of a large `PACKET_DATA` reply. More parts follow, and the
+/// last part arrives as an ordinary `PACKET_DATA` frame.
+pub const PACKET_DATA_PART: u8 = 0x04;
+
+/// Driver name sent in the handshake.
+pub const DRIVER_SIG: &str = "maazdb-rust-driver-v1";
+
+/// Trivial statement used to check that the server is responding.
+pub(crate) const PING_SQL: &str = "SELECT 1;";
+
+/// Default limit on a payload held in memory at once.
+pub const DEFAULT_MAX_PACKET_SIZE: usize = 10 * 1024 * 1024;
+
+/// Length of a frame header: the packet type and the payload length.
+pub const HEADER_LEN: usize = 5;
+
+// --- Packets ---
+
+/// One frame on the wire. Payloads are kept as raw bytes, so a frame re-encodes exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Handshake(Bytes),
+    AuthOk(Bytes),
+    AuthErr(Bytes),
+    Query(Bytes),
+    Msg(Bytes),
+    Data(Bytes),
+    DataPart(Bytes),
+    /// A packet type this driver does not know.
+    Other(u8, Bytes),
+}
+
+impl Packet {
+    /// Builds a packet from its type byte and payload.
+    pub fn new(ptype: u8, payload: impl Into<Bytes>) -> Self {
+        let payload = payload.into();
+        match ptype {
+            PACKET_HANDSHAKE => Packet::Handshake(payload),
+            PACKET_AUTH_OK => Packet::AuthOk(payload),
+            PACKET_AUTH_ERR => Packet::AuthErr(payload),
+            PACKET_QUERY => Packet::Query(payload),
+            PACKET_MSG => Packet::Msg(payload),
+            PACKET_DATA => Packet::Data(payload),
+            PACKET_DATA_PART => Packet::DataPart(payload),
+            other => Packet::Other(other, payload),
+        }
+    }
+
+    /// A handshake as this driver sends it.
+    pub fn handshake(user: &str, pass: &str, application_name: Option<&str>) -> Self {
+        Packet::Handshake(handshake_payload(user, pass, application_name).into())
+    }
+
+    pub fn query(sql: &str) -> Self {
+        Packet::Query(Bytes::copy_from_slice(sql.as_bytes()))
+    }
+
+    pub fn packet_type(&self) -> u8 {
+        match self {
+            Packet::Handshake(_) => PACKET_HANDSHAKE,
+            Packet::AuthOk(_) => PACKET_AUTH_OK,
+            Packet::AuthErr(_) => PACKET_AUTH_ERR,
+            Packet::Query(_) => PACKET_QUERY,
+            Packet::Msg(_) => PACKET_MSG,
+            Packet::Data(_) => PACKET_DATA,
+            Packet::DataPart(_) => PACKET_DATA_PART,
+            Packet::Other(ptype, _) => *ptype,
+        }
+    }
+
+    pub fn payload(&self) -> &Bytes {
+        match self {
+            Packet::Handshake(p) | Packet::AuthOk(p) | Packet::AuthErr(p) | Packet::Query(p)
+            | Packet::Msg(p) | Packet::Data(p) | Packet::DataPart(p) | Packet::Other(_, p) => p,
+        }
+    }
+
+    pub fn into_parts(self) -> (u8, Bytes) {
+        let ptype = self.packet_type();
+        match self {
+            Packet::Handshake(p) | Packet::AuthOk(p) | Packet::AuthErr(p) | Packet::Query(p)
+            | Packet::Msg(p) | Packet::Data(p) | Packet::DataPart(p) | Packet::Other(_, p) => (ptype, p),
+        }
+    }
+
+    /// Splits a handshake payload into user, password, driver and optional application name.
+    pub fn handshake_fields(&self) -> Option<(&str, &str, &str, Option<&str>)> {
+        let Packet::Handshake(payload) = self else { return None };
+        let text = std::str::from_utf8(payload).ok()?;
+        let mut fields = text.splitn(4, '\0');
+        Some((fields.next()?, fields.next()?, fields.next()?, fields.next()))
+    }
+}
+
+// --- Codec ---
+
+/// Reads and writes single frames, rejecting payloads over `max_packet_size`.
+///
+/// `DataPart` frames are returned as they arrive; joining them is up to the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketCodec {
+    max_packet_size: usize,
+}
+
+impl Default for PacketCodec {
+    fn default() -> Self {
+        PacketCodec::new()
+    }
+}
+
+impl PacketCodec {
+    pub fn new() -> Self {
+        PacketCodec { max_packet_size: DEFAULT_MAX_PACKET_SIZE }
+    }
+
+    pub fn max_packet_size(mut self, bytes: usize) -> Self {
+        self.max_packet_size = bytes;
+        self
+    }
+
+    /// Reads one frame. A stream that ends before the header fails with `UnexpectedEof`.
+    pub fn read_from<R: Read>(&self, reader: &mut R) -> io::Result<Packet> {
+        let (ptype, len) = read_header(reader, self.max_packet_size)?;
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+        Ok(Packet::new(ptype, payload))
+    }
+
+    /// Writes one frame and flushes.
+    pub fn write_to<W: Write>(&self, writer: &mut W, packet: &Packet) -> io::Result<()> {
+        check_len(packet.payload().len(), self.max_packet_size)?;
+        send_packet(writer, packet.packet_type(), packet.payload())
+    }
+
+    pub async fn read_from_async<R: AsyncRead + Unpin>(&self, reader: &mut R) -> io::Result<Packet> {
+        let (ptype, len) = read_header_async(reader, self.max_packet_size).await?;
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload).await?;
+        Ok(Packet::new(ptype, payload))
+    }
+
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W, packet: &Packet) -> io::Result<()> {
+        check_len(packet.payload().len(), self.max_packet_size)?;
+        send_packet_async(writer, packet.packet_type(), packet.payload()).await
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = Packet;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Packet>> {
+        if src.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([src[1], src[2], src[3], src[4]]) as usize;
+        check_len(len, self.max_packet_size)?;
+        if src.len() < HEADER_LEN + len {
+            src.reserve(HEADER_LEN + len - src.len());
+            return Ok(None);
+        }
+        let ptype = src.get_u8();
+        src.advance(4);
+        Ok(Some(Packet::new(ptype, src.split_to(len).freeze())))
+    }
+}
+
+impl Encoder<Packet> for PacketCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: Packet, dst: &mut BytesMut) -> io::Result<()> {
+        let (ptype, payload) = packet.into_parts();
+        check_len(payload.len(), self.max_packet_size)?;
+        let header = header(ptype, payload.len())?;
+        dst.reserve(HEADER_LEN + payload.len());
+        dst.put_slice(&header);
+        dst.put_slice(&payload);
+        Ok(())
+    }
+}
+
+// --- Framing ---
+
+/// Builds the `PACKET_HANDSHAKE` payload: `user\0pass\0driver`, plus `\0app` when an application name is set.
+pub(crate) fn handshake_payload(user: &str, pass: &str, application_name: Option<&str>) -> String {
+    match application_name {
+        Some(app) => format!("{}\0{}\0{}\0{}", user, pass, DRIVER_SIG, app),
+        None => format!("{}\0{}\0{}", user, pass, DRIVER_SIG),
+    }
+}
+
+/// The header for a frame of `len` bytes. Fails if the length does not fit the `u32` field.
+fn header(ptype: u8, len: usize) -> io::Result<[u8; HEADER_LEN]> {
+    let len = u32::try_from(len).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Packet too large"))?;
+    let [a, b, c, d] = len.to_be_bytes();
+    Ok([ptype, a, b, c, d])
+}
+
+/// A whole frame in one buffer: over TLS each write becomes its own record and TCP segment.
+fn frame(ptype: u8, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&header(ptype, payload.len())?);
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+pub(crate) fn send_packet<W: Write>(stream: &mut W, ptype: u8, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&frame(ptype, payload)?)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Reads a frame header, rejecting frames longer than `max` bytes.
+pub(crate) fn read_header<R: Read>(stream: &mut R, max: usize) -> io::Result<(u8, usize)> {
+    let ptype = stream.read_u8()?;
+    let len = stream.read_u32::<BigEndian>()? as usize;
+    check_len(len, max)?;
+    Ok((ptype, len))
+}
+
+/// Reads a whole reply, joining `PACKET_DATA_PART` frames. `max` limits the joined payload.
+pub(crate) fn read_packet<R: Read>(stream: &mut R, max: usize) -> io::Result<(u8, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut continued = false;
+    loop {
+        let (ptype, len) = read_header(stream, max - buf.len())?;
+        let start = buf.len();
+        buf.resize(start + len, 0);
+        stream.read_exact(&mut buf[start..])?;
+        if let Some(ptype) = finish_part(ptype, continued)? {
+            return Ok((ptype, buf));
+        }
+        continued = true;
+    }
+}
+
+fn check_len(len: usize, max: usize) -> io::Result<()> {
+    if len > max {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Packet too large"));
+    }
+    Ok(())
+}
+
+/// Returns the reply type if a frame of `ptype` completes the reply, or `None` if more parts
+/// follow. `continued` is set when earlier frames were `PACKET_DATA_PART`.
+pub(crate) fn finish_part(ptype: u8, continued: bool) -> io::Result<Option<u8>> {
+    match ptype {
+        PACKET_DATA_PART => Ok(None),
+        PACKET_DATA => Ok(Some(ptype)),
+        _ if continued => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Packet type 0x{:02X} after partial data", ptype),
+        )),
+        _ => Ok(Some(ptype)),
+    }
+}
+
+pub(crate) async fn send_packet_async<W: AsyncWrite + Unpin>(stream: &mut W, ptype: u8, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&frame(ptype, payload)?).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+pub(crate) async fn read_header_async<R: AsyncRead + Unpin>(stream: &mut R, max: usize) -> io::Result<(u8, usize)> {
+    let ptype = stream.read_u8().await?;
+    let len = stream.read_u32().await? as usize;
+    check_len(len, max)?;
+    Ok((ptype, len))
+}
+
+pub(crate) async fn read_packet_async<R: AsyncRead + Unpin>(stream: &mut R, max: usize) -> io::Result<(u8, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut continued = false;
+    loop {
+        let (ptype, len) = read_header_async(stream, max - buf.len()).await?;
+        let start = buf.len();
+        buf.resize(start + len, 0);
+        stream.read_exact(&mut buf[start..]).await?;
+        if let Some(ptype) = finish_part(ptype, continued)? {
+            return Ok((ptype, buf));
+        }
+        continued = true;
+    }
+}
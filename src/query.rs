@@ -0,0 +1,313 @@
+//! A small query builder for the statements MaazDB understands.
+//!
+//! ```
+//! use maazdb_rs::query::{col, Desc, Select};
+//!
+//! let sql = Select::from("users")
+//!     .columns(["name", "age"])
+//!     .filter(col("age").gt(25).and(col("active").eq(true)))
+//!     .order_by("age", Desc)
+//!     .limit(2)
+//!     .offset(1)
+//!     .build()?;
+//! assert_eq!(sql, "SELECT name, age FROM users WHERE age > 25 AND active = TRUE ORDER BY age DESC LIMIT 2 OFFSET 1;");
+//! # Ok::<(), maazdb_rs::MaazDBError>(())
+//! ```
+//!
+//! Values are rendered with [`ToSql`], the same escaping [`bind`](crate::bind) uses, and
+//! table and column names must be plain identifiers. Anything invalid is reported by
+//! `build()` as a `ParameterError`. Only single-table statements are produced: the server
+//! has no joins or `GROUP BY`. Conditions are limited to `=`, `>`, `AND`, `OR` and
+//! parentheses, the forms the example SQL suites show the server accepting.
+
+use crate::params::identifier;
+use crate::{MaazDBError, ToSql};
+
+/// Sort direction for [`Select::order_by`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+pub use Order::{Asc, Desc};
+
+/// A column to build conditions on. See [`col`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column(String);
+
+/// Refers to a column, e.g. `col("age").gt(25)`.
+pub fn col(name: &str) -> Column {
+    Column(name.to_string())
+}
+
+/// How tightly a condition binds, to decide where parentheses are needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Or,
+    And,
+    Atom,
+}
+
+/// A `WHERE` condition. Combine with [`and`](Condition::and) and [`or`](Condition::or).
+#[derive(Debug)]
+pub struct Condition {
+    sql: Result<String, MaazDBError>,
+    precedence: Precedence,
+}
+
+impl Column {
+    fn compare<V: ToSql>(self, op: &str, value: V) -> Condition {
+        let sql = self.sql().and_then(|c| Ok(format!("{} {} {}", c, op, value.to_sql()?)));
+        Condition { sql, precedence: Precedence::Atom }
+    }
+
+    fn sql(&self) -> Result<String, MaazDBError> {
+        identifier(&self.0, "column").map(str::to_string)
+    }
+
+    /// `col = value`. A `NULL` value never matches.
+    pub fn eq<V: ToSql>(self, value: V) -> Condition {
+        self.compare("=", value)
+    }
+
+    /// `col > value`.
+    pub fn gt<V: ToSql>(self, value: V) -> Condition {
+        self.compare(">", value)
+    }
+}
+
+impl Condition {
+    /// Both conditions.
+    pub fn and(self, other: Condition) -> Condition {
+        self.combine("AND", Precedence::And, other)
+    }
+
+    /// Either condition.
+    pub fn or(self, other: Condition) -> Condition {
+        self.combine("OR", Precedence::Or, other)
+    }
+
+    fn combine(self, op: &str, precedence: Precedence, other: Condition) -> Condition {
+        let sql = self.operand(precedence).and_then(|l| Ok(format!("{} {} {}", l, op, other.operand(precedence)?)));
+        Condition { sql, precedence }
+    }
+
+    /// The SQL for this condition as an operand of an operator binding at `outer`.
+    fn operand(self, outer: Precedence) -> Result<String, MaazDBError> {
+        let wrap = self.precedence < outer;
+        self.sql.map(|s| if wrap { format!("({})", s) } else { s })
+    }
+}
+
+/// `filter` called twice means both conditions.
+fn add_filter(filter: &mut Option<Condition>, condition: Condition) {
+    *filter = Some(match filter.take() {
+        Some(existing) => existing.and(condition),
+        None => condition,
+    });
+}
+
+fn where_clause(out: &mut String, filter: Option<Condition>) -> Result<(), MaazDBError> {
+    if let Some(filter) = filter {
+        out.push_str(" WHERE ");
+        out.push_str(&filter.sql?);
+    }
+    Ok(())
+}
+
+/// `SELECT columns FROM table [WHERE ...] [ORDER BY ...] [LIMIT n] [OFFSET m];`
+#[derive(Debug)]
+pub struct Select {
+    table: String,
+    columns: Vec<String>,
+    filter: Option<Condition>,
+    order_by: Vec<(String, Order)>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl Select {
+    /// Every column (`*`) of `table` until [`columns`](Select::columns) is called.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from(table: &str) -> Self {
+        Select { table: table.to_string(), columns: Vec::new(), filter: None, order_by: Vec::new(), limit: None, offset: None }
+    }
+
+    pub fn columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.columns.extend(columns.into_iter().map(|c| c.as_ref().to_string()));
+        self
+    }
+
+    pub fn filter(mut self, condition: Condition) -> Self {
+        add_filter(&mut self.filter, condition);
+        self
+    }
+
+    /// Sorts by `column`. Later calls break ties of earlier ones.
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.order_by.push((column.to_string(), order));
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn build(self) -> Result<String, MaazDBError> {
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            self.columns.iter().map(|c| identifier(c, "column")).collect::<Result<Vec<_>, _>>()?.join(", ")
+        };
+        let mut sql = format!("SELECT {} FROM {}", columns, identifier(&self.table, "table")?);
+        where_clause(&mut sql, self.filter)?;
+        if !self.order_by.is_empty() {
+            let keys = self.order_by.iter().map(|(c, order)| {
+                identifier(c, "column").map(|c| match order {
+                    Asc => c.to_string(),
+                    Desc => format!("{} DESC", c),
+                })
+            });
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&keys.collect::<Result<Vec<_>, _>>()?.join(", "));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        sql.push(';');
+        Ok(sql)
+    }
+}
+
+/// `INSERT INTO table (columns) VALUES (...), (...);`
+#[derive(Debug)]
+pub struct Insert {
+    table: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Result<String, MaazDBError>>>,
+}
+
+impl Insert {
+    pub fn into(table: &str) -> Self {
+        Insert { table: table.to_string(), columns: Vec::new(), rows: vec![Vec::new()] }
+    }
+
+    /// Sets `column` in the current row. The first row fixes the column list.
+    pub fn value<V: ToSql>(mut self, column: &str, value: V) -> Self {
+        if self.rows.len() == 1 {
+            self.columns.push(column.to_string());
+        } else if self.columns.get(self.rows.last().map_or(0, Vec::len)).map(String::as_str) != Some(column) {
+            let error = MaazDBError::ParameterError(format!("Column '{}' is out of order in row {}", column, self.rows.len()));
+            self.rows.last_mut().expect("at least one row").push(Err(error));
+            return self;
+        }
+        self.rows.last_mut().expect("at least one row").push(value.to_sql());
+        self
+    }
+
+    /// Starts another row, which must set the same columns in the same order.
+    pub fn row(mut self) -> Self {
+        self.rows.push(Vec::new());
+        self
+    }
+
+    pub fn build(self) -> Result<String, MaazDBError> {
+        if self.columns.is_empty() {
+            return Err(MaazDBError::ParameterError("INSERT needs at least one value".into()));
+        }
+        let table = identifier(&self.table, "table")?;
+        let columns = self.columns.iter().map(|c| identifier(c, "column")).collect::<Result<Vec<_>, _>>()?;
+        let mut rows = Vec::with_capacity(self.rows.len());
+        for (i, row) in self.rows.into_iter().enumerate() {
+            let values = row.into_iter().collect::<Result<Vec<_>, _>>()?;
+            if values.len() != columns.len() {
+                return Err(MaazDBError::ParameterError(format!(
+                    "Row {} has {} values for {} columns", i + 1, values.len(), columns.len()
+                )));
+            }
+            rows.push(format!("({})", values.join(", ")));
+        }
+        Ok(format!("INSERT INTO {} ({}) VALUES {};", table, columns.join(", "), rows.join(", ")))
+    }
+}
+
+/// `UPDATE table SET column = value, ... [WHERE ...];`
+#[derive(Debug)]
+pub struct Update {
+    table: String,
+    assignments: Vec<(String, Result<String, MaazDBError>)>,
+    filter: Option<Condition>,
+}
+
+impl Update {
+    pub fn table(table: &str) -> Self {
+        Update { table: table.to_string(), assignments: Vec::new(), filter: None }
+    }
+
+    pub fn set<V: ToSql>(mut self, column: &str, value: V) -> Self {
+        self.assignments.push((column.to_string(), value.to_sql()));
+        self
+    }
+
+    /// Without a filter every row is updated.
+    pub fn filter(mut self, condition: Condition) -> Self {
+        add_filter(&mut self.filter, condition);
+        self
+    }
+
+    pub fn build(self) -> Result<String, MaazDBError> {
+        if self.assignments.is_empty() {
+            return Err(MaazDBError::ParameterError("UPDATE needs at least one SET".into()));
+        }
+        let mut set = Vec::with_capacity(self.assignments.len());
+        for (column, value) in self.assignments {
+            set.push(format!("{} = {}", identifier(&column, "column")?, value?));
+        }
+        let mut sql = format!("UPDATE {} SET {}", identifier(&self.table, "table")?, set.join(", "));
+        where_clause(&mut sql, self.filter)?;
+        sql.push(';');
+        Ok(sql)
+    }
+}
+
+/// `DELETE FROM table [WHERE ...];`
+#[derive(Debug)]
+pub struct Delete {
+    table: String,
+    filter: Option<Condition>,
+}
+
+impl Delete {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from(table: &str) -> Self {
+        Delete { table: table.to_string(), filter: None }
+    }
+
+    /// Without a filter every row is deleted.
+    pub fn filter(mut self, condition: Condition) -> Self {
+        add_filter(&mut self.filter, condition);
+        self
+    }
+
+    pub fn build(self) -> Result<String, MaazDBError> {
+        let mut sql = format!("DELETE FROM {}", identifier(&self.table, "table")?);
+        where_clause(&mut sql, self.filter)?;
+        sql.push(';');
+        Ok(sql)
+    }
+}
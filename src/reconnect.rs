@@ -0,0 +1,125 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// Opt-in rules for recovering from a broken connection.
+///
+/// When the stream fails, the next statement first re-runs the handshake with the stored
+/// credentials and restores the database selected with `USE`. Read-only statements
+/// (`SELECT`, `SHOW`, `DESCRIBE`, `EXPLAIN`) that fail mid-flight are retried with
+/// exponential backoff and jitter; anything else is reported to the caller, because the
+/// server may already have applied it.
+///
+/// A connection inside a [`crate::Transaction`] (or, for [`crate::AsyncMaazDB`], between a
+/// `BEGIN` and its `COMMIT`/`ROLLBACK`) is never reconnected, since the transaction would
+/// silently be lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub(crate) max_retries: u32,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attempts after the first failure, for both reconnecting and re-running a read-only query.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Delay before the first retry; doubled on every further attempt.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper limit on the delay between attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Delay before retry number `attempt` (starting at 0): somewhere between half and all
+    /// of the capped exponential backoff, so that many clients do not retry in lockstep.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.initial_backoff.saturating_mul(1u32 << attempt.min(16));
+        let cap = exp.min(self.max_backoff);
+        let jitter = (RandomState::new().hash_one(attempt) % 1000) as f64 / 1000.0;
+        cap.mul_f64(0.5 + jitter / 2.0)
+    }
+}
+
+/// Whether `sql` is a single statement that cannot change data, and so is safe to re-run.
+pub(crate) fn is_read_only(sql: &str) -> bool {
+    let body = sql.trim_end().trim_end_matches(';');
+    if body.contains(';') {
+        return false;
+    }
+    matches!(
+        first_keyword(body).to_ascii_uppercase().as_str(),
+        "SELECT" | "SHOW" | "DESCRIBE" | "DESC" | "EXPLAIN"
+    )
+}
+
+/// [`is_read_only`] for a statement given as raw bytes; anything that is not UTF-8 is treated as a write.
+pub(crate) fn is_read_only_bytes(sql: &[u8]) -> bool {
+    std::str::from_utf8(sql).is_ok_and(is_read_only)
+}
+
+/// The database named by a `USE <db>` statement, if `sql` is one.
+pub(crate) fn use_target(sql: &str) -> Option<&str> {
+    let body = skip_comments(sql);
+    let keyword = first_keyword(body);
+    if !keyword.eq_ignore_ascii_case("USE") {
+        return None;
+    }
+    let name = body[keyword.len()..].trim().trim_end_matches(';').trim_end();
+    (!name.is_empty() && !name.contains(char::is_whitespace)).then_some(name)
+}
+
+/// Whether `sql` opens (`BEGIN`, `START TRANSACTION`) or ends (`COMMIT`, `ROLLBACK`) a
+/// transaction; `None` for anything else, including `ROLLBACK TO SAVEPOINT`.
+pub(crate) fn transaction_change(sql: &str) -> Option<bool> {
+    let body = skip_comments(sql).trim_end().trim_end_matches(';').trim_end();
+    let mut words = body.split_whitespace().map(str::to_ascii_uppercase);
+    match (words.next()?.as_str(), words.next().as_deref()) {
+        ("BEGIN", _) | ("START", Some("TRANSACTION")) => Some(true),
+        ("COMMIT" | "END", _) => Some(false),
+        ("ROLLBACK", None | Some("WORK" | "TRANSACTION")) => Some(false),
+        _ => None,
+    }
+}
+
+fn first_keyword(sql: &str) -> &str {
+    let body = skip_comments(sql).trim_start_matches('(');
+    let end = body.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(body.len());
+    &body[..end]
+}
+
+/// Skips leading whitespace and `--` / `/* */` comments.
+fn skip_comments(mut sql: &str) -> &str {
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.split_once('\n').map_or("", |(_, after)| after);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.split_once("*/").map_or("", |(_, after)| after);
+        } else {
+            return sql;
+        }
+    }
+}
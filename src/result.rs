@@ -0,0 +1,252 @@
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value as Json;
+
+use crate::MaazDBError;
+use crate::protocol::PACKET_DATA;
+
+/// A single cell returned by the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    Text(String),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the cell as text, if it was sent as a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn from_json(json: &Json) -> Self {
+        match json {
+            Json::Null => Value::Null,
+            Json::Bool(b) => Value::Bool(*b),
+            Json::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Double(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Json::String(s) => Value::Text(s.clone()),
+            other => Value::Text(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Double(d) => write!(f, "{}", d),
+            Value::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// The error for a row-returning call whose statement only produced a message.
+pub(crate) fn no_rows_error(msg: &str) -> MaazDBError {
+    MaazDBError::ProtocolError(format!("Statement did not return rows: {}", msg))
+}
+
+/// Column names from the `headers` array of a result set.
+pub(crate) fn columns_from_json(headers: &[Json]) -> Arc<[String]> {
+    headers.iter()
+        .map(|h| h.as_str().map(str::to_string).unwrap_or_else(|| h.to_string()))
+        .collect()
+}
+
+/// A single row of a [`ResultSet`].
+#[derive(Debug, Clone)]
+pub struct Row {
+    columns: Arc<[String]>,
+    values: Vec<Value>,
+}
+
+impl Row {
+    /// Builds a row from one element of the `data` array.
+    pub(crate) fn from_json(columns: &Arc<[String]>, row: &Json) -> Result<Self, MaazDBError> {
+        let cells = row.as_array()
+            .ok_or_else(|| MaazDBError::ProtocolError("Result row is not an array".into()))?;
+        if cells.len() != columns.len() {
+            return Err(MaazDBError::ProtocolError(format!(
+                "Result row has {} values but there are {} columns", cells.len(), columns.len()
+            )));
+        }
+        Ok(Row {
+            columns: columns.clone(),
+            values: cells.iter().map(Value::from_json).collect(),
+        })
+    }
+
+    /// Column names, shared with the owning result set.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the cell at position `idx`.
+    pub fn get(&self, idx: usize) -> Option<&Value> {
+        self.values.get(idx)
+    }
+
+    /// Returns the cell in the column called `name` (case-insensitive).
+    pub fn get_named(&self, name: &str) -> Option<&Value> {
+        self.column_index(name).and_then(|i| self.values.get(i))
+    }
+
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Rows and column names decoded from a `PACKET_DATA` response.
+#[derive(Debug, Clone)]
+pub struct ResultSet {
+    columns: Arc<[String]>,
+    rows: Vec<Row>,
+}
+
+impl ResultSet {
+    /// Parses the server's `{"headers":[...],"data":[[...]]}` payload.
+    pub fn from_json(payload: &str) -> Result<Self, MaazDBError> {
+        let parsed: Json = serde_json::from_str(payload)
+            .map_err(|e| MaazDBError::ProtocolError(format!("Invalid result set: {}", e)))?;
+        Self::from_parsed(&parsed)
+    }
+
+    fn from_parsed(parsed: &Json) -> Result<Self, MaazDBError> {
+        let headers = parsed["headers"].as_array()
+            .ok_or_else(|| MaazDBError::ProtocolError("Result set is missing 'headers'".into()))?;
+        let data = parsed["data"].as_array()
+            .ok_or_else(|| MaazDBError::ProtocolError("Result set is missing 'data'".into()))?;
+
+        let columns = columns_from_json(headers);
+        let rows = data.iter()
+            .map(|row| Row::from_json(&columns, row))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ResultSet { columns, rows })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Number of rows returned.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Row> {
+        self.rows.get(idx)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Row> {
+        self.rows.iter()
+    }
+}
+
+impl IntoIterator for ResultSet {
+    type Item = Row;
+    type IntoIter = std::vec::IntoIter<Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ResultSet {
+    type Item = &'a Row;
+    type IntoIter = std::slice::Iter<'a, Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter()
+    }
+}
+
+/// The typed outcome of a single statement.
+#[derive(Debug, Clone)]
+pub enum QueryResult {
+    /// The server returned a table (`SELECT`, `SHOW`, `DESCRIBE`, ...).
+    Rows(ResultSet),
+    /// The server returned a status message (`INSERT`, `CREATE`, ...).
+    CommandComplete(String),
+}
+
+impl QueryResult {
+    /// Interprets an already validated `PACKET_MSG`/`PACKET_DATA` response.
+    ///
+    /// A `PACKET_DATA` payload that is not JSON at all is plain text, so it becomes a message;
+    /// JSON that is not a `headers`/`data` table is a protocol error.
+    pub(crate) fn from_packet(ptype: u8, msg: String) -> Result<Self, MaazDBError> {
+        if ptype != PACKET_DATA {
+            return Ok(QueryResult::CommandComplete(msg));
+        }
+        match serde_json::from_str::<Json>(&msg) {
+            Ok(parsed) => Ok(QueryResult::Rows(ResultSet::from_parsed(&parsed)?)),
+            Err(_) => Ok(QueryResult::CommandComplete(msg)),
+        }
+    }
+
+    /// Returns the result set, or an error if the statement only produced a message.
+    pub(crate) fn expect_rows(self) -> Result<ResultSet, MaazDBError> {
+        match self {
+            QueryResult::Rows(rs) => Ok(rs),
+            QueryResult::CommandComplete(msg) => Err(no_rows_error(&msg)),
+        }
+    }
+
+    /// Returns the result set, if the statement produced one.
+    pub fn rows(&self) -> Option<&ResultSet> {
+        match self {
+            QueryResult::Rows(rs) => Some(rs),
+            QueryResult::CommandComplete(_) => None,
+        }
+    }
+
+    pub fn into_rows(self) -> Option<ResultSet> {
+        match self {
+            QueryResult::Rows(rs) => Some(rs),
+            QueryResult::CommandComplete(_) => None,
+        }
+    }
+
+    /// Returns the status message, if the statement produced one.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            QueryResult::Rows(_) => None,
+            QueryResult::CommandComplete(msg) => Some(msg),
+        }
+    }
+}
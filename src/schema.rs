@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::params::identifier;
+use crate::{AsyncMaazDB, MaazDB, MaazDBError, ResultSet, Row, Value};
+
+/// A column type as reported by `DESCRIBE`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DataType {
+    Serial,
+    Int,
+    Double,
+    Text,
+    Bool,
+    Timestamp,
+    Uuid,
+    Blob,
+    /// A type this driver does not know, as the server spelled it.
+    Other(String),
+}
+
+impl FromStr for DataType {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_uppercase().as_str() {
+            "SERIAL" => DataType::Serial,
+            "INT" | "INTEGER" | "BIGINT" => DataType::Int,
+            "DOUBLE" | "FLOAT" | "REAL" => DataType::Double,
+            "TEXT" | "VARCHAR" | "STRING" => DataType::Text,
+            "BOOL" | "BOOLEAN" => DataType::Bool,
+            "TIMESTAMP" | "DATETIME" => DataType::Timestamp,
+            "UUID" => DataType::Uuid,
+            "BLOB" | "BYTEA" => DataType::Blob,
+            _ => DataType::Other(s.trim().to_string()),
+        })
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DataType::Serial => "SERIAL",
+            DataType::Int => "INT",
+            DataType::Double => "DOUBLE",
+            DataType::Text => "TEXT",
+            DataType::Bool => "BOOL",
+            DataType::Timestamp => "TIMESTAMP",
+            DataType::Uuid => "UUID",
+            DataType::Blob => "BLOB",
+            DataType::Other(name) => name,
+        };
+        f.write_str(name)
+    }
+}
+
+/// The column a foreign key points at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.table, self.column)
+    }
+}
+
+/// One column of a [`TableSchema`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub data_type: DataType,
+    pub nullable: bool,
+    pub primary_key: bool,
+    /// The `DEFAULT` expression, as SQL.
+    pub default: Option<String>,
+    pub references: Option<ForeignKey>,
+}
+
+/// The columns of a table, in declaration order. Returned by [`MaazDB::describe`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnInfo>,
+}
+
+impl TableSchema {
+    /// Builds a schema from the reply to `DESCRIBE <name>;`.
+    ///
+    /// Columns are found by header (`column`, `type`, `nullable`, `primary_key`, `default`,
+    /// `references`), falling back to the first two positions for the name and type.
+    pub fn from_describe(name: &str, rs: &ResultSet) -> Result<Self, MaazDBError> {
+        let columns = rs.iter().map(column_info).collect::<Result<_, _>>()?;
+        Ok(TableSchema { name: name.to_string(), columns })
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnInfo> {
+        self.columns.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The primary key columns.
+    pub fn primary_key(&self) -> impl Iterator<Item = &ColumnInfo> {
+        self.columns.iter().filter(|c| c.primary_key)
+    }
+}
+
+fn column_info(row: &Row) -> Result<ColumnInfo, MaazDBError> {
+    let cell = |names: &[&str], fallback: Option<usize>| {
+        names.iter().find_map(|n| row.column_index(n)).or(fallback.filter(|i| *i < row.len()))
+            .and_then(|i| row.get(i))
+            .filter(|v| !v.is_null())
+    };
+    let text = |value: &Value| match value {
+        Value::Text(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    let name = cell(&["column", "field", "name"], Some(0))
+        .map(text)
+        .ok_or_else(|| MaazDBError::ProtocolError("DESCRIBE row has no column name".into()))?;
+    let data_type = cell(&["type", "data_type"], Some(1)).map(text).unwrap_or_default().parse().unwrap_or_else(|e| match e {});
+    let primary_key = cell(&["primary_key", "key", "pk"], None).is_some_and(flag);
+    let nullable = cell(&["nullable", "null"], None).map_or(!primary_key, flag);
+    let default = cell(&["default"], None).map(text);
+    let references = cell(&["references", "foreign_key"], None).map(text).and_then(|r| parse_reference(&r));
+
+    Ok(ColumnInfo { name, data_type, nullable, primary_key, default, references })
+}
+
+/// `true`, `1`, `YES` or `PRI` (MySQL-style key column).
+fn flag(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Int(i) => *i != 0,
+        Value::Text(s) => matches!(s.trim().to_ascii_uppercase().as_str(), "TRUE" | "T" | "1" | "YES" | "Y" | "PRI"),
+        _ => false,
+    }
+}
+
+/// `users(id)` or `users.id`.
+fn parse_reference(s: &str) -> Option<ForeignKey> {
+    let s = s.trim();
+    let (table, column) = match s.strip_suffix(')').and_then(|rest| rest.split_once('(')) {
+        Some(parts) => parts,
+        None => s.split_once('.')?,
+    };
+    let (table, column) = (table.trim(), column.trim());
+    (!table.is_empty() && !column.is_empty()).then(|| ForeignKey { table: table.to_string(), column: column.to_string() })
+}
+
+/// The first column of every row, e.g. the names listed by `SHOW TABLES`.
+fn names(rs: &ResultSet) -> Vec<String> {
+    rs.iter().filter_map(|row| row.get(0)).filter(|v| !v.is_null()).map(|v| match v {
+        Value::Text(s) => s.clone(),
+        other => other.to_string(),
+    }).collect()
+}
+
+fn describe_sql(table: &str) -> Result<String, MaazDBError> {
+    Ok(format!("DESCRIBE {};", identifier(table, "table")?))
+}
+
+impl MaazDB {
+    /// Names of all databases (`SHOW DATABASES`).
+    pub fn list_databases(&mut self) -> Result<Vec<String>, MaazDBError> {
+        Ok(names(&self.execute("SHOW DATABASES;")?.expect_rows()?))
+    }
+
+    /// Names of the tables in the current database (`SHOW TABLES`).
+    pub fn list_tables(&mut self) -> Result<Vec<String>, MaazDBError> {
+        Ok(names(&self.execute("SHOW TABLES;")?.expect_rows()?))
+    }
+
+    /// The columns of `table` (`DESCRIBE`).
+    pub fn describe(&mut self, table: &str) -> Result<TableSchema, MaazDBError> {
+        let rs = self.execute(&describe_sql(table)?)?.expect_rows()?;
+        TableSchema::from_describe(table, &rs)
+    }
+}
+
+impl AsyncMaazDB {
+    /// Async version of [`MaazDB::list_databases`].
+    pub async fn list_databases(&mut self) -> Result<Vec<String>, MaazDBError> {
+        Ok(names(&self.execute("SHOW DATABASES;").await?.expect_rows()?))
+    }
+
+    /// Async version of [`MaazDB::list_tables`].
+    pub async fn list_tables(&mut self) -> Result<Vec<String>, MaazDBError> {
+        Ok(names(&self.execute("SHOW TABLES;").await?.expect_rows()?))
+    }
+
+    /// Async version of [`MaazDB::describe`].
+    pub async fn describe(&mut self, table: &str) -> Result<TableSchema, MaazDBError> {
+        let rs = self.execute(&describe_sql(table)?).await?.expect_rows()?;
+        TableSchema::from_describe(table, &rs)
+    }
+}
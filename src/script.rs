@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::Path;
+
+use crate::{AsyncMaazDB, ConnectionState, MaazDB, MaazDBError, QueryResult};
+
+/// What [`MaazDB::execute_script`] does after a statement fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    /// Skip the rest of the script.
+    #[default]
+    Stop,
+    /// Run the remaining statements anyway. A broken connection still stops the script.
+    Continue,
+}
+
+/// One statement of a script, with comments removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStatement {
+    /// The statement text, without the terminating `;`.
+    pub sql: String,
+    /// 1-based line of the script the statement starts on.
+    pub line: usize,
+}
+
+/// A statement that was sent, and what came back.
+#[derive(Debug)]
+pub struct StatementOutcome {
+    pub statement: ScriptStatement,
+    pub result: Result<QueryResult, MaazDBError>,
+}
+
+/// Per-statement results of [`MaazDB::execute_script`].
+#[derive(Debug, Default)]
+pub struct ScriptReport {
+    /// Every statement that was sent, in script order.
+    pub outcomes: Vec<StatementOutcome>,
+    /// Statements left unsent after a failure.
+    pub skipped: usize,
+}
+
+impl ScriptReport {
+    /// `true` if every statement was sent and succeeded.
+    pub fn is_success(&self) -> bool {
+        self.skipped == 0 && self.outcomes.iter().all(|o| o.result.is_ok())
+    }
+
+    /// The statements that failed, with their errors.
+    pub fn errors(&self) -> impl Iterator<Item = (&ScriptStatement, &MaazDBError)> {
+        self.outcomes.iter().filter_map(|o| o.result.as_ref().err().map(|e| (&o.statement, e)))
+    }
+
+    /// The results in order, or the first error.
+    pub fn into_result(self) -> Result<Vec<QueryResult>, MaazDBError> {
+        self.outcomes.into_iter().map(|o| o.result).collect()
+    }
+}
+
+/// Splits a script into statements on `;`.
+///
+/// Semicolons inside string literals (`'it''s; fine'`), quoted identifiers and comments do
+/// not end a statement. `--` and `/* */` comments are dropped and empty statements skipped.
+pub fn split_statements(script: &str) -> Vec<ScriptStatement> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    // Line of the first token of `current`, once it has one
+    let mut start: Option<usize> = None;
+    let mut line = 1;
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                start.get_or_insert(line);
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == quote {
+                        // A doubled quote is an escaped quote, not the end of the literal
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                current.push_str(&script[last..i]);
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                last = i;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                current.push_str(&script[last..i]);
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                // Keeps `a/* */b` as two words
+                current.push(' ');
+                last = i;
+            }
+            b';' => {
+                current.push_str(&script[last..i]);
+                push_statement(&mut statements, &mut current, start.take());
+                i += 1;
+                last = i;
+            }
+            b'\n' => {
+                line += 1;
+                i += 1;
+            }
+            c => {
+                if !c.is_ascii_whitespace() {
+                    start.get_or_insert(line);
+                }
+                i += 1;
+            }
+        }
+    }
+    current.push_str(&script[last..]);
+    push_statement(&mut statements, &mut current, start);
+    statements
+}
+
+fn push_statement(statements: &mut Vec<ScriptStatement>, current: &mut String, start: Option<usize>) {
+    let sql = current.trim();
+    if let Some(line) = start
+        && !sql.is_empty()
+    {
+        statements.push(ScriptStatement { sql: sql.to_string(), line });
+    }
+    current.clear();
+}
+
+impl MaazDB {
+    /// Runs every statement of a multi-statement script in order.
+    ///
+    /// The script is split with [`split_statements`]. Each statement's result is recorded in
+    /// the report; with [`OnError::Stop`] the first failure ends the script.
+    pub fn execute_script(&mut self, script: &str, on_error: OnError) -> ScriptReport {
+        let statements = split_statements(script);
+        let total = statements.len();
+        let mut report = ScriptReport::default();
+
+        for statement in statements {
+            let result = self.execute(&statement.sql);
+            let failed = result.is_err();
+            report.outcomes.push(StatementOutcome { statement, result });
+            if failed && (on_error == OnError::Stop || self.state() == ConnectionState::Broken) {
+                break;
+            }
+        }
+        report.skipped = total - report.outcomes.len();
+        report
+    }
+
+    /// Reads a `.sql` file and runs it with [`MaazDB::execute_script`].
+    pub fn execute_file(&mut self, path: impl AsRef<Path>, on_error: OnError) -> Result<ScriptReport, MaazDBError> {
+        let script = fs::read_to_string(path)?;
+        Ok(self.execute_script(&script, on_error))
+    }
+}
+
+impl AsyncMaazDB {
+    /// Async version of [`MaazDB::execute_script`].
+    pub async fn execute_script(&mut self, script: &str, on_error: OnError) -> ScriptReport {
+        let statements = split_statements(script);
+        let total = statements.len();
+        let mut report = ScriptReport::default();
+
+        for statement in statements {
+            let result = self.execute(&statement.sql).await;
+            let failed = result.is_err();
+            report.outcomes.push(StatementOutcome { statement, result });
+            if failed && (on_error == OnError::Stop || self.state() == ConnectionState::Broken) {
+                break;
+            }
+        }
+        report.skipped = total - report.outcomes.len();
+        report
+    }
+
+    /// Async version of [`MaazDB::execute_file`].
+    pub async fn execute_file(&mut self, path: impl AsRef<Path>, on_error: OnError) -> Result<ScriptReport, MaazDBError> {
+        let script = tokio::fs::read_to_string(path).await?;
+        Ok(self.execute_script(&script, on_error).await)
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+/// Broad class of a server-reported error, derived from the server's message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The statement could not be parsed.
+    Syntax,
+    /// A FOREIGN KEY, PRIMARY KEY, UNIQUE or NOT NULL rule rejected the change.
+    ConstraintViolation,
+    /// The database, table or user being created already exists.
+    AlreadyExists,
+    UnknownDatabase,
+    UnknownTable,
+    UnknownColumn,
+    PermissionDenied,
+    /// A temporary condition (timeout, lock, busy server); the statement may succeed if retried.
+    Transient,
+    Other,
+}
+
+impl ErrorCategory {
+    /// The closest SQLSTATE code, for callers that already branch on those.
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            ErrorCategory::Syntax => "42601",
+            ErrorCategory::ConstraintViolation => "23000",
+            ErrorCategory::AlreadyExists => "42P07",
+            ErrorCategory::UnknownDatabase => "3D000",
+            ErrorCategory::UnknownTable => "42P01",
+            ErrorCategory::UnknownColumn => "42703",
+            ErrorCategory::PermissionDenied => "42501",
+            ErrorCategory::Transient => "40001",
+            ErrorCategory::Other => "XX000",
+        }
+    }
+
+    /// Classifies a server error message by the phrases MaazDB uses.
+    ///
+    /// Quoted names are ignored, so "Table 'unique_codes' not found" is not a constraint
+    /// violation. For "not found" errors the category follows the object the message is
+    /// about, so "Column 'x' not found in table 'y'" is an unknown column, not an unknown table.
+    pub fn classify(message: &str) -> Self {
+        let msg = unquoted(&message.to_ascii_lowercase());
+        let has = |words: &[&str]| words.iter().any(|w| msg.contains(w));
+
+        if has(&["syntax", "parse error", "unexpected token", "unexpected end", "unrecognized", "invalid sql"]) {
+            ErrorCategory::Syntax
+        } else if has(&["permission", "denied", "not authorized", "unauthorized", "not allowed"]) {
+            ErrorCategory::PermissionDenied
+        } else if has(&["already exists"]) {
+            ErrorCategory::AlreadyExists
+        } else if has(&["foreign key", "constraint", "duplicate", "unique", "not null", "primary key violation"]) {
+            ErrorCategory::ConstraintViolation
+        } else if has(&["no database selected"]) {
+            ErrorCategory::UnknownDatabase
+        } else if let Some(category) = missing_object(&msg) {
+            category
+        } else if has(&["timeout", "timed out", "deadlock", "locked", "busy", "try again", "temporarily"]) {
+            ErrorCategory::Transient
+        } else {
+            ErrorCategory::Other
+        }
+    }
+}
+
+/// Phrases that follow the missing object: "Table 'x' not found".
+const MISSING_AFTER: &[&str] = &["not found", "does not exist", "doesn't exist", "not exist"];
+/// Phrases that precede it: "Unknown column 'x' in table 'y'".
+const MISSING_BEFORE: &[&str] = &["unknown", "no such"];
+
+/// The category of a "not found" message, from the object word nearest the first such phrase:
+/// the last one before a trailing phrase, or the first one after a leading phrase.
+fn missing_object(msg: &str) -> Option<ErrorCategory> {
+    let first = |phrases: &'static [&'static str]| {
+        phrases.iter().filter_map(|p| msg.find(p).map(|i| (i, p.len()))).min()
+    };
+    let object = |word: &str| match word.trim_end_matches('s') {
+        "database" => Some(ErrorCategory::UnknownDatabase),
+        "table" => Some(ErrorCategory::UnknownTable),
+        "column" | "field" => Some(ErrorCategory::UnknownColumn),
+        _ => None,
+    };
+    let words = |s: &str| s.split(|c: char| !c.is_ascii_alphabetic()).filter(|w| !w.is_empty()).map(str::to_string).collect::<Vec<_>>();
+
+    match (first(MISSING_AFTER), first(MISSING_BEFORE)) {
+        (Some((at, _)), before) if before.is_none_or(|(b, _)| at < b) => {
+            words(&msg[..at]).iter().rev().find_map(|w| object(w))
+        }
+        (_, Some((at, len))) => words(&msg[at + len..]).iter().find_map(|w| object(w)),
+        _ => None,
+    }
+}
+
+/// `msg` with quoted names blanked out, so a table called 'column_log' is not taken for a column.
+/// An apostrophe right after a letter ("doesn't") does not open a quote.
+fn unquoted(msg: &str) -> String {
+    let mut out = String::with_capacity(msg.len());
+    let mut quote = None;
+    let mut prev = ' ';
+    for c in msg.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => out.push(' '),
+            None if matches!(c, '"' | '`') || (c == '\'' && !prev.is_ascii_alphanumeric()) => quote = Some(c),
+            None => out.push(c),
+        }
+        prev = c;
+    }
+    out
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorCategory::Syntax => "syntax error",
+            ErrorCategory::ConstraintViolation => "constraint violation",
+            ErrorCategory::AlreadyExists => "already exists",
+            ErrorCategory::UnknownDatabase => "unknown database",
+            ErrorCategory::UnknownTable => "unknown table",
+            ErrorCategory::UnknownColumn => "unknown column",
+            ErrorCategory::PermissionDenied => "permission denied",
+            ErrorCategory::Transient => "transient error",
+            ErrorCategory::Other => "error",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An error reported by the server for a specific statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerError {
+    pub category: ErrorCategory,
+    /// Packet type the server used for the reply.
+    pub packet_type: u8,
+    /// The server's message, unchanged.
+    pub message: String,
+    /// The statement that failed.
+    pub statement: String,
+}
+
+impl ServerError {
+    pub(crate) fn new(packet_type: u8, message: String, statement: &str) -> Self {
+        ServerError {
+            category: ErrorCategory::classify(&message),
+            packet_type,
+            message,
+            statement: statement.to_string(),
+        }
+    }
+
+    /// SQLSTATE-style code for the category.
+    pub fn code(&self) -> &'static str {
+        self.category.sqlstate()
+    }
+
+    /// Whether re-running the same statement might succeed.
+    pub fn is_retryable(&self) -> bool {
+        self.category == ErrorCategory::Transient
+    }
+
+    pub fn is_constraint_violation(&self) -> bool {
+        self.category == ErrorCategory::ConstraintViolation
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {} (statement: {})", self.category, self.code(), self.message, self.statement)
+    }
+}
+
+impl std::error::Error for ServerError {}
@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// Lifecycle of a client connection, as seen by the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// Authenticated, running the setup statements from [`crate::ConnectOptions`].
+    Connecting,
+    /// Idle and usable.
+    Ready,
+    /// A statement has been sent and its reply not yet read. Seen afterwards only if the
+    /// call was interrupted (e.g. an async query was cancelled), which leaves the stream
+    /// out of step, so the next call treats the connection as broken.
+    InQuery,
+    /// An IO or TLS failure left the stream unusable. A reconnect is needed.
+    Broken,
+    /// Closed by the caller.
+    Closed,
+}
+
+impl ConnectionState {
+    /// Whether a statement may be sent in this state.
+    pub(crate) fn can_send(self) -> bool {
+        matches!(self, ConnectionState::Connecting | ConnectionState::Ready)
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Ready => "ready",
+            ConnectionState::InQuery => "in query",
+            ConnectionState::Broken => "broken",
+            ConnectionState::Closed => "closed",
+        };
+        f.write_str(name)
+    }
+}
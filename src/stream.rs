@@ -0,0 +1,418 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{self, Stream};
+use serde_json::Value as Json;
+use tokio::io::AsyncReadExt;
+
+use crate::async_client::with_timeout;
+use crate::protocol::{self, PACKET_DATA, PACKET_DATA_PART};
+use crate::result::{columns_from_json, Row};
+use crate::{AsyncMaazDB, ConnectionState, MaazDB, MaazDBError};
+
+/// Bytes read from the socket per step while streaming.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// What the decoder expects next in `{"headers":[...],"data":[[...],...]}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    ObjectStart,
+    /// A key, or `}` right after `{`.
+    KeyOrEnd,
+    Key,
+    Colon,
+    Value,
+    /// A row, or `]` right after `[`.
+    RowOrEnd,
+    Row,
+    /// `,` or `]` after a row.
+    AfterRow,
+    /// `,` or `}` after a member.
+    AfterMember,
+    Done,
+}
+
+/// Progress through a JSON value that may be split across chunks.
+#[derive(Debug, Default)]
+struct Scan {
+    active: bool,
+    /// Bytes scanned so far, from the start of the value.
+    offset: usize,
+    depth: usize,
+    in_string: bool,
+    escape: bool,
+}
+
+/// Decodes a `PACKET_DATA` payload fed in arbitrary chunks, one row at a time.
+///
+/// Only the row being decoded is buffered, so the payload itself can be any size.
+pub(crate) struct RowDecoder {
+    buf: Vec<u8>,
+    pos: usize,
+    expect: Expect,
+    scan: Scan,
+    key: String,
+    max_value: usize,
+    columns: Option<Arc<[String]>>,
+    seen_data: bool,
+    /// Rows received before `headers`, decoded once the column names are known.
+    early: Vec<Json>,
+    ready: VecDeque<Row>,
+}
+
+impl RowDecoder {
+    /// `max_value` limits the size of a single row (or of the headers).
+    pub(crate) fn new(max_value: usize) -> Self {
+        RowDecoder {
+            buf: Vec::new(),
+            pos: 0,
+            expect: Expect::ObjectStart,
+            scan: Scan::default(),
+            key: String::new(),
+            max_value,
+            columns: None,
+            seen_data: false,
+            early: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn feed(&mut self, data: &[u8]) -> Result<(), MaazDBError> {
+        self.buf.extend_from_slice(data);
+        self.parse()?;
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// Next fully decoded row, if any.
+    pub(crate) fn pop(&mut self) -> Option<Row> {
+        self.ready.pop_front()
+    }
+
+    /// Checks that the payload was complete. Call once all of it has been fed.
+    pub(crate) fn finish(&mut self) -> Result<(), MaazDBError> {
+        if self.expect != Expect::Done {
+            return Err(invalid("Result set ended unexpectedly"));
+        }
+        if self.columns.is_none() {
+            return Err(invalid("Result set is missing 'headers'"));
+        }
+        if !self.seen_data {
+            return Err(invalid("Result set is missing 'data'"));
+        }
+        Ok(())
+    }
+
+    fn parse(&mut self) -> Result<(), MaazDBError> {
+        loop {
+            if !self.scan.active {
+                while self.buf.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+                    self.pos += 1;
+                }
+                let Some(&b) = self.buf.get(self.pos) else { return Ok(()) };
+                let next = match (self.expect, b) {
+                    (Expect::ObjectStart, b'{') => Expect::KeyOrEnd,
+                    (Expect::KeyOrEnd | Expect::AfterMember, b'}') => Expect::Done,
+                    (Expect::AfterMember, b',') => Expect::Key,
+                    (Expect::Colon, b':') => Expect::Value,
+                    (Expect::Value, b'[') if self.key == "data" => {
+                        self.seen_data = true;
+                        Expect::RowOrEnd
+                    }
+                    (Expect::RowOrEnd | Expect::AfterRow, b']') => Expect::AfterMember,
+                    (Expect::AfterRow, b',') => Expect::Row,
+                    (Expect::KeyOrEnd | Expect::Key | Expect::Value | Expect::RowOrEnd | Expect::Row, _) => {
+                        self.scan = Scan { active: true, ..Scan::default() };
+                        self.expect
+                    }
+                    (_, b) => return Err(invalid(&format!("Unexpected '{}' in result set", b as char))),
+                };
+                if !self.scan.active {
+                    self.pos += 1;
+                    self.expect = next;
+                    continue;
+                }
+            }
+
+            let Some(end) = self.scan_value()? else { return Ok(()) };
+            if end > self.max_value {
+                return Err(self.too_large());
+            }
+            let value = &self.buf[self.pos..self.pos + end];
+            self.pos += end;
+            self.scan.active = false;
+
+            self.expect = match self.expect {
+                Expect::KeyOrEnd | Expect::Key => {
+                    self.key = serde_json::from_slice(value).map_err(json_error)?;
+                    Expect::Colon
+                }
+                Expect::Value => {
+                    if self.key == "headers" {
+                        let headers: Json = serde_json::from_slice(value).map_err(json_error)?;
+                        let headers = headers.as_array()
+                            .ok_or_else(|| invalid("Result set is missing 'headers'"))?;
+                        let columns = columns_from_json(headers);
+                        for row in self.early.drain(..) {
+                            self.ready.push_back(Row::from_json(&columns, &row)?);
+                        }
+                        self.columns = Some(columns);
+                    }
+                    Expect::AfterMember
+                }
+                _ => {
+                    let row: Json = serde_json::from_slice(value).map_err(json_error)?;
+                    match &self.columns {
+                        Some(columns) => self.ready.push_back(Row::from_json(columns, &row)?),
+                        None => self.early.push(row),
+                    }
+                    Expect::AfterRow
+                }
+            };
+        }
+    }
+
+    /// Scans the value starting at `pos`, returning its length once it is complete.
+    fn scan_value(&mut self) -> Result<Option<usize>, MaazDBError> {
+        let bytes = &self.buf[self.pos..];
+        let s = &mut self.scan;
+        while let Some(&b) = bytes.get(s.offset) {
+            if s.in_string {
+                if s.escape {
+                    s.escape = false;
+                } else if b == b'\\' {
+                    s.escape = true;
+                } else if b == b'"' {
+                    s.in_string = false;
+                    if s.depth == 0 {
+                        return Ok(Some(s.offset + 1));
+                    }
+                }
+            } else {
+                match b {
+                    b'"' => s.in_string = true,
+                    b'[' | b'{' => s.depth += 1,
+                    b']' | b'}' if s.depth > 0 => {
+                        s.depth -= 1;
+                        if s.depth == 0 {
+                            return Ok(Some(s.offset + 1));
+                        }
+                    }
+                    // The end of a bare scalar (number, true, false, null).
+                    b',' | b']' | b'}' if s.depth == 0 => return Ok(Some(s.offset)),
+                    b if b.is_ascii_whitespace() && s.depth == 0 => return Ok(Some(s.offset)),
+                    _ => {}
+                }
+            }
+            s.offset += 1;
+        }
+        if s.offset > self.max_value {
+            return Err(self.too_large());
+        }
+        Ok(None)
+    }
+
+    fn too_large(&self) -> MaazDBError {
+        invalid(&format!("Result row exceeds max_packet_size ({} bytes)", self.max_value))
+    }
+}
+
+fn invalid(msg: &str) -> MaazDBError {
+    MaazDBError::ProtocolError(msg.to_string())
+}
+
+fn json_error(e: serde_json::Error) -> MaazDBError {
+    MaazDBError::ProtocolError(format!("Invalid result set: {}", e))
+}
+
+// --- Blocking ---
+
+/// Rows of a result set, decoded as they arrive. Returned by [`MaazDB::query_iter`].
+///
+/// The connection is busy until the iterator is exhausted or dropped. Dropping it early
+/// reads and discards the rest of the reply, so the connection stays usable: this takes
+/// constant memory, but blocks until the server has sent every remaining row, however many
+/// there are. If only the first rows are wanted, put a `LIMIT` in the query. If the rest of
+/// the reply cannot be read, the connection is left [`ConnectionState::Broken`].
+pub struct RowIter<'a> {
+    conn: &'a mut MaazDB,
+    decoder: RowDecoder,
+    /// Bytes left in the current frame.
+    remaining: usize,
+    /// Whether the current frame is the last one.
+    last: bool,
+    done: bool,
+    chunk: Vec<u8>,
+}
+
+impl<'a> RowIter<'a> {
+    pub(crate) fn new(conn: &'a mut MaazDB, ptype: u8, len: usize) -> Self {
+        let max_value = conn.options.max_packet_size;
+        RowIter {
+            conn,
+            decoder: RowDecoder::new(max_value),
+            remaining: len,
+            last: ptype == PACKET_DATA,
+            done: false,
+            chunk: vec![0; CHUNK_SIZE],
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<Row>, MaazDBError> {
+        loop {
+            if let Some(row) = self.decoder.pop() {
+                return Ok(Some(row));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            if self.remaining == 0 {
+                if self.last {
+                    self.decoder.finish()?;
+                    self.done = true;
+                    self.conn.state = ConnectionState::Ready;
+                } else {
+                    let (ptype, len) = protocol::read_header(&mut self.conn.stream, usize::MAX)?;
+                    self.last = protocol::finish_part(ptype, true)?.is_some();
+                    self.remaining = len;
+                }
+                continue;
+            }
+            let n = self.remaining.min(CHUNK_SIZE);
+            self.conn.stream.read_exact(&mut self.chunk[..n])?;
+            self.remaining -= n;
+            self.decoder.feed(&self.chunk[..n])?;
+        }
+    }
+
+    /// Reads and discards the rest of the reply.
+    fn drain(&mut self) -> io::Result<()> {
+        loop {
+            io::copy(&mut (&mut self.conn.stream).take(self.remaining as u64), &mut io::sink())?;
+            if self.last {
+                return Ok(());
+            }
+            let (ptype, len) = protocol::read_header(&mut self.conn.stream, usize::MAX)?;
+            self.last = protocol::finish_part(ptype, true)?.is_some();
+            self.remaining = len;
+        }
+    }
+}
+
+impl Iterator for RowIter<'_> {
+    type Item = Result<Row, MaazDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_row() {
+            Ok(row) => row.map(Ok),
+            Err(e) => {
+                // The reply was only partly read, so the stream is out of step.
+                self.done = true;
+                self.decoder.ready.clear();
+                self.conn.state = ConnectionState::Broken;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl Drop for RowIter<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.conn.state = match self.drain() {
+                Ok(()) => ConnectionState::Ready,
+                Err(_) => ConnectionState::Broken,
+            };
+        }
+    }
+}
+
+// --- Async ---
+
+/// Rows of a result set, decoded as they arrive. Returned by [`AsyncMaazDB::query_stream`].
+///
+/// Dropping the stream before the end leaves the rest of the reply unread, so the
+/// connection is then treated as broken.
+pub struct RowStream<'a> {
+    inner: Pin<Box<dyn Stream<Item = Result<Row, MaazDBError>> + Send + 'a>>,
+}
+
+struct AsyncRowReader<'a> {
+    conn: &'a mut AsyncMaazDB,
+    decoder: RowDecoder,
+    remaining: usize,
+    last: bool,
+    done: bool,
+    chunk: Vec<u8>,
+}
+
+impl<'a> RowStream<'a> {
+    pub(crate) fn new(conn: &'a mut AsyncMaazDB, ptype: u8, len: usize) -> Self {
+        let max_value = conn.options.max_packet_size;
+        let reader = AsyncRowReader {
+            conn,
+            decoder: RowDecoder::new(max_value),
+            remaining: len,
+            last: ptype == PACKET_DATA,
+            done: false,
+            chunk: vec![0; CHUNK_SIZE],
+        };
+        let inner = stream::try_unfold(reader, |mut reader| async move {
+            match reader.next_row().await {
+                Ok(row) => Ok(row.map(|row| (row, reader))),
+                Err(e) => {
+                    reader.conn.state = ConnectionState::Broken;
+                    Err(e)
+                }
+            }
+        });
+        RowStream { inner: Box::pin(inner) }
+    }
+}
+
+impl AsyncRowReader<'_> {
+    async fn next_row(&mut self) -> Result<Option<Row>, MaazDBError> {
+        let read_timeout = self.conn.read_timeout;
+        loop {
+            if let Some(row) = self.decoder.pop() {
+                return Ok(Some(row));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            if self.remaining == 0 {
+                if self.last {
+                    self.decoder.finish()?;
+                    self.done = true;
+                    self.conn.state = ConnectionState::Ready;
+                } else {
+                    let (ptype, len) = with_timeout(read_timeout, protocol::read_header_async(&mut self.conn.stream, usize::MAX)).await?;
+                    self.last = protocol::finish_part(ptype, true)?.is_some();
+                    self.remaining = len;
+                }
+                continue;
+            }
+            let n = self.remaining.min(CHUNK_SIZE);
+            with_timeout(read_timeout, self.conn.stream.read_exact(&mut self.chunk[..n])).await?;
+            self.remaining -= n;
+            self.decoder.feed(&self.chunk[..n])?;
+        }
+    }
+}
+
+impl Stream for RowStream<'_> {
+    type Item = Result<Row, MaazDBError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Whether a reply starting with a frame of `ptype` is a result set to stream.
+pub(crate) fn is_data(ptype: u8) -> bool {
+    ptype == PACKET_DATA || ptype == PACKET_DATA_PART
+}
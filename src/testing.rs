@@ -0,0 +1,407 @@
+//! An in-process MaazDB server for integration tests. Requires the `testing` feature.
+//!
+//! [`MockServer`] listens on a local TLS port with a freshly generated self-signed certificate,
+//! answers the handshake and replies to each query from a script:
+//!
+//! ```no_run
+//! use maazdb_rs::testing::MockServer;
+//! use maazdb_rs::Value;
+//!
+//! let server = MockServer::start();
+//! server.expect_query("SELECT id, name FROM users;")
+//!     .respond_rows(&["id", "name"], vec![vec![Value::Int(1), Value::Text("Maaz".into())]]);
+//!
+//! let mut db = server.connect().unwrap();
+//! let rs = db.execute("SELECT id, name FROM users;").unwrap().into_rows().unwrap();
+//! assert_eq!(rs.len(), 1);
+//! server.verify();
+//! ```
+//!
+//! For tests of a whole data layer, [`MockServer::with_engine`] answers every unscripted query
+//! from a small in-memory SQL engine instead: `CREATE`/`DROP DATABASE` and `TABLE`, `USE`,
+//! `INSERT` (multi-row and `INSERT ... SELECT`), `SELECT` with `WHERE`/`ORDER BY`/`LIMIT`/`OFFSET`,
+//! `COUNT`/`SUM`/`AVG`/`MIN`/`MAX`, `UPDATE`, `DELETE`, `SERIAL` keys, `PRIMARY KEY`, `UNIQUE`,
+//! `NOT NULL` and `FOREIGN KEY` checks, `SHOW TABLES`/`DATABASES`, `DESCRIBE` and transactions
+//! with savepoints. Joins and `GROUP BY` are not supported.
+
+mod engine;
+mod sql;
+
+use std::io::{self, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use serde_json::Value as Json;
+
+use crate::protocol::{Packet, PacketCodec};
+use engine::{Engine, Output, Session};
+use crate::{AsyncMaazDB, ConnectOptions, Fingerprint, MaazDB, MaazDBError, TlsMode, Value};
+
+/// What the server sends back for a matched query.
+#[derive(Debug, Clone)]
+enum Reply {
+    Packets(Vec<Packet>),
+    /// Written as-is, for truncated or otherwise malformed frames.
+    Raw(Vec<u8>),
+    /// Close the connection without replying.
+    Close,
+}
+
+#[derive(Debug)]
+struct Rule {
+    sql: String,
+    /// `None` matches any number of times.
+    remaining: Option<usize>,
+    reply: Reply,
+}
+
+#[derive(Debug)]
+enum Auth {
+    /// Accept these credentials, or any if `None`.
+    Accept(Option<(String, String)>),
+    Reject(String),
+}
+
+#[derive(Debug)]
+struct Script {
+    auth: Auth,
+    rules: Vec<Rule>,
+    received: Vec<String>,
+    connections: usize,
+    /// Answers queries that no rule matches.
+    engine: Option<Engine>,
+}
+
+struct Shared {
+    script: Mutex<Script>,
+    /// Handles to every accepted socket, for [`MockServer::drop_connections`].
+    sockets: Mutex<Vec<TcpStream>>,
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn script(&self) -> MutexGuard<'_, Script> {
+        // A panicking test thread must not take every other assertion down with it.
+        self.script.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A scripted MaazDB server on `127.0.0.1`. Stops accepting connections when dropped.
+pub struct MockServer {
+    port: u16,
+    fingerprint: Fingerprint,
+    shared: Arc<Shared>,
+}
+
+impl MockServer {
+    /// Binds a free local port and starts serving. Accepts any credentials until
+    /// [`MockServer::credentials`] or [`MockServer::reject_auth`] is called.
+    ///
+    /// Panics if the port cannot be bound or the certificate cannot be generated.
+    pub fn start() -> Self {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()])
+            .expect("MockServer: failed to generate a certificate");
+        let der = cert.serialize_der().expect("MockServer: failed to serialize the certificate");
+        let config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(vec![rustls::Certificate(der.clone())], rustls::PrivateKey(cert.serialize_private_key_der()))
+            .expect("MockServer: invalid certificate");
+        let config = Arc::new(config);
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("MockServer: failed to bind a local port");
+        let port = listener.local_addr().expect("MockServer: no local address").port();
+        let shared = Arc::new(Shared {
+            script: Mutex::new(Script {
+                auth: Auth::Accept(None),
+                rules: Vec::new(),
+                received: Vec::new(),
+                connections: 0,
+                engine: None,
+            }),
+            sockets: Mutex::new(Vec::new()),
+            stop: AtomicBool::new(false),
+        });
+
+        let accept_shared = shared.clone();
+        thread::spawn(move || {
+            for sock in listener.incoming() {
+                if accept_shared.stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                let Ok(sock) = sock else { continue };
+                if let Ok(handle) = sock.try_clone() {
+                    accept_shared.sockets.lock().unwrap_or_else(|e| e.into_inner()).push(handle);
+                }
+                let Ok(conn) = rustls::ServerConnection::new(config.clone()) else { continue };
+                let shared = accept_shared.clone();
+                thread::spawn(move || {
+                    let _ = serve(rustls::StreamOwned::new(conn, sock), &shared);
+                });
+            }
+        });
+
+        MockServer { port, fingerprint: Fingerprint::of_certificate(&der), shared }
+    }
+
+    /// Starts a server backed by an empty in-memory database (just `system`). Scripted
+    /// expectations still take precedence; every other query runs against the engine.
+    pub fn with_engine() -> Self {
+        let server = MockServer::start();
+        server.shared.script().engine = Some(Engine::new());
+        server
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// SHA-256 fingerprint of the server certificate.
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    /// Accepts only this user and password. Other handshakes get `AUTH_ERR`.
+    pub fn credentials(&self, user: &str, password: &str) {
+        self.shared.script().auth = Auth::Accept(Some((user.to_string(), password.to_string())));
+    }
+
+    /// Rejects every handshake with `message`.
+    pub fn reject_auth(&self, message: &str) {
+        self.shared.script().auth = Auth::Reject(message.to_string());
+    }
+
+    /// Options for connecting to this server, pinned to its certificate. Uses the configured
+    /// credentials, or `admin`/`admin`.
+    pub fn connect_options(&self) -> ConnectOptions {
+        let (user, password) = match &self.shared.script().auth {
+            Auth::Accept(Some((user, password))) => (user.clone(), password.clone()),
+            _ => ("admin".to_string(), "admin".to_string()),
+        };
+        ConnectOptions::new("127.0.0.1", self.port, &user, &password)
+            .tls_mode(TlsMode::Pinned(self.fingerprint))
+    }
+
+    pub fn connect(&self) -> Result<MaazDB, MaazDBError> {
+        MaazDB::connect_with(&self.connect_options())
+    }
+
+    pub async fn connect_async(&self) -> Result<AsyncMaazDB, MaazDBError> {
+        AsyncMaazDB::connect_with(&self.connect_options()).await
+    }
+
+    /// Scripts the reply to `sql`. Whitespace and a trailing `;` are ignored when matching.
+    /// Expectations are tried in the order they were added; queries that match none get a
+    /// server error.
+    pub fn expect_query(&self, sql: &str) -> Expectation<'_> {
+        Expectation { server: self, sql: normalize(sql), times: Some(1) }
+    }
+
+    /// Every query received so far, across all connections, in order.
+    pub fn received(&self) -> Vec<String> {
+        self.shared.script().received.clone()
+    }
+
+    /// Number of connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.shared.script().connections
+    }
+
+    /// Closes every open connection without a word, as a server restart or an idle timeout
+    /// would. Clients only notice when they next read from the socket.
+    pub fn drop_connections(&self) {
+        for sock in self.shared.sockets.lock().unwrap_or_else(|e| e.into_inner()).drain(..) {
+            let _ = sock.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Panics if any expectation with a fixed count was not used up.
+    pub fn verify(&self) {
+        let script = self.shared.script();
+        let pending: Vec<String> = script.rules.iter()
+            .filter(|rule| rule.remaining.is_some_and(|n| n > 0))
+            .map(|rule| format!("{} ({} left)", rule.sql, rule.remaining.unwrap_or(0)))
+            .collect();
+        assert!(pending.is_empty(), "MockServer: expected queries were not received: {:?}", pending);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+    }
+}
+
+/// A scripted query. Created by [`MockServer::expect_query`]; a `respond_*` call registers it.
+#[must_use = "an expectation does nothing until one of its respond methods is called"]
+pub struct Expectation<'a> {
+    server: &'a MockServer,
+    sql: String,
+    times: Option<usize>,
+}
+
+impl Expectation<'_> {
+    /// Matches `n` times instead of once.
+    pub fn times(mut self, n: usize) -> Self {
+        self.times = Some(n);
+        self
+    }
+
+    /// Matches any number of times, and is not checked by [`MockServer::verify`].
+    pub fn always(mut self) -> Self {
+        self.times = None;
+        self
+    }
+
+    /// Replies with a result set.
+    pub fn respond_rows(self, headers: &[&str], rows: Vec<Vec<Value>>) {
+        self.respond_packets(vec![rows_packet(headers, &rows)]);
+    }
+
+    /// Replies with a status message, e.g. `Inserted 1 row`.
+    pub fn respond_message(self, message: &str) {
+        self.respond_packets(vec![Packet::Msg(message.to_string().into())]);
+    }
+
+    /// Replies with a server error, reported to the client as [`MaazDBError::ServerError`].
+    pub fn respond_error(self, message: &str) {
+        self.respond_packets(vec![error_packet(message.to_string())]);
+    }
+
+    /// Replies with these frames, e.g. a result set split into `DataPart`s.
+    pub fn respond_packets(self, packets: Vec<Packet>) {
+        self.register(Reply::Packets(packets));
+    }
+
+    /// Writes `bytes` to the connection unframed, for testing malformed replies.
+    pub fn respond_raw(self, bytes: impl Into<Vec<u8>>) {
+        self.register(Reply::Raw(bytes.into()));
+    }
+
+    /// Closes the connection instead of replying.
+    pub fn close_connection(self) {
+        self.register(Reply::Close);
+    }
+
+    fn register(self, reply: Reply) {
+        self.server.shared.script().rules.push(Rule { sql: self.sql, remaining: self.times, reply });
+    }
+}
+
+fn rows_packet<S: AsRef<str>>(headers: &[S], rows: &[Vec<Value>]) -> Packet {
+    let headers: Vec<&str> = headers.iter().map(AsRef::as_ref).collect();
+    let data: Vec<Json> = rows.iter()
+        .map(|row| Json::Array(row.iter().map(to_json).collect()))
+        .collect();
+    let body = serde_json::json!({ "headers": headers, "data": data });
+    Packet::Data(body.to_string().into())
+}
+
+/// The protocol has no dedicated error type and the client treats any reply other than
+/// `MSG` or `DATA` as an error, so the mock reuses `AUTH_ERR`.
+fn error_packet(message: String) -> Packet {
+    Packet::AuthErr(message.into())
+}
+
+fn to_json(value: &Value) -> Json {
+    match value {
+        Value::Null => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::Int(i) => Json::from(*i),
+        Value::Double(d) => serde_json::Number::from_f64(*d).map_or(Json::Null, Json::Number),
+        Value::Text(s) => Json::String(s.clone()),
+    }
+}
+
+fn normalize(sql: &str) -> String {
+    sql.trim().trim_end_matches(';').split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Handles one connection until the client disconnects or a scripted `Close`.
+fn serve(mut stream: rustls::StreamOwned<rustls::ServerConnection, TcpStream>, shared: &Shared) -> io::Result<()> {
+    let codec = PacketCodec::new().max_packet_size(usize::MAX);
+
+    let hello = codec.read_from(&mut stream)?;
+    let Some((user, password, _, _)) = hello.handshake_fields() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a handshake"));
+    };
+    let verdict = {
+        let mut script = shared.script();
+        script.connections += 1;
+        match &script.auth {
+            Auth::Accept(None) => Ok(()),
+            Auth::Accept(Some((u, p))) if u == user && p == password => Ok(()),
+            Auth::Accept(Some(_)) => Err("Invalid username or password".to_string()),
+            Auth::Reject(message) => Err(message.clone()),
+        }
+    };
+    match verdict {
+        Ok(()) => codec.write_to(&mut stream, &Packet::AuthOk("Welcome".into()))?,
+        Err(message) => return codec.write_to(&mut stream, &Packet::AuthErr(message.into())),
+    }
+
+    let mut session = Session::default();
+    let result = serve_queries(&mut stream, &codec, shared, &mut session);
+    if let Some(engine) = &mut shared.script().engine {
+        engine.end_session(&mut session);
+    }
+    result
+}
+
+fn serve_queries(
+    stream: &mut rustls::StreamOwned<rustls::ServerConnection, TcpStream>,
+    codec: &PacketCodec,
+    shared: &Shared,
+    session: &mut Session,
+) -> io::Result<()> {
+    loop {
+        let packet = codec.read_from(stream)?;
+        let Packet::Query(raw) = packet else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "expected a query"));
+        };
+        let raw = String::from_utf8_lossy(&raw);
+        let sql = normalize(&raw);
+        let reply = {
+            let mut guard = shared.script();
+            let script = &mut *guard;
+            script.received.push(sql.clone());
+            let rule = script.rules.iter_mut()
+                .find(|rule| rule.sql == sql && rule.remaining.is_none_or(|n| n > 0));
+            match (rule, &mut script.engine) {
+                (Some(rule), _) => {
+                    if let Some(n) = &mut rule.remaining {
+                        *n -= 1;
+                    }
+                    rule.reply.clone()
+                }
+                (None, Some(engine)) => Reply::Packets(vec![match engine.execute(session, &raw) {
+                    Ok(Output::Message(message)) => Packet::Msg(message.into()),
+                    Ok(Output::Rows(headers, rows)) => rows_packet(&headers, &rows),
+                    Err(message) => error_packet(message),
+                }]),
+                (None, None) => Reply::Packets(vec![error_packet(
+                    format!("MockServer: no expectation for query: {}", sql),
+                )]),
+            }
+        };
+        match reply {
+            Reply::Packets(packets) => {
+                for packet in &packets {
+                    codec.write_to(stream, packet)?;
+                }
+            }
+            Reply::Raw(bytes) => {
+                stream.write_all(&bytes)?;
+                stream.flush()?;
+            }
+            Reply::Close => {
+                stream.conn.send_close_notify();
+                return stream.flush();
+            }
+        }
+    }
+}
@@ -0,0 +1,809 @@
+//! The in-memory database behind [`MockServer::with_engine`](super::MockServer::with_engine).
+//!
+//! Errors are plain messages, worded like the server's so [`crate::ErrorCategory::classify`]
+//! sorts them the same way.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use super::sql::{self, Aggregate, BinaryOp, ColumnDef, ColumnType, Expr, InsertSource, Select, SelectItem, Statement};
+use crate::{FromValue, Value};
+
+/// What a statement produced.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Output {
+    Message(String),
+    Rows(Vec<String>, Vec<Vec<Value>>),
+}
+
+#[derive(Debug, Clone)]
+struct Table {
+    name: String,
+    columns: Vec<ColumnDef>,
+    rows: Vec<Vec<Value>>,
+    /// Last value handed out by a `SERIAL` column.
+    last_serial: i64,
+}
+
+impl Table {
+    fn column_index(&self, name: &str) -> Result<usize, String> {
+        column_index(&self.columns, name)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Database {
+    name: String,
+    /// Keyed by lower-cased name.
+    tables: BTreeMap<String, Table>,
+}
+
+impl Database {
+    fn new(name: &str) -> Self {
+        Database { name: name.to_string(), tables: BTreeMap::new() }
+    }
+
+    fn table(&self, name: &str) -> Result<&Table, String> {
+        self.tables.get(&name.to_ascii_lowercase()).ok_or_else(|| format!("Table '{}' not found", name))
+    }
+}
+
+/// Every database, keyed by lower-cased name. Cloned whole for transaction snapshots.
+#[derive(Debug, Clone)]
+struct Catalog {
+    databases: BTreeMap<String, Database>,
+}
+
+/// Per-connection state: the selected database and any open transaction.
+#[derive(Debug, Default)]
+pub(crate) struct Session {
+    database: Option<String>,
+    /// The catalog as it was at `BEGIN`, then at each savepoint.
+    snapshots: Vec<(Option<String>, Catalog)>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Engine {
+    catalog: Catalog,
+}
+
+impl Engine {
+    /// An engine with just the `system` database.
+    pub(crate) fn new() -> Self {
+        let mut databases = BTreeMap::new();
+        databases.insert("system".to_string(), Database::new("system"));
+        Engine { catalog: Catalog { databases } }
+    }
+
+    pub(crate) fn execute(&mut self, session: &mut Session, sql: &str) -> Result<Output, String> {
+        match sql::parse(sql)? {
+            Statement::CreateDatabase { name, if_not_exists } => {
+                let key = name.to_ascii_lowercase();
+                if self.catalog.databases.contains_key(&key) {
+                    if if_not_exists {
+                        return Ok(message(format!("Database '{}' already exists, skipped", name)));
+                    }
+                    return Err(format!("Database '{}' already exists", name));
+                }
+                self.catalog.databases.insert(key, Database::new(&name));
+                Ok(message(format!("Database '{}' created", name)))
+            }
+            Statement::DropDatabase { name, if_exists } => {
+                let key = name.to_ascii_lowercase();
+                if self.catalog.databases.remove(&key).is_none() {
+                    if if_exists {
+                        return Ok(message(format!("Database '{}' does not exist, skipped", name)));
+                    }
+                    return Err(format!("Database '{}' not found", name));
+                }
+                if session.database.as_ref() == Some(&key) {
+                    session.database = None;
+                }
+                Ok(message(format!("Database '{}' dropped", name)))
+            }
+            Statement::Use(name) => {
+                let key = name.to_ascii_lowercase();
+                if !self.catalog.databases.contains_key(&key) {
+                    return Err(format!("Database '{}' not found", name));
+                }
+                session.database = Some(key);
+                Ok(message(format!("Switched to database '{}'", name)))
+            }
+            Statement::CreateTable { name, if_not_exists, columns } => self.create_table(session, name, if_not_exists, columns),
+            Statement::DropTable { name, if_exists } => {
+                let db = self.database_mut(session)?;
+                let key = name.to_ascii_lowercase();
+                if !db.tables.contains_key(&key) {
+                    if if_exists {
+                        return Ok(message(format!("Table '{}' does not exist, skipped", name)));
+                    }
+                    return Err(format!("Table '{}' not found", name));
+                }
+                let referenced_by = db.tables.values()
+                    .filter(|t| !t.name.eq_ignore_ascii_case(&name))
+                    .find(|t| t.columns.iter().any(|c| c.references.as_ref().is_some_and(|(r, _)| r.eq_ignore_ascii_case(&name))));
+                if let Some(child) = referenced_by {
+                    return Err(format!(
+                        "Cannot drop table '{}': it is referenced by a foreign key constraint on '{}'", name, child.name
+                    ));
+                }
+                db.tables.remove(&key);
+                Ok(message(format!("Table '{}' dropped", name)))
+            }
+            Statement::Insert { table, columns, source } => self.insert(session, &table, columns.as_deref(), &source),
+            Statement::Select(select) => {
+                let (headers, rows) = self.select(session, &select)?;
+                Ok(Output::Rows(headers, rows))
+            }
+            Statement::Update { table, assignments, filter } => self.update(session, &table, &assignments, filter.as_ref()),
+            Statement::Delete { table, filter } => self.delete(session, &table, filter.as_ref()),
+            Statement::ShowTables => {
+                let db = self.database(session)?;
+                let rows = db.tables.values().map(|t| vec![Value::Text(t.name.clone())]).collect();
+                Ok(Output::Rows(vec!["table".into()], rows))
+            }
+            Statement::ShowDatabases => {
+                let rows = self.catalog.databases.values().map(|d| vec![Value::Text(d.name.clone())]).collect();
+                Ok(Output::Rows(vec!["database".into()], rows))
+            }
+            Statement::Describe(name) => {
+                let table = self.database(session)?.table(&name)?;
+                let rows = table.columns.iter().map(|c| vec![
+                    Value::Text(c.name.clone()),
+                    Value::Text(c.ty.to_string()),
+                    Value::Bool(!c.not_null && !c.primary_key),
+                    Value::Bool(c.primary_key),
+                    c.default.as_ref().map_or(Value::Null, |d| Value::Text(d.to_string())),
+                    c.references.as_ref().map_or(Value::Null, |(t, col)| Value::Text(format!("{}({})", t, col))),
+                ]).collect();
+                let headers = ["column", "type", "nullable", "primary_key", "default", "references"];
+                Ok(Output::Rows(headers.iter().map(|h| h.to_string()).collect(), rows))
+            }
+            Statement::Begin => {
+                if !session.snapshots.is_empty() {
+                    return Err("A transaction is already in progress".into());
+                }
+                session.snapshots.push((None, self.catalog.clone()));
+                Ok(message("BEGIN".into()))
+            }
+            Statement::Commit => {
+                if session.snapshots.is_empty() {
+                    return Err("No transaction in progress".into());
+                }
+                session.snapshots.clear();
+                Ok(message("COMMIT".into()))
+            }
+            Statement::Rollback => {
+                let Some((_, catalog)) = session.snapshots.drain(..).next() else {
+                    return Err("No transaction in progress".into());
+                };
+                self.catalog = catalog;
+                Ok(message("ROLLBACK".into()))
+            }
+            Statement::Savepoint(name) => {
+                if session.snapshots.is_empty() {
+                    return Err("SAVEPOINT can only be used in a transaction".into());
+                }
+                session.snapshots.push((Some(name), self.catalog.clone()));
+                Ok(message("SAVEPOINT".into()))
+            }
+            Statement::RollbackTo(name) => {
+                let at = find_savepoint(session, &name)?;
+                self.catalog = session.snapshots[at].1.clone();
+                session.snapshots.truncate(at + 1);
+                Ok(message("ROLLBACK".into()))
+            }
+            Statement::Release(name) => {
+                let at = find_savepoint(session, &name)?;
+                session.snapshots.truncate(at);
+                Ok(message("RELEASE".into()))
+            }
+            Statement::CreateUser(name) => Ok(message(format!("User '{}' created", name))),
+            Statement::Backup(name) => Ok(message(format!("Backup '{}' created", name))),
+        }
+    }
+
+    /// Rolls back a transaction left open by a closed connection.
+    pub(crate) fn end_session(&mut self, session: &mut Session) {
+        if let Some((_, catalog)) = session.snapshots.drain(..).next() {
+            self.catalog = catalog;
+        }
+    }
+
+    fn database(&self, session: &Session) -> Result<&Database, String> {
+        let key = session.database.as_ref().ok_or("No database selected")?;
+        self.catalog.databases.get(key).ok_or_else(|| format!("Database '{}' not found", key))
+    }
+
+    fn database_mut(&mut self, session: &Session) -> Result<&mut Database, String> {
+        let key = session.database.as_ref().ok_or("No database selected")?;
+        self.catalog.databases.get_mut(key).ok_or_else(|| format!("Database '{}' not found", key))
+    }
+
+    fn table_mut(&mut self, session: &Session, name: &str) -> Result<&mut Table, String> {
+        self.database_mut(session)?
+            .tables.get_mut(&name.to_ascii_lowercase())
+            .ok_or_else(|| format!("Table '{}' not found", name))
+    }
+
+    fn create_table(&mut self, session: &Session, name: String, if_not_exists: bool, columns: Vec<ColumnDef>) -> Result<Output, String> {
+        let db = self.database_mut(session)?;
+        let key = name.to_ascii_lowercase();
+        if db.tables.contains_key(&key) {
+            if if_not_exists {
+                return Ok(message(format!("Table '{}' already exists, skipped", name)));
+            }
+            return Err(format!("Table '{}' already exists", name));
+        }
+        for (i, column) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|c| c.name.eq_ignore_ascii_case(&column.name)) {
+                return Err(format!("Column '{}' already exists", column.name));
+            }
+            let Some((ref_table, ref_column)) = &column.references else { continue };
+            if ref_table.eq_ignore_ascii_case(&name) {
+                column_index(&columns, ref_column)?;
+            } else {
+                db.table(ref_table)?.column_index(ref_column)?;
+            }
+        }
+        db.tables.insert(key, Table { name: name.clone(), columns, rows: Vec::new(), last_serial: 0 });
+        Ok(message(format!("Table '{}' created", name)))
+    }
+
+    fn insert(&mut self, session: &Session, name: &str, columns: Option<&[String]>, source: &InsertSource) -> Result<Output, String> {
+        let values: Vec<Vec<Value>> = match source {
+            InsertSource::Values(rows) => rows.iter()
+                .map(|row| row.iter().map(|e| eval(e, Ctx::EMPTY)).collect())
+                .collect::<Result<_, _>>()?,
+            InsertSource::Select(select) => self.select(session, select)?.1,
+        };
+
+        let db = self.database(session)?;
+        let table = db.table(name)?;
+        let targets: Vec<usize> = match columns {
+            Some(names) => names.iter().map(|n| table.column_index(n)).collect::<Result<_, _>>()?,
+            None => (0..table.columns.len()).collect(),
+        };
+        let mut last_serial = table.last_serial;
+        let mut rows = Vec::with_capacity(values.len());
+        for row_values in values {
+            if row_values.len() != targets.len() {
+                return Err(format!("INSERT has {} values for {} columns", row_values.len(), targets.len()));
+            }
+            let mut given = vec![None; table.columns.len()];
+            for (&i, value) in targets.iter().zip(row_values) {
+                given[i] = Some(value);
+            }
+            let mut row = Vec::with_capacity(table.columns.len());
+            for (column, value) in table.columns.iter().zip(given) {
+                let value = match value {
+                    Some(Value::Null) | None if column.ty == ColumnType::Serial => {
+                        last_serial += 1;
+                        Value::Int(last_serial)
+                    }
+                    Some(value) => value,
+                    None => column.default.as_ref().map_or(Ok(Value::Null), |d| eval(d, Ctx::EMPTY))?,
+                };
+                let value = coerce(column, value)?;
+                if let (ColumnType::Serial, Value::Int(i)) = (column.ty, &value) {
+                    last_serial = last_serial.max(*i);
+                }
+                row.push(value);
+            }
+            rows.push(row);
+        }
+        check_constraints(db, table, &rows, &[])?;
+
+        let count = rows.len();
+        let table = self.table_mut(session, name)?;
+        table.rows.extend(rows);
+        table.last_serial = last_serial;
+        Ok(message(format!("Inserted {} {}", count, if count == 1 { "row" } else { "rows" })))
+    }
+
+    fn update(&mut self, session: &Session, name: &str, assignments: &[(String, Expr)], filter: Option<&Expr>) -> Result<Output, String> {
+        let db = self.database(session)?;
+        let table = db.table(name)?;
+        let targets: Vec<(usize, &Expr)> = assignments.iter()
+            .map(|(column, expr)| Ok((table.column_index(column)?, expr)))
+            .collect::<Result<_, String>>()?;
+
+        let mut indices = Vec::new();
+        let mut updated = Vec::new();
+        for (i, row) in table.rows.iter().enumerate() {
+            let ctx = Ctx::row(&table.columns, row);
+            if !matches(filter, ctx)? {
+                continue;
+            }
+            let mut new_row = row.clone();
+            for &(column, expr) in &targets {
+                new_row[column] = coerce(&table.columns[column], eval(expr, ctx)?)?;
+            }
+            indices.push(i);
+            updated.push(new_row);
+        }
+        check_constraints(db, table, &updated, &indices)?;
+        let mut remaining = table.rows.clone();
+        for (&i, row) in indices.iter().zip(&updated) {
+            remaining[i] = row.clone();
+        }
+        check_children(db, table, &remaining)?;
+
+        let count = indices.len();
+        self.table_mut(session, name)?.rows = remaining;
+        Ok(message(format!("Updated {} {}", count, if count == 1 { "row" } else { "rows" })))
+    }
+
+    fn delete(&mut self, session: &Session, name: &str, filter: Option<&Expr>) -> Result<Output, String> {
+        let db = self.database(session)?;
+        let table = db.table(name)?;
+        let mut remaining = Vec::with_capacity(table.rows.len());
+        for row in &table.rows {
+            if !matches(filter, Ctx::row(&table.columns, row))? {
+                remaining.push(row.clone());
+            }
+        }
+        check_children(db, table, &remaining)?;
+
+        let count = table.rows.len() - remaining.len();
+        self.table_mut(session, name)?.rows = remaining;
+        Ok(message(format!("Deleted {} {}", count, if count == 1 { "row" } else { "rows" })))
+    }
+
+    fn select(&self, session: &Session, select: &Select) -> Result<(Vec<String>, Vec<Vec<Value>>), String> {
+        let (columns, source): (&[ColumnDef], Vec<&[Value]>) = match &select.from {
+            Some(name) => {
+                let table = self.database(session)?.table(name)?;
+                (&table.columns, table.rows.iter().map(Vec::as_slice).collect())
+            }
+            None => (&[], vec![&[]]),
+        };
+        let mut rows = Vec::new();
+        for row in source {
+            if matches(select.filter.as_ref(), Ctx::row(columns, row))? {
+                rows.push(row);
+            }
+        }
+
+        let mut headers = Vec::new();
+        let mut outputs: Vec<Expr> = Vec::new();
+        for item in &select.items {
+            match item {
+                SelectItem::Wildcard => {
+                    if select.from.is_none() {
+                        return Err("Syntax error: SELECT * requires a FROM clause".into());
+                    }
+                    for column in columns {
+                        headers.push(column.name.clone());
+                        outputs.push(Expr::Column(column.name.clone()));
+                    }
+                }
+                SelectItem::Expr { expr, alias } => {
+                    headers.push(alias.clone().unwrap_or_else(|| expr.to_string()));
+                    outputs.push(expr.clone());
+                }
+            }
+        }
+
+        let offset = select.offset.unwrap_or(0);
+        let limit = select.limit.unwrap_or(usize::MAX);
+        if outputs.iter().any(Expr::has_aggregate) {
+            // No GROUP BY: the whole selection is one group, and the result one row.
+            let ctx = Ctx { columns, row: None, group: Some(&rows) };
+            let row = outputs.iter().map(|e| eval(e, ctx)).collect::<Result<Vec<_>, _>>()?;
+            return Ok((headers, std::iter::once(row).skip(offset).take(limit).collect()));
+        }
+
+        if !select.order_by.is_empty() {
+            // `ORDER BY` may name an output alias as well as a column.
+            let keys: Vec<(&Expr, bool)> = select.order_by.iter()
+                .map(|(expr, desc)| match expr {
+                    Expr::Column(name) if column_index(columns, name).is_err() => headers.iter()
+                        .position(|h| h.eq_ignore_ascii_case(name))
+                        .map_or((expr, *desc), |i| (&outputs[i], *desc)),
+                    _ => (expr, *desc),
+                })
+                .collect();
+            let mut keyed = Vec::with_capacity(rows.len());
+            for row in rows {
+                let ctx = Ctx::row(columns, row);
+                let key = keys.iter().map(|(e, _)| eval(e, ctx)).collect::<Result<Vec<_>, _>>()?;
+                keyed.push((key, row));
+            }
+            keyed.sort_by(|(a, _), (b, _)| {
+                keys.iter().zip(a.iter().zip(b))
+                    .map(|((_, desc), (x, y))| if *desc { sort_cmp(y, x) } else { sort_cmp(x, y) })
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            rows = keyed.into_iter().map(|(_, row)| row).collect();
+        }
+
+        let mut result = Vec::new();
+        for row in rows.into_iter().skip(offset).take(limit) {
+            let ctx = Ctx::row(columns, row);
+            result.push(outputs.iter().map(|e| eval(e, ctx)).collect::<Result<Vec<_>, _>>()?);
+        }
+        Ok((headers, result))
+    }
+}
+
+fn message(text: String) -> Output {
+    Output::Message(text)
+}
+
+fn find_savepoint(session: &Session, name: &str) -> Result<usize, String> {
+    session.snapshots.iter()
+        .rposition(|(sp, _)| sp.as_ref().is_some_and(|sp| sp.eq_ignore_ascii_case(name)))
+        .ok_or_else(|| format!("Savepoint '{}' does not exist", name))
+}
+
+fn column_index(columns: &[ColumnDef], name: &str) -> Result<usize, String> {
+    columns.iter()
+        .position(|c| c.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("Column '{}' not found", name))
+}
+
+// --- Constraints ---
+
+/// Checks `NOT NULL`, `PRIMARY KEY`/`UNIQUE` and `FOREIGN KEY` rules for `rows`, which are
+/// appended to `table` or, for an `UPDATE`, replace the rows at `replacing`.
+fn check_constraints(db: &Database, table: &Table, rows: &[Vec<Value>], replacing: &[usize]) -> Result<(), String> {
+    for (i, column) in table.columns.iter().enumerate() {
+        for row in rows {
+            let value = &row[i];
+            if value.is_null() {
+                if column.not_null || column.primary_key {
+                    return Err(format!("NOT NULL constraint violated: column '{}' cannot be NULL", column.name));
+                }
+                continue;
+            }
+            let Some((ref_table, ref_column)) = &column.references else { continue };
+            let self_reference = ref_table.eq_ignore_ascii_case(&table.name);
+            let target = if self_reference { table } else { db.table(ref_table)? };
+            let j = target.column_index(ref_column)?;
+            let found = target.rows.iter().any(|r| same(&r[j], value))
+                || (self_reference && rows.iter().any(|r| same(&r[j], value)));
+            if !found {
+                return Err(format!(
+                    "Foreign key constraint violation: {} = {} has no matching row in {}({})",
+                    column.name, value, ref_table, ref_column
+                ));
+            }
+        }
+
+        if column.primary_key || column.unique {
+            let mut seen: Vec<&Value> = table.rows.iter().enumerate()
+                .filter(|(k, _)| !replacing.contains(k))
+                .map(|(_, r)| &r[i])
+                .filter(|v| !v.is_null())
+                .collect();
+            for row in rows {
+                let value = &row[i];
+                if value.is_null() {
+                    continue;
+                }
+                if seen.iter().any(|s| same(s, value)) {
+                    let kind = if column.primary_key { "primary key" } else { "unique" };
+                    return Err(format!("Duplicate value {} for {} column '{}'", value, kind, column.name));
+                }
+                seen.push(value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every row referencing `table` through a foreign key still has a match once
+/// `table` holds only `remaining`.
+fn check_children(db: &Database, table: &Table, remaining: &[Vec<Value>]) -> Result<(), String> {
+    for child in db.tables.values() {
+        for (i, column) in child.columns.iter().enumerate() {
+            let Some((ref_table, ref_column)) = &column.references else { continue };
+            if !ref_table.eq_ignore_ascii_case(&table.name) {
+                continue;
+            }
+            let j = table.column_index(ref_column)?;
+            let child_rows = if child.name.eq_ignore_ascii_case(&table.name) { remaining } else { &child.rows };
+            for row in child_rows {
+                let value = &row[i];
+                if !value.is_null() && !remaining.iter().any(|r| same(&r[j], value)) {
+                    return Err(format!(
+                        "Foreign key constraint violation: {}({}) = {} is still referenced by {}.{}",
+                        table.name, ref_column, value, child.name, column.name
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts `value` to the column's type, or fails with a type mismatch.
+fn coerce(column: &ColumnDef, value: Value) -> Result<Value, String> {
+    let mismatch = |value: &Value| {
+        format!("Type mismatch: column '{}' is {}, got {}", column.name, column.ty, describe(value))
+    };
+    let coerced = match (column.ty, &value) {
+        (_, Value::Null) => Some(Value::Null),
+        (ColumnType::Serial | ColumnType::Int, Value::Int(i)) => Some(Value::Int(*i)),
+        (ColumnType::Serial | ColumnType::Int, Value::Double(d)) if d.fract() == 0.0 && d.abs() < 9.0e18 => {
+            Some(Value::Int(*d as i64))
+        }
+        (ColumnType::Double, Value::Int(i)) => Some(Value::Double(*i as f64)),
+        (ColumnType::Double, Value::Double(d)) => Some(Value::Double(*d)),
+        (ColumnType::Text, Value::Text(_)) => Some(value.clone()),
+        (ColumnType::Text, Value::Int(_) | Value::Double(_) | Value::Bool(_)) => Some(Value::Text(value.to_string())),
+        (ColumnType::Bool, Value::Bool(b)) => Some(Value::Bool(*b)),
+        (ColumnType::Bool, Value::Int(i @ (0 | 1))) => Some(Value::Bool(*i == 1)),
+        (ColumnType::Timestamp, Value::Text(_)) => NaiveDateTime::from_value(&value).ok()
+            .map(|dt| Value::Text(dt.format("%Y-%m-%d %H:%M:%S%.f").to_string())),
+        (ColumnType::Uuid, Value::Text(s)) => Uuid::parse_str(s.trim()).ok()
+            .map(|u| Value::Text(u.hyphenated().to_string())),
+        (ColumnType::Blob, Value::Text(s)) if s.len() % 2 == 0 && s.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(Value::Text(s.to_ascii_uppercase()))
+        }
+        _ => None,
+    };
+    coerced.ok_or_else(|| mismatch(&value))
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".into(),
+        Value::Bool(b) => format!("boolean {}", b),
+        Value::Int(i) => format!("integer {}", i),
+        Value::Double(d) => format!("double {}", d),
+        Value::Text(s) => format!("text '{}'", s),
+    }
+}
+
+// --- Expressions ---
+
+/// What an expression can see while it is evaluated.
+#[derive(Clone, Copy)]
+struct Ctx<'a> {
+    columns: &'a [ColumnDef],
+    row: Option<&'a [Value]>,
+    /// Every selected row, while evaluating the items of an aggregate query.
+    group: Option<&'a [&'a [Value]]>,
+}
+
+impl<'a> Ctx<'a> {
+    const EMPTY: Ctx<'static> = Ctx { columns: &[], row: None, group: None };
+
+    fn row(columns: &'a [ColumnDef], row: &'a [Value]) -> Self {
+        Ctx { columns, row: Some(row), group: None }
+    }
+}
+
+fn matches(filter: Option<&Expr>, ctx: Ctx<'_>) -> Result<bool, String> {
+    match filter {
+        Some(filter) => Ok(truth(&eval(filter, ctx)?)? == Some(true)),
+        None => Ok(true),
+    }
+}
+
+fn eval(expr: &Expr, ctx: Ctx<'_>) -> Result<Value, String> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Column(name) => {
+            let i = column_index(ctx.columns, name)?;
+            match ctx.row {
+                Some(row) => Ok(row[i].clone()),
+                None if ctx.group.is_some() => Err(format!(
+                    "Column '{}' must be used in an aggregate function, since GROUP BY is not supported", name
+                )),
+                None => Err(format!("Column '{}' not found", name)),
+            }
+        }
+        Expr::Not(e) => Ok(truth(&eval(e, ctx)?)?.map_or(Value::Null, |b| Value::Bool(!b))),
+        Expr::Neg(e) => match eval(e, ctx)? {
+            Value::Null => Ok(Value::Null),
+            Value::Int(i) => i.checked_neg().map(Value::Int).ok_or_else(|| "Integer overflow".to_string()),
+            Value::Double(d) => Ok(Value::Double(-d)),
+            other => Err(format!("Type mismatch: cannot negate {}", describe(&other))),
+        },
+        Expr::Binary(l, op @ (BinaryOp::And | BinaryOp::Or), r) => {
+            // Three-valued logic: NULL AND FALSE is FALSE, NULL OR TRUE is TRUE.
+            let short = *op == BinaryOp::Or;
+            let left = truth(&eval(l, ctx)?)?;
+            if left == Some(short) {
+                return Ok(Value::Bool(short));
+            }
+            let right = truth(&eval(r, ctx)?)?;
+            Ok(match (left, right) {
+                (_, Some(b)) if b == short => Value::Bool(short),
+                (Some(_), Some(_)) => Value::Bool(!short),
+                _ => Value::Null,
+            })
+        }
+        Expr::Binary(l, op, r) => binary(*op, eval(l, ctx)?, eval(r, ctx)?),
+        Expr::IsNull { expr, negated } => Ok(Value::Bool(eval(expr, ctx)?.is_null() != *negated)),
+        Expr::Like { expr, pattern, negated } => match (eval(expr, ctx)?, eval(pattern, ctx)?) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::Text(s), Value::Text(p)) => {
+                let s: Vec<char> = s.chars().collect();
+                let p: Vec<char> = p.chars().collect();
+                Ok(Value::Bool(like(&s, &p) != *negated))
+            }
+            (s, _) => Err(format!("Type mismatch: LIKE needs text, got {}", describe(&s))),
+        },
+        Expr::InList { expr, list, negated } => {
+            let value = eval(expr, ctx)?;
+            if value.is_null() {
+                return Ok(Value::Null);
+            }
+            let mut saw_null = false;
+            for item in list {
+                let item = eval(item, ctx)?;
+                if item.is_null() {
+                    saw_null = true;
+                } else if compare(&value, &item)? == Ordering::Equal {
+                    return Ok(Value::Bool(!negated));
+                }
+            }
+            Ok(if saw_null { Value::Null } else { Value::Bool(*negated) })
+        }
+        Expr::Aggregate(func, arg) => {
+            let Some(rows) = ctx.group else {
+                return Err(format!("Aggregate {} is not allowed here", expr));
+            };
+            let Some(arg) = arg else {
+                return Ok(Value::Int(rows.len() as i64));
+            };
+            let mut values = Vec::with_capacity(rows.len());
+            for row in rows {
+                let value = eval(arg, Ctx::row(ctx.columns, row))?;
+                if !value.is_null() {
+                    values.push(value);
+                }
+            }
+            aggregate(*func, values)
+        }
+    }
+}
+
+fn aggregate(func: Aggregate, values: Vec<Value>) -> Result<Value, String> {
+    if func == Aggregate::Count {
+        return Ok(Value::Int(values.len() as i64));
+    }
+    if values.is_empty() {
+        return Ok(Value::Null);
+    }
+    match func {
+        Aggregate::Sum | Aggregate::Avg => {
+            let count = values.len();
+            let mut sum = Value::Int(0);
+            for value in values {
+                if !matches!(value, Value::Int(_) | Value::Double(_)) {
+                    return Err(format!("Type mismatch: cannot add up {}", describe(&value)));
+                }
+                sum = binary(BinaryOp::Add, sum, value)?;
+            }
+            match (func, sum) {
+                (Aggregate::Avg, Value::Int(i)) => Ok(Value::Double(i as f64 / count as f64)),
+                (Aggregate::Avg, Value::Double(d)) => Ok(Value::Double(d / count as f64)),
+                (_, sum) => Ok(sum),
+            }
+        }
+        _ => {
+            let mut best = values[0].clone();
+            for value in &values[1..] {
+                let order = compare(value, &best)?;
+                if (func == Aggregate::Min && order.is_lt()) || (func == Aggregate::Max && order.is_gt()) {
+                    best = value.clone();
+                }
+            }
+            Ok(best)
+        }
+    }
+}
+
+fn truth(value: &Value) -> Result<Option<bool>, String> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Bool(b) => Ok(Some(*b)),
+        Value::Int(i) => Ok(Some(*i != 0)),
+        other => Err(format!("Type mismatch: expected a boolean, got {}", describe(other))),
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, String> {
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+    let order = |wanted: fn(Ordering) -> bool| compare(&left, &right).map(|o| Value::Bool(wanted(o)));
+    match op {
+        BinaryOp::Eq => order(Ordering::is_eq),
+        BinaryOp::NotEq => order(Ordering::is_ne),
+        BinaryOp::Lt => order(Ordering::is_lt),
+        BinaryOp::LtEq => order(Ordering::is_le),
+        BinaryOp::Gt => order(Ordering::is_gt),
+        BinaryOp::GtEq => order(Ordering::is_ge),
+        BinaryOp::Concat => Ok(Value::Text(format!("{}{}", left, right))),
+        BinaryOp::And | BinaryOp::Or => unreachable!("handled by eval"),
+        _ => arithmetic(op, &left, &right),
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, String> {
+    if matches!(op, BinaryOp::Div | BinaryOp::Mod) && as_f64(right) == 0.0 {
+        return Err("Division by zero".into());
+    }
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => {
+            let result = match op {
+                BinaryOp::Add => a.checked_add(*b),
+                BinaryOp::Sub => a.checked_sub(*b),
+                BinaryOp::Mul => a.checked_mul(*b),
+                BinaryOp::Div => a.checked_div(*b),
+                _ => a.checked_rem(*b),
+            };
+            result.map(Value::Int).ok_or_else(|| "Integer overflow".to_string())
+        }
+        (Value::Int(_) | Value::Double(_), Value::Int(_) | Value::Double(_)) => {
+            let (a, b) = (as_f64(left), as_f64(right));
+            Ok(Value::Double(match op {
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                _ => a % b,
+            }))
+        }
+        _ => Err(format!(
+            "Type mismatch: cannot apply '{}' to {} and {}", op.symbol(), describe(left), describe(right)
+        )),
+    }
+}
+
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Int(i) => *i as f64,
+        Value::Double(d) => *d,
+        _ => f64::NAN,
+    }
+}
+
+/// Orders two non-NULL values of comparable types.
+fn compare(left: &Value, right: &Value) -> Result<Ordering, String> {
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => Ok(a.cmp(b)),
+        (Value::Int(_) | Value::Double(_), Value::Int(_) | Value::Double(_)) => {
+            Ok(as_f64(left).partial_cmp(&as_f64(right)).unwrap_or(Ordering::Equal))
+        }
+        (Value::Text(a), Value::Text(b)) => Ok(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Ok(a.cmp(b)),
+        _ => Err(format!("Type mismatch: cannot compare {} with {}", describe(left), describe(right))),
+    }
+}
+
+fn same(left: &Value, right: &Value) -> bool {
+    compare(left, right).is_ok_and(Ordering::is_eq)
+}
+
+/// Ordering for `ORDER BY`: NULLs first, then by value. Mismatched types fall back to a fixed
+/// order instead of failing.
+fn sort_cmp(left: &Value, right: &Value) -> Ordering {
+    let rank = |v: &Value| match v {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Int(_) | Value::Double(_) => 2,
+        Value::Text(_) => 3,
+    };
+    compare(left, right).unwrap_or_else(|_| rank(left).cmp(&rank(right)))
+}
+
+/// SQL `LIKE`: `%` matches any run of characters, `_` exactly one.
+fn like(s: &[char], pattern: &[char]) -> bool {
+    match pattern.split_first() {
+        None => s.is_empty(),
+        Some(('%', rest)) => (0..=s.len()).any(|i| like(&s[i..], rest)),
+        Some(('_', rest)) => !s.is_empty() && like(&s[1..], rest),
+        Some((c, rest)) => s.first() == Some(c) && like(&s[1..], rest),
+    }
+}
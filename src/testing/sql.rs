@@ -0,0 +1,912 @@
+//! Lexer and parser for the SQL understood by the mock engine.
+
+use std::fmt;
+
+use crate::Value;
+
+// --- Statements ---
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Statement {
+    CreateDatabase { name: String, if_not_exists: bool },
+    DropDatabase { name: String, if_exists: bool },
+    Use(String),
+    CreateTable { name: String, if_not_exists: bool, columns: Vec<ColumnDef> },
+    DropTable { name: String, if_exists: bool },
+    Insert { table: String, columns: Option<Vec<String>>, source: InsertSource },
+    Select(Select),
+    Update { table: String, assignments: Vec<(String, Expr)>, filter: Option<Expr> },
+    Delete { table: String, filter: Option<Expr> },
+    ShowTables,
+    ShowDatabases,
+    Describe(String),
+    Begin,
+    Commit,
+    Rollback,
+    Savepoint(String),
+    RollbackTo(String),
+    Release(String),
+    CreateUser(String),
+    Backup(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum InsertSource {
+    Values(Vec<Vec<Expr>>),
+    Select(Box<Select>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Select {
+    pub(crate) items: Vec<SelectItem>,
+    pub(crate) from: Option<String>,
+    pub(crate) filter: Option<Expr>,
+    /// Expressions with `true` for `DESC`.
+    pub(crate) order_by: Vec<(Expr, bool)>,
+    pub(crate) limit: Option<usize>,
+    pub(crate) offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SelectItem {
+    Wildcard,
+    Expr { expr: Expr, alias: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColumnType {
+    Serial,
+    Int,
+    Double,
+    Text,
+    Bool,
+    Timestamp,
+    Uuid,
+    Blob,
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnType::Serial => "SERIAL",
+            ColumnType::Int => "INT",
+            ColumnType::Double => "DOUBLE",
+            ColumnType::Text => "TEXT",
+            ColumnType::Bool => "BOOL",
+            ColumnType::Timestamp => "TIMESTAMP",
+            ColumnType::Uuid => "UUID",
+            ColumnType::Blob => "BLOB",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ColumnDef {
+    pub(crate) name: String,
+    pub(crate) ty: ColumnType,
+    pub(crate) primary_key: bool,
+    pub(crate) not_null: bool,
+    pub(crate) unique: bool,
+    pub(crate) default: Option<Expr>,
+    /// Referenced `(table, column)`.
+    pub(crate) references: Option<(String, String)>,
+}
+
+// --- Expressions ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Concat,
+}
+
+impl BinaryOp {
+    pub(crate) fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "OR",
+            BinaryOp::And => "AND",
+            BinaryOp::Eq => "=",
+            BinaryOp::NotEq => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::LtEq => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::GtEq => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Concat => "||",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Literal(Value),
+    /// A column, without any `table.` qualifier.
+    Column(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    IsNull { expr: Box<Expr>, negated: bool },
+    Like { expr: Box<Expr>, pattern: Box<Expr>, negated: bool },
+    InList { expr: Box<Expr>, list: Vec<Expr>, negated: bool },
+    /// `None` is `COUNT(*)`.
+    Aggregate(Aggregate, Option<Box<Expr>>),
+}
+
+impl Expr {
+    pub(crate) fn has_aggregate(&self) -> bool {
+        match self {
+            Expr::Aggregate(..) => true,
+            Expr::Literal(_) | Expr::Column(_) => false,
+            Expr::Not(e) | Expr::Neg(e) | Expr::IsNull { expr: e, .. } => e.has_aggregate(),
+            Expr::Binary(l, _, r) => l.has_aggregate() || r.has_aggregate(),
+            Expr::Like { expr, pattern, .. } => expr.has_aggregate() || pattern.has_aggregate(),
+            Expr::InList { expr, list, .. } => expr.has_aggregate() || list.iter().any(Expr::has_aggregate),
+        }
+    }
+}
+
+/// Renders the expression the way result headers show it, e.g. `COUNT(*)` or `1 + 1`.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(Value::Text(s)) => write!(f, "'{}'", s.replace('\'', "''")),
+            Expr::Literal(Value::Bool(b)) => f.write_str(if *b { "TRUE" } else { "FALSE" }),
+            Expr::Literal(v) => write!(f, "{}", v),
+            Expr::Column(name) => f.write_str(name),
+            Expr::Not(e) => write!(f, "NOT {}", e),
+            Expr::Neg(e) => write!(f, "-{}", e),
+            Expr::Binary(l, op, r) => write!(f, "{} {} {}", l, op.symbol(), r),
+            Expr::IsNull { expr, negated } => write!(f, "{} IS {}NULL", expr, if *negated { "NOT " } else { "" }),
+            Expr::Like { expr, pattern, negated } => write!(f, "{} {}LIKE {}", expr, if *negated { "NOT " } else { "" }, pattern),
+            Expr::InList { expr, list, negated } => {
+                let items: Vec<String> = list.iter().map(Expr::to_string).collect();
+                write!(f, "{} {}IN ({})", expr, if *negated { "NOT " } else { "" }, items.join(", "))
+            }
+            Expr::Aggregate(func, arg) => {
+                let name = match func {
+                    Aggregate::Count => "COUNT",
+                    Aggregate::Sum => "SUM",
+                    Aggregate::Avg => "AVG",
+                    Aggregate::Min => "MIN",
+                    Aggregate::Max => "MAX",
+                };
+                match arg {
+                    Some(arg) => write!(f, "{}({})", name, arg),
+                    None => write!(f, "{}(*)", name),
+                }
+            }
+        }
+    }
+}
+
+// --- Lexer ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// An unquoted identifier or keyword, as written.
+    Word(String),
+    /// A `"quoted"` identifier.
+    Quoted(String),
+    Str(String),
+    Number(String),
+    /// Hex digits of an `X'..'` literal.
+    Blob(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(w) | Token::Number(w) => f.write_str(w),
+            Token::Quoted(q) => write!(f, "\"{}\"", q),
+            Token::Str(s) => write!(f, "'{}'", s),
+            Token::Blob(b) => write!(f, "X'{}'", b),
+            Token::Symbol(s) => f.write_str(s),
+        }
+    }
+}
+
+const SYMBOLS: &[&str] = &[
+    "<>", "!=", "<=", ">=", "||", "(", ")", ",", ";", ".", "*", "+", "-", "/", "%", "=", "<", ">",
+];
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err("Syntax error: unterminated comment".into());
+            }
+            i += 2;
+        } else if (c == 'X' || c == 'x') && next == Some('\'') {
+            let (digits, end) = quoted(&chars, i + 1)?;
+            if digits.len() % 2 != 0 || !digits.chars().all(|d| d.is_ascii_hexdigit()) {
+                return Err(format!("Syntax error: invalid hex literal X'{}'", digits));
+            }
+            tokens.push(Token::Blob(digits.to_ascii_uppercase()));
+            i = end;
+        } else if c == '\'' || c == '"' {
+            let (text, end) = quoted(&chars, i)?;
+            tokens.push(if c == '\'' { Token::Str(text) } else { Token::Quoted(text) });
+            i = end;
+        } else if c.is_ascii_digit() || (c == '.' && next.is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            let symbol = SYMBOLS.iter()
+                .find(|s| s.chars().zip(&chars[i..]).filter(|(a, b)| a == *b).count() == s.len())
+                .ok_or_else(|| format!("Syntax error: unexpected character '{}'", c))?;
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads the quoted text starting at `start`, with doubled quotes as escapes. Returns the
+/// text and the position after the closing quote.
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut text = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) != Some(&quote) {
+                return Ok((text, i + 1));
+            }
+            i += 1;
+        }
+        text.push(chars[i]);
+        i += 1;
+    }
+    Err("Syntax error: unterminated quoted string".into())
+}
+
+// --- Parser ---
+
+/// Words that end an expression, so they are never taken as a bare column alias.
+const RESERVED: &[&str] = &[
+    "FROM", "WHERE", "ORDER", "GROUP", "HAVING", "LIMIT", "OFFSET", "AND", "OR", "NOT", "AS", "ON",
+    "JOIN", "UNION", "ASC", "DESC", "IS", "LIKE", "IN", "SET", "VALUES", "SELECT",
+];
+
+/// Parses one statement. A trailing `;` is allowed.
+pub(crate) fn parse(sql: &str) -> Result<Statement, String> {
+    let mut parser = Parser { tokens: tokenize(sql)?, pos: 0 };
+    let statement = parser.statement()?;
+    while parser.eat_symbol(";") {}
+    if parser.peek().is_some() {
+        return Err(parser.error("end of statement"));
+    }
+    Ok(statement)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(token) => format!("Syntax error: expected {}, found '{}'", expected, token),
+            None => format!("Syntax error: expected {}, found end of statement", expected),
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) { Ok(()) } else { Err(self.error(keyword)) }
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat_symbol(symbol) { Ok(()) } else { Err(self.error(&format!("'{}'", symbol))) }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => {
+                let name = w.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("a name")),
+        }
+    }
+
+    /// A table or column name. A `schema.` or `table.` qualifier is dropped.
+    fn qualified_ident(&mut self) -> Result<String, String> {
+        let mut name = self.ident()?;
+        while self.eat_symbol(".") {
+            name = self.ident()?;
+        }
+        Ok(name)
+    }
+
+    fn if_exists(&mut self) -> Result<bool, String> {
+        if self.eat_keyword("IF") {
+            self.expect_keyword("EXISTS")?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn if_not_exists(&mut self) -> Result<bool, String> {
+        if self.eat_keyword("IF") {
+            self.expect_keyword("NOT")?;
+            self.expect_keyword("EXISTS")?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn usize_literal(&mut self) -> Result<usize, String> {
+        match self.next() {
+            Some(Token::Number(n)) => n.parse().map_err(|_| format!("Syntax error: invalid count '{}'", n)),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("a number"))
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, String> {
+        let Some(Token::Word(word)) = self.peek().cloned() else {
+            return Err(self.error("a statement"));
+        };
+        self.pos += 1;
+        match word.to_ascii_uppercase().as_str() {
+            "CREATE" => self.create(),
+            "DROP" => {
+                if self.eat_keyword("DATABASE") {
+                    let if_exists = self.if_exists()?;
+                    Ok(Statement::DropDatabase { if_exists, name: self.ident()? })
+                } else {
+                    self.expect_keyword("TABLE")?;
+                    let if_exists = self.if_exists()?;
+                    Ok(Statement::DropTable { if_exists, name: self.qualified_ident()? })
+                }
+            }
+            "USE" => Ok(Statement::Use(self.ident()?)),
+            "INSERT" => self.insert(),
+            "SELECT" => Ok(Statement::Select(self.select_body()?)),
+            "UPDATE" => self.update(),
+            "DELETE" => {
+                self.expect_keyword("FROM")?;
+                let table = self.qualified_ident()?;
+                let filter = self.where_clause()?;
+                Ok(Statement::Delete { table, filter })
+            }
+            "SHOW" => {
+                if self.eat_keyword("TABLES") {
+                    Ok(Statement::ShowTables)
+                } else if self.eat_keyword("DATABASES") {
+                    Ok(Statement::ShowDatabases)
+                } else {
+                    Err(self.error("TABLES or DATABASES"))
+                }
+            }
+            "DESCRIBE" | "DESC" => Ok(Statement::Describe(self.qualified_ident()?)),
+            "BEGIN" => {
+                self.eat_keyword("TRANSACTION");
+                Ok(Statement::Begin)
+            }
+            "START" => {
+                self.expect_keyword("TRANSACTION")?;
+                Ok(Statement::Begin)
+            }
+            "COMMIT" => {
+                self.eat_keyword("TRANSACTION");
+                Ok(Statement::Commit)
+            }
+            "ROLLBACK" => {
+                self.eat_keyword("TRANSACTION");
+                if self.eat_keyword("TO") {
+                    self.eat_keyword("SAVEPOINT");
+                    return Ok(Statement::RollbackTo(self.ident()?));
+                }
+                Ok(Statement::Rollback)
+            }
+            "SAVEPOINT" => Ok(Statement::Savepoint(self.ident()?)),
+            "RELEASE" => {
+                self.eat_keyword("SAVEPOINT");
+                Ok(Statement::Release(self.ident()?))
+            }
+            "BACKUP" => match self.next() {
+                Some(Token::Str(name)) => Ok(Statement::Backup(name)),
+                _ => {
+                    self.pos -= 1;
+                    Err(self.error("a backup name"))
+                }
+            },
+            _ => {
+                self.pos -= 1;
+                Err(format!("Syntax error: unsupported statement '{}'", word))
+            }
+        }
+    }
+
+    fn create(&mut self) -> Result<Statement, String> {
+        if self.eat_keyword("DATABASE") {
+            let if_not_exists = self.if_not_exists()?;
+            return Ok(Statement::CreateDatabase { if_not_exists, name: self.ident()? });
+        }
+        if self.eat_keyword("USER") {
+            let name = self.ident()?;
+            // `PASSWORD 'x'` or `IDENTIFIED BY 'x'`; the mock does not check logins against it.
+            while self.peek().is_some_and(|t| *t != Token::Symbol(";")) {
+                self.pos += 1;
+            }
+            return Ok(Statement::CreateUser(name));
+        }
+        self.expect_keyword("TABLE")?;
+        let if_not_exists = self.if_not_exists()?;
+        let name = self.qualified_ident()?;
+        self.expect_symbol("(")?;
+        let mut columns: Vec<ColumnDef> = Vec::new();
+        loop {
+            if self.peek_keyword("PRIMARY") || self.peek_keyword("FOREIGN") || self.peek_keyword("UNIQUE") {
+                self.table_constraint(&mut columns)?;
+            } else {
+                columns.push(self.column_def()?);
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        self.expect_symbol(")")?;
+        Ok(Statement::CreateTable { name, if_not_exists, columns })
+    }
+
+    fn column_def(&mut self) -> Result<ColumnDef, String> {
+        let name = self.ident()?;
+        let type_name = self.ident()?.to_ascii_uppercase();
+        let ty = match type_name.as_str() {
+            "SERIAL" | "BIGSERIAL" => ColumnType::Serial,
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" => ColumnType::Int,
+            "DOUBLE" | "FLOAT" | "REAL" | "DECIMAL" | "NUMERIC" => {
+                self.eat_keyword("PRECISION");
+                ColumnType::Double
+            }
+            "TEXT" | "VARCHAR" | "CHAR" | "STRING" => ColumnType::Text,
+            "BOOL" | "BOOLEAN" => ColumnType::Bool,
+            "TIMESTAMP" | "DATETIME" | "DATE" => ColumnType::Timestamp,
+            "UUID" => ColumnType::Uuid,
+            "BLOB" | "BYTEA" => ColumnType::Blob,
+            other => return Err(format!("Syntax error: unknown column type '{}'", other)),
+        };
+        // Length or precision, e.g. VARCHAR(255) or DECIMAL(10, 2), is accepted and ignored.
+        if self.eat_symbol("(") {
+            while !self.eat_symbol(")") {
+                if self.next().is_none() {
+                    return Err(self.error("')'"));
+                }
+            }
+        }
+
+        let mut column = ColumnDef {
+            name,
+            ty,
+            primary_key: false,
+            not_null: false,
+            unique: false,
+            default: None,
+            references: None,
+        };
+        loop {
+            if self.eat_keyword("PRIMARY") {
+                self.expect_keyword("KEY")?;
+                column.primary_key = true;
+            } else if self.eat_keyword("NOT") {
+                self.expect_keyword("NULL")?;
+                column.not_null = true;
+            } else if self.eat_keyword("NULL") {
+                column.not_null = false;
+            } else if self.eat_keyword("UNIQUE") {
+                column.unique = true;
+            } else if self.eat_keyword("DEFAULT") {
+                column.default = Some(self.unary()?);
+            } else if self.eat_keyword("REFERENCES") {
+                column.references = Some(self.reference()?);
+            } else {
+                return Ok(column);
+            }
+        }
+    }
+
+    /// `PRIMARY KEY (col)`, `UNIQUE (col)` or `FOREIGN KEY (col) REFERENCES t(col)`.
+    fn table_constraint(&mut self, columns: &mut [ColumnDef]) -> Result<(), String> {
+        let kind = self.ident()?.to_ascii_uppercase();
+        if kind != "UNIQUE" {
+            self.expect_keyword("KEY")?;
+        }
+        self.expect_symbol("(")?;
+        let name = self.ident()?;
+        self.expect_symbol(")")?;
+        let column = columns.iter_mut()
+            .find(|c| c.name.eq_ignore_ascii_case(&name))
+            .ok_or_else(|| format!("Column '{}' not found", name))?;
+        match kind.as_str() {
+            "PRIMARY" => column.primary_key = true,
+            "UNIQUE" => column.unique = true,
+            _ => {
+                self.expect_keyword("REFERENCES")?;
+                column.references = Some(self.reference()?);
+            }
+        }
+        Ok(())
+    }
+
+    fn reference(&mut self) -> Result<(String, String), String> {
+        let table = self.qualified_ident()?;
+        self.expect_symbol("(")?;
+        let column = self.ident()?;
+        self.expect_symbol(")")?;
+        Ok((table, column))
+    }
+
+    fn insert(&mut self) -> Result<Statement, String> {
+        self.expect_keyword("INTO")?;
+        let table = self.qualified_ident()?;
+        let columns = if self.eat_symbol("(") {
+            let mut names = vec![self.ident()?];
+            while self.eat_symbol(",") {
+                names.push(self.ident()?);
+            }
+            self.expect_symbol(")")?;
+            Some(names)
+        } else {
+            None
+        };
+
+        let source = if self.eat_keyword("SELECT") {
+            InsertSource::Select(Box::new(self.select_body()?))
+        } else {
+            self.expect_keyword("VALUES")?;
+            let mut rows = Vec::new();
+            loop {
+                self.expect_symbol("(")?;
+                let mut row = vec![self.expr()?];
+                while self.eat_symbol(",") {
+                    row.push(self.expr()?);
+                }
+                self.expect_symbol(")")?;
+                rows.push(row);
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            InsertSource::Values(rows)
+        };
+        Ok(Statement::Insert { table, columns, source })
+    }
+
+    fn update(&mut self) -> Result<Statement, String> {
+        let table = self.qualified_ident()?;
+        self.expect_keyword("SET")?;
+        let mut assignments = Vec::new();
+        loop {
+            let column = self.qualified_ident()?;
+            self.expect_symbol("=")?;
+            assignments.push((column, self.expr()?));
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let filter = self.where_clause()?;
+        Ok(Statement::Update { table, assignments, filter })
+    }
+
+    fn where_clause(&mut self) -> Result<Option<Expr>, String> {
+        if self.eat_keyword("WHERE") { Ok(Some(self.expr()?)) } else { Ok(None) }
+    }
+
+    /// Everything after `SELECT`.
+    fn select_body(&mut self) -> Result<Select, String> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat_symbol("*") {
+                items.push(SelectItem::Wildcard);
+            } else {
+                let expr = self.expr()?;
+                let alias = if self.eat_keyword("AS") {
+                    Some(self.ident()?)
+                } else {
+                    match self.peek() {
+                        Some(Token::Word(w)) if !RESERVED.iter().any(|r| w.eq_ignore_ascii_case(r)) => Some(self.ident()?),
+                        Some(Token::Quoted(_)) => Some(self.ident()?),
+                        _ => None,
+                    }
+                };
+                items.push(SelectItem::Expr { expr, alias });
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+
+        let from = if self.eat_keyword("FROM") { Some(self.qualified_ident()?) } else { None };
+        if self.peek_keyword("JOIN") || self.peek_keyword("INNER") || self.peek_keyword("LEFT") || self.eat_symbol(",") {
+            return Err("Syntax error: joins are not supported".into());
+        }
+        let filter = self.where_clause()?;
+        if self.peek_keyword("GROUP") || self.peek_keyword("HAVING") {
+            return Err("Syntax error: GROUP BY is not supported".into());
+        }
+
+        let mut order_by = Vec::new();
+        if self.eat_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                let expr = self.expr()?;
+                let desc = if self.eat_keyword("DESC") {
+                    true
+                } else {
+                    self.eat_keyword("ASC");
+                    false
+                };
+                order_by.push((expr, desc));
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let mut limit = None;
+        let mut offset = None;
+        loop {
+            if limit.is_none() && self.eat_keyword("LIMIT") {
+                limit = Some(self.usize_literal()?);
+            } else if offset.is_none() && self.eat_keyword("OFFSET") {
+                offset = Some(self.usize_literal()?);
+            } else {
+                break;
+            }
+        }
+        Ok(Select { items, from, filter, order_by, limit, offset })
+    }
+
+    // Precedence, loosest first: OR, AND, NOT, comparisons, + - ||, * / %, unary minus.
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat_keyword("OR") {
+            left = Expr::Binary(Box::new(left), BinaryOp::Or, Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.eat_keyword("AND") {
+            left = Expr::Binary(Box::new(left), BinaryOp::And, Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let op = match self.peek() {
+            Some(Token::Symbol("=")) => BinaryOp::Eq,
+            Some(Token::Symbol("<>")) | Some(Token::Symbol("!=")) => BinaryOp::NotEq,
+            Some(Token::Symbol("<")) => BinaryOp::Lt,
+            Some(Token::Symbol("<=")) => BinaryOp::LtEq,
+            Some(Token::Symbol(">")) => BinaryOp::Gt,
+            Some(Token::Symbol(">=")) => BinaryOp::GtEq,
+            _ => return self.postfix(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Binary(Box::new(left), op, Box::new(self.additive()?)))
+    }
+
+    /// `IS [NOT] NULL`, `[NOT] LIKE` and `[NOT] IN (...)`.
+    fn postfix(&mut self, left: Expr) -> Result<Expr, String> {
+        let expr = Box::new(left);
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull { expr, negated });
+        }
+        let start = self.pos;
+        let negated = self.eat_keyword("NOT");
+        if self.eat_keyword("LIKE") {
+            return Ok(Expr::Like { expr, pattern: Box::new(self.additive()?), negated });
+        }
+        if self.eat_keyword("IN") {
+            self.expect_symbol("(")?;
+            let mut list = vec![self.expr()?];
+            while self.eat_symbol(",") {
+                list.push(self.expr()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expr::InList { expr, list, negated });
+        }
+        self.pos = start;
+        Ok(*expr)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("+")) => BinaryOp::Add,
+                Some(Token::Symbol("-")) => BinaryOp::Sub,
+                Some(Token::Symbol("||")) => BinaryOp::Concat,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol("*")) => BinaryOp::Mul,
+                Some(Token::Symbol("/")) => BinaryOp::Div,
+                Some(Token::Symbol("%")) => BinaryOp::Mod,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_symbol("-") {
+            return Ok(match self.unary()? {
+                Expr::Literal(Value::Int(i)) => Expr::Literal(Value::Int(-i)),
+                Expr::Literal(Value::Double(d)) => Expr::Literal(Value::Double(-d)),
+                other => Expr::Neg(Box::new(other)),
+            });
+        }
+        self.eat_symbol("+");
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.next() else {
+            return Err(self.error("an expression"));
+        };
+        match token {
+            Token::Number(n) => {
+                let value = if n.contains('.') {
+                    n.parse().map(Value::Double).ok()
+                } else {
+                    n.parse().map(Value::Int).ok()
+                };
+                value.map(Expr::Literal).ok_or_else(|| format!("Syntax error: invalid number '{}'", n))
+            }
+            Token::Str(s) | Token::Blob(s) => Ok(Expr::Literal(Value::Text(s))),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Token::Quoted(_) => {
+                self.pos -= 1;
+                Ok(Expr::Column(self.qualified_ident()?))
+            }
+            Token::Word(word) => {
+                let func = match word.to_ascii_uppercase().as_str() {
+                    "NULL" => return Ok(Expr::Literal(Value::Null)),
+                    "TRUE" => return Ok(Expr::Literal(Value::Bool(true))),
+                    "FALSE" => return Ok(Expr::Literal(Value::Bool(false))),
+                    "COUNT" => Some(Aggregate::Count),
+                    "SUM" => Some(Aggregate::Sum),
+                    "AVG" => Some(Aggregate::Avg),
+                    "MIN" => Some(Aggregate::Min),
+                    "MAX" => Some(Aggregate::Max),
+                    _ => None,
+                };
+                if let Some(func) = func
+                    && self.eat_symbol("(")
+                {
+                    let arg = if func == Aggregate::Count && self.eat_symbol("*") {
+                        None
+                    } else {
+                        Some(Box::new(self.expr()?))
+                    };
+                    self.expect_symbol(")")?;
+                    return Ok(Expr::Aggregate(func, arg));
+                }
+                if RESERVED.iter().any(|r| word.eq_ignore_ascii_case(r)) {
+                    self.pos -= 1;
+                    return Err(self.error("an expression"));
+                }
+                self.pos -= 1;
+                Ok(Expr::Column(self.qualified_ident()?))
+            }
+            Token::Symbol(_) => {
+                self.pos -= 1;
+                Err(self.error("an expression"))
+            }
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerName};
+
+use crate::pin::{Fingerprint, PinnedVerifier, TofuVerifier};
+use crate::MaazDBError;
+
+/// How the server certificate is checked during the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TlsMode {
+    /// Accept any certificate. Only suitable for local development.
+    NoVerify,
+    /// Verify the certificate chain against the trust roots, but not the hostname.
+    VerifyCa,
+    /// Verify the certificate chain and that it was issued for the host passed to `connect`.
+    #[default]
+    VerifyFull,
+    /// Accept only a leaf certificate with this fingerprint, e.g. a known self-signed server.
+    Pinned(Fingerprint),
+    /// Record the server's public key fingerprint in this known_hosts-style file on first
+    /// connection, and refuse any other key for the same `host:port` afterwards.
+    TrustOnFirstUse(PathBuf),
+}
+
+/// Where trusted CA certificates come from when the mode verifies the chain.
+#[derive(Debug, Clone, Default)]
+pub enum TrustRoots {
+    /// The operating system's certificate store.
+    #[default]
+    System,
+    /// A PEM bundle on disk.
+    CaFile(PathBuf),
+    /// A PEM bundle already in memory.
+    CaPem(Vec<u8>),
+}
+
+/// A client certificate chain and private key presented to servers that require mutual TLS.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    certs: Vec<Certificate>,
+    key: PrivateKey,
+}
+
+impl ClientIdentity {
+    /// Loads a PEM certificate chain (leaf first) and a PEM private key (PKCS#8, RSA or SEC1).
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, MaazDBError> {
+        let certs = parse_pem_certs(cert_pem)?;
+        if certs.is_empty() {
+            return Err(MaazDBError::TlsError(rustls::Error::General("No client certificate found in PEM".into())));
+        }
+
+        let key = rustls_pemfile::read_all(&mut &key_pem[..])?
+            .into_iter()
+            .find_map(|item| match item {
+                rustls_pemfile::Item::PKCS8Key(der)
+                | rustls_pemfile::Item::RSAKey(der)
+                | rustls_pemfile::Item::ECKey(der) => Some(der),
+                _ => None,
+            })
+            .ok_or_else(|| MaazDBError::TlsError(rustls::Error::General("No private key found in PEM".into())))?;
+
+        Ok(Self::from_der(certs, key))
+    }
+
+    /// Reads the certificate chain and private key from PEM files.
+    pub fn from_pem_files(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Result<Self, MaazDBError> {
+        Self::from_pem(&fs::read(cert_path)?, &fs::read(key_path)?)
+    }
+
+    /// Uses a DER certificate chain (leaf first) and a DER PKCS#8 private key.
+    pub fn from_der(certs: Vec<Vec<u8>>, key: Vec<u8>) -> Self {
+        ClientIdentity {
+            certs: certs.into_iter().map(Certificate).collect(),
+            key: PrivateKey(key),
+        }
+    }
+}
+
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("certs", &self.certs.len())
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// TLS settings for a connection.
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    pub mode: TlsMode,
+    pub roots: TrustRoots,
+    pub client_identity: Option<ClientIdentity>,
+}
+
+impl TlsOptions {
+    pub fn new(mode: TlsMode) -> Self {
+        TlsOptions { mode, ..Default::default() }
+    }
+
+    /// Presents `identity` to the server during the handshake (mutual TLS).
+    pub fn client_identity(mut self, identity: ClientIdentity) -> Self {
+        self.client_identity = Some(identity);
+        self
+    }
+
+    /// Trusts the CA certificates in the PEM file at `path` instead of the system roots.
+    pub fn ca_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.roots = TrustRoots::CaFile(path.into());
+        self
+    }
+
+    /// Trusts the CA certificates in `pem` instead of the system roots.
+    pub fn ca_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.roots = TrustRoots::CaPem(pem.into());
+        self
+    }
+
+    /// Builds the rustls client configuration for connecting to `host:port`.
+    pub(crate) fn client_config(&self, host: &str, port: u16) -> Result<Arc<ClientConfig>, MaazDBError> {
+        let verifier: Arc<dyn ServerCertVerifier> = match &self.mode {
+            TlsMode::NoVerify => Arc::new(NoCertificateVerification),
+            TlsMode::VerifyCa => Arc::new(CaOnlyVerifier(WebPkiVerifier::new(self.root_store()?, None))),
+            TlsMode::VerifyFull => Arc::new(WebPkiVerifier::new(self.root_store()?, None)),
+            TlsMode::Pinned(fp) => Arc::new(PinnedVerifier(*fp)),
+            TlsMode::TrustOnFirstUse(path) => {
+                Arc::new(TofuVerifier::new(path.clone(), format!("{}:{}", host, port)))
+            }
+        };
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(verifier);
+        let config = match &self.client_identity {
+            Some(identity) => builder.with_client_auth_cert(identity.certs.clone(), identity.key.clone())?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+
+    fn root_store(&self) -> Result<RootCertStore, MaazDBError> {
+        let certs = match &self.roots {
+            TrustRoots::System => rustls_native_certs::load_native_certs()?
+                .into_iter()
+                .map(|c| c.0)
+                .collect(),
+            TrustRoots::CaFile(path) => parse_pem_certs(&fs::read(path)?)?,
+            TrustRoots::CaPem(pem) => parse_pem_certs(pem)?,
+        };
+
+        let mut store = RootCertStore::empty();
+        let (added, _) = store.add_parsable_certificates(&certs);
+        if added == 0 {
+            return Err(MaazDBError::TlsError(rustls::Error::General("No usable CA certificates found".into())));
+        }
+        Ok(store)
+    }
+}
+
+fn parse_pem_certs(pem: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    rustls_pemfile::certs(&mut &pem[..])
+}
+
+/// Parses the host passed to `connect` as the name the certificate must match.
+pub(crate) fn server_name(host: &str) -> Result<ServerName, MaazDBError> {
+    ServerName::try_from(host)
+        .map_err(|_| MaazDBError::TlsError(rustls::Error::General(format!("Invalid server name '{}'", host))))
+}
+
+/// Whether an IO error was caused by a TLS error (e.g. a received alert).
+pub(crate) fn is_tls_error(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<rustls::Error>())
+}
+
+/// Turns a handshake IO error back into the TLS error that caused it.
+pub(crate) fn handshake_error(err: io::Error) -> MaazDBError {
+    let tls_err = err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        .cloned();
+    match tls_err {
+        Some(e @ rustls::Error::InvalidCertificate(_)) => MaazDBError::CertificateError(e),
+        Some(e) => MaazDBError::TlsError(e),
+        None => MaazDBError::IoError(err),
+    }
+}
+
+// Helper for self-signed certs. Handshake signatures are still checked by the trait's
+// default webpki implementation, which the pinning verifiers reuse.
+pub(crate) struct NoCertificateVerification;
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(&self, _: &Certificate, _: &[Certificate], _: &ServerName, _: &mut dyn Iterator<Item = &[u8]>, _: &[u8], _: SystemTime) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Verifies the chain like `WebPkiVerifier`, but ignores a hostname mismatch.
+struct CaOnlyVerifier(WebPkiVerifier);
+impl ServerCertVerifier for CaOnlyVerifier {
+    fn verify_server_cert(&self, end_entity: &Certificate, intermediates: &[Certificate], server_name: &ServerName, scts: &mut dyn Iterator<Item = &[u8]>, ocsp_response: &[u8], now: SystemTime) -> Result<ServerCertVerified, rustls::Error> {
+        match self.0.verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now) {
+            Err(rustls::Error::InvalidCertificate(rustls::CertificateError::NotValidForName)) => {
+                Ok(ServerCertVerified::assertion())
+            }
+            other => other,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{MaazDB, MaazDBError};
+
+/// An open transaction on a [`MaazDB`] connection.
+///
+/// Derefs to the connection, so all query methods are available. Changes are only kept
+/// if [`Transaction::commit`] is called: dropping the guard (including during a panic)
+/// rolls back. Calling [`Transaction::transaction`] nests a savepoint.
+pub struct Transaction<'a> {
+    conn: &'a mut MaazDB,
+    /// Savepoint name for nested transactions, `None` for the outermost one.
+    savepoint: Option<String>,
+    depth: usize,
+    finished: bool,
+}
+
+impl MaazDB {
+    /// Issues `BEGIN` and returns a guard that rolls back unless committed.
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, MaazDBError> {
+        // `send_query` notices the `BEGIN` and marks the connection as in a transaction.
+        self.query("BEGIN;")?;
+        Ok(Transaction { conn: self, savepoint: None, depth: 0, finished: false })
+    }
+}
+
+impl Transaction<'_> {
+    /// Starts a nested transaction backed by a savepoint.
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, MaazDBError> {
+        let depth = self.depth + 1;
+        let name = format!("maazdb_sp_{}", depth);
+        self.conn.query(&format!("SAVEPOINT {};", name))?;
+        Ok(Transaction { conn: &mut *self.conn, savepoint: Some(name), depth, finished: false })
+    }
+
+    /// Makes the changes permanent (or, when nested, keeps them in the enclosing transaction).
+    pub fn commit(mut self) -> Result<(), MaazDBError> {
+        self.finished = true;
+        let sql = match &self.savepoint {
+            Some(name) => format!("RELEASE SAVEPOINT {};", name),
+            None => "COMMIT;".to_string(),
+        };
+        self.finish(&sql)
+    }
+
+    /// Discards the changes made in this transaction.
+    pub fn rollback(mut self) -> Result<(), MaazDBError> {
+        self.finished = true;
+        self.rollback_statement()
+    }
+
+    /// Nesting level: 0 for the outermost transaction.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn rollback_statement(&mut self) -> Result<(), MaazDBError> {
+        let sql = match &self.savepoint {
+            Some(name) => format!("ROLLBACK TO SAVEPOINT {};", name),
+            None => "ROLLBACK;".to_string(),
+        };
+        self.finish(&sql)
+    }
+
+    fn finish(&mut self, sql: &str) -> Result<(), MaazDBError> {
+        let result = self.conn.query(sql).map(|_| ());
+        if self.savepoint.is_none() {
+            self.conn.in_transaction = false;
+        }
+        result
+    }
+}
+
+impl Deref for Transaction<'_> {
+    type Target = MaazDB;
+
+    fn deref(&self) -> &MaazDB {
+        self.conn
+    }
+}
+
+impl DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut MaazDB {
+        self.conn
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            // Best effort: if the connection is gone, the server discards the transaction anyway.
+            let _ = self.rollback_statement();
+        }
+    }
+}
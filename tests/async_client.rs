@@ -0,0 +1,67 @@
+// `AsyncMaazDB` against `MockServer`: queries, typed rows and server errors.
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ConnectionState, ErrorCategory, FromRow, MaazDBError, Value};
+
+#[derive(Debug, PartialEq, FromRow)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+#[tokio::test]
+async fn query_and_execute() {
+    let server = MockServer::start();
+    server.expect_query("SELECT id, name FROM users;").times(2)
+        .respond_rows(&["id", "name"], vec![vec![Value::Int(1), Value::Text("Ada".into())]]);
+    server.expect_query("INSERT INTO users (name) VALUES ('Alan');").respond_message("Inserted 1 row");
+
+    let mut db = server.connect_async().await.unwrap();
+    assert_eq!(db.query("SELECT id, name FROM users;").await.unwrap(), r#"{"data":[[1,"Ada"]],"headers":["id","name"]}"#);
+
+    let rows = db.execute("SELECT id, name FROM users;").await.unwrap().into_rows().unwrap();
+    assert_eq!(rows.columns(), ["id", "name"]);
+    assert_eq!(rows.get(0).unwrap().get_named("name"), Some(&Value::Text("Ada".into())));
+
+    let done = db.execute("INSERT INTO users (name) VALUES ('Alan');").await.unwrap();
+    assert_eq!(done.message(), Some("Inserted 1 row"));
+    assert_eq!(db.state(), ConnectionState::Ready);
+    server.verify();
+}
+
+#[tokio::test]
+async fn query_as_decodes_rows() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect_async().await.unwrap();
+    db.query("CREATE DATABASE app;").await.unwrap();
+    db.query("USE app;").await.unwrap();
+    db.query("CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT);").await.unwrap();
+    db.query_with("INSERT INTO users (name) VALUES ($1);", &[&"O'Brien"]).await.unwrap();
+    db.query("INSERT INTO users (name) VALUES ('Zoë');").await.unwrap();
+
+    let users: Vec<User> = db.query_as("SELECT id, name FROM users ORDER BY id;").await.unwrap();
+    assert_eq!(users, [User { id: 1, name: "O'Brien".into() }, User { id: 2, name: "Zoë".into() }]);
+    let pairs: Vec<(i64, String)> = db.query_as("SELECT id, name FROM users WHERE id = 2;").await.unwrap();
+    assert_eq!(pairs, [(2, "Zoë".to_string())]);
+}
+
+#[tokio::test]
+async fn server_errors_leave_the_connection_usable() {
+    let server = MockServer::start();
+    server.expect_query("SELECT * FROM missing;").respond_error("Table 'missing' not found");
+    server.expect_query("SELECT 1;").respond_rows(&["1"], vec![vec![Value::Int(1)]]);
+
+    let mut db = server.connect_async().await.unwrap();
+    match db.query_as::<(i64,)>("SELECT * FROM missing;").await {
+        Err(MaazDBError::ServerError(e)) => {
+            assert_eq!(e.category, ErrorCategory::UnknownTable);
+            assert_eq!(e.message, "Table 'missing' not found");
+            assert_eq!(e.statement, "SELECT * FROM missing;");
+        }
+        other => panic!("expected a ServerError, got {:?}", other),
+    }
+    assert_eq!(db.state(), ConnectionState::Ready);
+    assert_eq!(db.query_as::<(i64,)>("SELECT 1;").await.unwrap(), [(1,)]);
+    assert_eq!(server.connections(), 1);
+    server.verify();
+}
@@ -0,0 +1,90 @@
+// Decoding cells and rows with `FromValue`, tuples and `#[derive(FromRow)]`.
+
+use maazdb_rs::{FromRow, MaazDBError, ResultSet, Row};
+
+fn rows(payload: &str) -> ResultSet {
+    ResultSet::from_json(payload).unwrap()
+}
+
+fn users() -> ResultSet {
+    rows(r#"{"headers":["id","full_name","age","nick"],"data":[[1,"Ada Lovelace",36,null],[2,"Alan Turing","41","al"]]}"#)
+}
+
+#[derive(Debug, PartialEq, FromRow)]
+struct User {
+    id: i64,
+    #[maazdb(rename = "full_name")]
+    name: String,
+    age: u8,
+    nick: Option<String>,
+}
+
+#[test]
+fn derive_decodes_by_column_name() {
+    let decoded: Vec<User> = users().decode().unwrap();
+    assert_eq!(decoded, [
+        User { id: 1, name: "Ada Lovelace".into(), age: 36, nick: None },
+        User { id: 2, name: "Alan Turing".into(), age: 41, nick: Some("al".into()) },
+    ]);
+}
+
+#[test]
+fn null_into_a_non_option_field() {
+    let rs = users();
+    let row = rs.get(0).unwrap();
+    match row.try_get::<String, _>("nick") {
+        Err(MaazDBError::UnexpectedNull(column)) => assert_eq!(column, "nick"),
+        other => panic!("expected UnexpectedNull, got {:?}", other),
+    }
+    assert_eq!(row.try_get::<Option<String>, _>("nick").unwrap(), None);
+
+    #[derive(Debug, FromRow)]
+    #[allow(dead_code)]
+    struct Strict {
+        nick: String,
+    }
+    assert!(matches!(Strict::from_row(row), Err(MaazDBError::UnexpectedNull(column)) if column == "nick"));
+}
+
+#[test]
+fn text_into_an_integer() {
+    let rs = users();
+    let row = rs.get(1).unwrap();
+    // Digits in a TEXT cell are accepted, anything else is a mismatch naming the column.
+    assert_eq!(row.try_get::<i64, _>("age").unwrap(), 41);
+    match row.try_get::<i64, _>("full_name") {
+        Err(MaazDBError::TypeMismatch(msg)) => {
+            assert!(msg.starts_with("column 'full_name': expected an integer"), "{}", msg);
+        }
+        other => panic!("expected a TypeMismatch, got {:?}", other),
+    }
+    let big = rows(r#"{"headers":["n"],"data":[[300]]}"#);
+    match big.get(0).unwrap().try_get::<u8, _>("n") {
+        Err(MaazDBError::TypeMismatch(msg)) => assert!(msg.contains("out of range for u8"), "{}", msg),
+        other => panic!("expected a TypeMismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn tuple_length_must_fit_the_row() {
+    let rs = rows(r#"{"headers":["a","b"],"data":[[1,"x"]]}"#);
+    assert_eq!(rs.decode::<(i64, String)>().unwrap(), [(1, "x".to_string())]);
+    // Extra columns are ignored, a tuple longer than the row is not.
+    assert_eq!(rs.decode::<(i64,)>().unwrap(), [(1,)]);
+    match rs.decode::<(i64, String, bool)>() {
+        Err(MaazDBError::ColumnNotFound(msg)) => assert_eq!(msg, "index 2 (row has 2 columns)"),
+        other => panic!("expected ColumnNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn missing_column() {
+    let rs = rows(r#"{"headers":["id","name"],"data":[[1,"Ada"]]}"#);
+    let row: &Row = rs.get(0).unwrap();
+    assert!(matches!(row.try_get::<i64, _>("age"), Err(MaazDBError::ColumnNotFound(c)) if c == "age"));
+    // The derive looks up the renamed column, not the field name.
+    match rs.decode::<User>() {
+        Err(MaazDBError::ColumnNotFound(column)) => assert_eq!(column, "full_name"),
+        other => panic!("expected ColumnNotFound, got {:?}", other),
+    }
+}
@@ -0,0 +1,166 @@
+// `migrate::Migrator` against the in-memory engine, with the scripts in tests/migrations.
+
+use maazdb_rs::migrate::{embed_migrations, Migration, MigrationState, Migrator, HISTORY_TABLE};
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{MaazDB, MaazDBError};
+
+const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/migrations");
+
+fn database(server: &MockServer) -> MaazDB {
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE app;").unwrap();
+    db.query("USE app;").unwrap();
+    db
+}
+
+fn tables(db: &mut MaazDB) -> Vec<String> {
+    db.query_as::<(String,)>("SHOW TABLES;").unwrap().into_iter().map(|(t,)| t).collect()
+}
+
+fn states(migrator: &Migrator, db: &mut MaazDB) -> Vec<(u32, MigrationState)> {
+    migrator.status(db).unwrap().into_iter().map(|s| (s.version, s.state)).collect()
+}
+
+fn migration_error(result: Result<Vec<u32>, MaazDBError>) -> String {
+    match result {
+        Err(MaazDBError::MigrationError(msg)) => msg,
+        other => panic!("expected a migration error, got {:?}", other),
+    }
+}
+
+#[test]
+fn loads_directories_and_embeds() {
+    let loaded = Migrator::from_dir(DIR).unwrap();
+    let embedded: Migrator = embed_migrations!("tests/migrations");
+    assert_eq!(loaded.migrations(), embedded.migrations());
+
+    let names: Vec<String> = loaded.migrations().iter().map(|m| m.to_string()).collect();
+    assert_eq!(names, ["V001__create_users", "V002__create_orders", "V003__seed_orders"]);
+    assert_eq!(loaded.migrations()[0].down.as_deref(), Some("DROP TABLE users;\n"));
+    assert_eq!(loaded.migrations()[2].down, None);
+}
+
+#[test]
+fn applies_pending_migrations_once() {
+    let server = MockServer::with_engine();
+    let mut db = database(&server);
+    let migrator = Migrator::from_dir(DIR).unwrap();
+
+    assert!(migrator.applied(&mut db).unwrap().is_empty());
+    assert_eq!(migrator.run(&mut db).unwrap(), [1, 2, 3]);
+    assert_eq!(tables(&mut db), [HISTORY_TABLE, "orders", "users"]);
+    let orders: Vec<(i64, f64)> = db.query_as("SELECT user_id, amount FROM orders ORDER BY id;").unwrap();
+    assert_eq!(orders, [(1, 9.99), (1, 20.0)]);
+
+    let applied = migrator.applied(&mut db).unwrap();
+    assert_eq!(applied.len(), 3);
+    assert_eq!(applied[1].name, "create_orders");
+    assert_eq!(applied[1].checksum, migrator.migrations()[1].checksum());
+    assert!(applied[1].applied_at.is_some());
+
+    assert!(migrator.run(&mut db).unwrap().is_empty());
+    assert_eq!(states(&migrator, &mut db), [(1, MigrationState::Applied), (2, MigrationState::Applied), (3, MigrationState::Applied)]);
+}
+
+#[test]
+fn detects_edits_and_missing_scripts() {
+    let server = MockServer::with_engine();
+    let mut db = database(&server);
+    let first = Migrator::new([Migration::new(1, "create_users", "CREATE TABLE users (id SERIAL PRIMARY KEY);")]);
+    first.run(&mut db).unwrap();
+
+    // Line endings alone do not count as an edit.
+    assert_eq!(
+        Migration::new(1, "a", "CREATE TABLE t (id INT);\r\nINSERT INTO t VALUES (1);\r\n").checksum(),
+        Migration::new(1, "a", "CREATE TABLE t (id INT);\nINSERT INTO t VALUES (1);\n").checksum(),
+    );
+
+    let edited = Migrator::new([
+        Migration::new(1, "create_users", "CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT);"),
+        Migration::new(2, "more", "CREATE TABLE more (id INT);"),
+    ]);
+    assert_eq!(states(&edited, &mut db), [(1, MigrationState::Modified), (2, MigrationState::Pending)]);
+    assert!(migration_error(edited.run(&mut db)).contains("V001__create_users was edited"));
+    assert!(!tables(&mut db).contains(&"more".to_string()));
+
+    let renumbered = Migrator::new([Migration::new(2, "create_users", "CREATE TABLE users (id SERIAL PRIMARY KEY);")]);
+    assert_eq!(states(&renumbered, &mut db), [(1, MigrationState::Missing), (2, MigrationState::Pending)]);
+    assert!(migration_error(renumbered.run(&mut db)).contains("no longer exists"));
+}
+
+#[test]
+fn rejects_out_of_order_and_duplicate_versions() {
+    let server = MockServer::with_engine();
+    let mut db = database(&server);
+    Migrator::new([Migration::new(2, "b", "CREATE TABLE b (id INT);")]).run(&mut db).unwrap();
+
+    let late = Migrator::new([
+        Migration::new(1, "a", "CREATE TABLE a (id INT);"),
+        Migration::new(2, "b", "CREATE TABLE b (id INT);"),
+    ]);
+    assert!(migration_error(late.run(&mut db)).contains("V001__a is pending, but V002 is already applied"));
+
+    let twice = Migrator::new([
+        Migration::new(3, "c", "CREATE TABLE c (id INT);"),
+        Migration::new(3, "d", "CREATE TABLE d (id INT);"),
+    ]);
+    assert!(migration_error(twice.run(&mut db)).contains("share a version"));
+}
+
+#[test]
+fn failed_migrations_roll_back() {
+    let server = MockServer::with_engine();
+    let mut db = database(&server);
+    let migrator = Migrator::new([
+        Migration::new(1, "ok", "CREATE TABLE ok (id INT);"),
+        Migration::new(2, "broken", "CREATE TABLE half (id INT); INSERT INTO nowhere VALUES (1);"),
+        Migration::new(3, "after", "CREATE TABLE after (id INT);"),
+    ]);
+
+    let msg = migration_error(migrator.run(&mut db));
+    assert!(msg.starts_with("V002__broken failed"), "{}", msg);
+    assert_eq!(tables(&mut db), [HISTORY_TABLE, "ok"]);
+    assert_eq!(states(&migrator, &mut db)[1..], [(2, MigrationState::Pending), (3, MigrationState::Pending)]);
+}
+
+#[test]
+fn reverts_with_down_scripts() {
+    let server = MockServer::with_engine();
+    let mut db = database(&server);
+    let migrator = Migrator::from_dir(DIR).unwrap();
+    migrator.run(&mut db).unwrap();
+
+    // V003 has no down script.
+    assert!(migration_error(migrator.revert(&mut db, 1)).contains("V003__seed_orders has no down migration"));
+    assert_eq!(tables(&mut db), [HISTORY_TABLE, "orders", "users"]);
+
+    let without_seed = Migrator::new(migrator.migrations()[..2].iter().cloned());
+    db.query("DELETE FROM orders;").unwrap();
+    db.query(&format!("DELETE FROM {} WHERE version = 3;", HISTORY_TABLE)).unwrap();
+    assert_eq!(without_seed.revert(&mut db, 1).unwrap(), [2]);
+    assert_eq!(tables(&mut db), [HISTORY_TABLE, "users"]);
+    assert_eq!(without_seed.revert(&mut db, 0).unwrap(), [1]);
+    assert_eq!(tables(&mut db), [HISTORY_TABLE]);
+    assert!(without_seed.applied(&mut db).unwrap().is_empty());
+
+    assert_eq!(migrator.run(&mut db).unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn rejects_unexpected_file_names() {
+    let dir = std::env::temp_dir().join(format!("maazdb-migrations-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("README.md"), "ignored").unwrap();
+    std::fs::write(dir.join("V1__a.sql"), "CREATE TABLE a (id INT);").unwrap();
+    assert_eq!(Migrator::from_dir(&dir).unwrap().migrations().len(), 1);
+
+    std::fs::write(dir.join("U2__b.sql"), "DROP TABLE b;").unwrap();
+    let orphan = Migrator::from_dir(&dir).unwrap_err();
+    std::fs::remove_file(dir.join("U2__b.sql")).unwrap();
+    std::fs::write(dir.join("V2_b.sql"), "CREATE TABLE b (id INT);").unwrap();
+    let typo = Migrator::from_dir(&dir).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(orphan.to_string().contains("U002__b.sql has no matching V002 migration"), "{}", orphan);
+    assert!(typo.to_string().contains("'V2_b.sql' is not named"), "{}", typo);
+}
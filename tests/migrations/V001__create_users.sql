@@ -0,0 +1,7 @@
+-- Accounts; `name` is shown on invoices.
+CREATE TABLE users (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    email TEXT UNIQUE
+);
+INSERT INTO users (name, email) VALUES ('admin', 'admin@example.com');
@@ -0,0 +1,6 @@
+CREATE TABLE orders (
+    id SERIAL PRIMARY KEY,
+    user_id INT,
+    amount DOUBLE,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
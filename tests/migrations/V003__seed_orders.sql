@@ -0,0 +1 @@
+INSERT INTO orders (user_id, amount) VALUES (1, 9.99), (1, 20.00);
@@ -0,0 +1,222 @@
+// The statements the conformance suite relies on, one by one against `MockServer::with_engine`.
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ErrorCategory, MaazDB, MaazDBError, QueryResult, ResultSet, Value};
+
+fn rows(db: &mut MaazDB, sql: &str) -> ResultSet {
+    match db.execute(sql) {
+        Ok(QueryResult::Rows(rs)) => rs,
+        other => panic!("{}: expected rows, got {:?}", sql, other),
+    }
+}
+
+fn message(db: &mut MaazDB, sql: &str) -> String {
+    match db.execute(sql) {
+        Ok(QueryResult::CommandComplete(msg)) => msg,
+        other => panic!("{}: expected a message, got {:?}", sql, other),
+    }
+}
+
+fn category(result: Result<String, MaazDBError>) -> ErrorCategory {
+    match result {
+        Err(MaazDBError::ServerError(e)) => e.category,
+        other => panic!("expected a server error, got {:?}", other),
+    }
+}
+
+fn users_db(server: &MockServer) -> MaazDB {
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE testdb;").unwrap();
+    db.query("USE testdb;").unwrap();
+    db.query("CREATE TABLE users (
+        id SERIAL PRIMARY KEY,
+        name TEXT,
+        age INT,
+        salary DOUBLE,
+        active BOOL,
+        created TIMESTAMP,
+        uuid UUID
+    );").unwrap();
+    let inserts = [
+        "INSERT INTO users (name, age, salary, active, created, uuid) VALUES ('Alice', 30, 50000.50, TRUE, '2024-01-15 10:30:00', '550e8400-e29b-41d4-a716-446655440000');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Bob', 25, 45000.75, FALSE, '550e8400-e29b-41d4-a716-446655440001');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Charlie', 35, 60000.00, TRUE, '550e8400-e29b-41d4-a716-446655440002');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('David', 40, 70000.00, FALSE, '550e8400-e29b-41d4-a716-446655440003');",
+        "INSERT INTO users (name, age, salary, active, uuid) VALUES ('Eve', 20, 30000.00, TRUE, '550e8400-e29b-41d4-a716-446655440004');",
+    ];
+    for insert in inserts {
+        assert_eq!(message(&mut db, insert), "Inserted 1 row");
+    }
+    db
+}
+
+#[test]
+fn select_filters_orders_and_pages() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server);
+
+    let all = rows(&mut db, "SELECT * FROM users;");
+    assert_eq!(all.columns(), ["id", "name", "age", "salary", "active", "created", "uuid"]);
+    let ids: Vec<(i64, String)> = db.query_as("SELECT id, name FROM users;").unwrap();
+    assert_eq!(ids[0], (1, "Alice".to_string()));
+    assert_eq!(ids[4], (5, "Eve".to_string()));
+
+    let alice = rows(&mut db, "SELECT * FROM users WHERE name = 'Alice';");
+    assert_eq!(alice.len(), 1);
+    assert_eq!(alice.get(0).unwrap().get_named("created"), Some(&Value::Text("2024-01-15 10:30:00".into())));
+    assert_eq!(rows(&mut db, "SELECT * FROM users WHERE age > 25;").len(), 3);
+    assert_eq!(rows(&mut db, "SELECT * FROM users WHERE age > 25 AND active = TRUE;").len(), 2);
+    assert_eq!(rows(&mut db, "SELECT * FROM users WHERE (age > 20 AND active = TRUE) OR salary > 50000;").len(), 3);
+    assert_eq!(rows(&mut db, "SELECT * FROM users WHERE id = 1;").len(), 1);
+
+    let by_age: Vec<(String, i64)> = db.query_as("SELECT name, age FROM users ORDER BY age DESC;").unwrap();
+    assert_eq!(by_age.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), ["David", "Charlie", "Alice", "Bob", "Eve"]);
+    let page: Vec<(i64,)> = db.query_as("SELECT id FROM users ORDER BY id LIMIT 2 OFFSET 1;").unwrap();
+    assert_eq!(page, [(2,), (3,)]);
+
+    let expr = rows(&mut db, "SELECT 1 + 1, 'Hello', TRUE;");
+    assert_eq!(expr.columns(), ["1 + 1", "'Hello'", "TRUE"]);
+    assert_eq!(expr.get(0).unwrap().values(), [Value::Int(2), Value::Text("Hello".into()), Value::Bool(true)]);
+    db.ping().unwrap();
+}
+
+#[test]
+fn update_delete_and_aggregates() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server);
+
+    assert_eq!(message(&mut db, "UPDATE users SET salary = 55000.00 WHERE name = 'Alice';"), "Updated 1 row");
+    let salary: Vec<(f64,)> = db.query_as("SELECT salary FROM users WHERE name = 'Alice';").unwrap();
+    assert_eq!(salary, [(55000.0,)]);
+    assert_eq!(message(&mut db, "DELETE FROM users WHERE name = 'Charlie';"), "Deleted 1 row");
+
+    let count: Vec<(i64,)> = db.query_as("SELECT COUNT(*) FROM users;").unwrap();
+    assert_eq!(count, [(4,)]);
+    let sum: Vec<(f64,)> = db.query_as("SELECT SUM(salary) FROM users;").unwrap();
+    assert!((sum[0].0 - 200000.75).abs() < 0.001);
+    let avg: Vec<(f64,)> = db.query_as("SELECT AVG(age) FROM users;").unwrap();
+    assert!((avg[0].0 - 28.75).abs() < 0.001);
+    let extremes: Vec<(i64, i64)> = db.query_as("SELECT MIN(age), MAX(age) FROM users WHERE active = TRUE;").unwrap();
+    assert_eq!(extremes, [(20, 30)]);
+
+    // SERIAL keeps counting after a delete.
+    db.query("INSERT INTO users (name) VALUES ('Frank');").unwrap();
+    let frank: Vec<(i64,)> = db.query_as("SELECT id FROM users WHERE name = 'Frank';").unwrap();
+    assert_eq!(frank, [(6,)]);
+}
+
+#[test]
+fn foreign_keys_and_constraints() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server);
+
+    db.query("CREATE TABLE orders (
+        order_id SERIAL PRIMARY KEY,
+        user_id INT,
+        amount DOUBLE,
+        FOREIGN KEY (user_id) REFERENCES users(id)
+    );").unwrap();
+    db.query("INSERT INTO orders (user_id, amount) VALUES (1, 100.50);").unwrap();
+    match db.query("INSERT INTO orders (user_id, amount) VALUES (999, 200.00);") {
+        Err(e) => assert!(e.is_constraint_violation(), "{}", e),
+        Ok(msg) => panic!("insert succeeded: {}", msg),
+    }
+    assert_eq!(category(db.query("DELETE FROM users WHERE id = 1;")), ErrorCategory::ConstraintViolation);
+    assert_eq!(category(db.query("DROP TABLE users;")), ErrorCategory::ConstraintViolation);
+    assert_eq!(category(db.query("INSERT INTO users (id, name) VALUES (2, 'Dup');")), ErrorCategory::ConstraintViolation);
+
+    assert_eq!(category(db.query("SELECT * FROM missing;")), ErrorCategory::UnknownTable);
+    assert_eq!(category(db.query("SELECT nope FROM users;")), ErrorCategory::UnknownColumn);
+    assert_eq!(category(db.query("USE nowhere;")), ErrorCategory::UnknownDatabase);
+    assert_eq!(category(db.query("CREATE TABLE users (id INT);")), ErrorCategory::AlreadyExists);
+    assert_eq!(category(db.query("SELEC * FROM users;")), ErrorCategory::Syntax);
+}
+
+#[test]
+fn batch_inserts_and_insert_select() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE IF NOT EXISTS testdb;").unwrap();
+    db.query("USE testdb;").unwrap();
+    db.query("CREATE TABLE batch_test (id SERIAL PRIMARY KEY, name TEXT, score INT);").unwrap();
+    let msg = message(&mut db, "INSERT INTO batch_test (name, score) VALUES ('Player1', 100), ('Player2', 200), ('Player3', 300);");
+    assert_eq!(msg, "Inserted 3 rows");
+
+    db.query("CREATE TABLE archive_test (id SERIAL PRIMARY KEY, name TEXT, score INT);").unwrap();
+    db.query("INSERT INTO archive_test (name, score) SELECT name, score FROM batch_test WHERE score > 150;").unwrap();
+    let archived: Vec<(i64, String, i64)> = db.query_as("SELECT * FROM archive_test ORDER BY id;").unwrap();
+    assert_eq!(archived, [(1, "Player2".to_string(), 200), (2, "Player3".to_string(), 300)]);
+
+    for i in 1..=10 {
+        db.query_with("INSERT INTO batch_test (name, score) VALUES ($1, $2);", &[&format!("O'Brien {}", i), &i]).unwrap();
+    }
+    let names: Vec<(String,)> = db.query_as("SELECT name FROM batch_test WHERE name LIKE 'O''Brien%' AND score >= 10;").unwrap();
+    assert_eq!(names, [("O'Brien 10".to_string(),)]);
+}
+
+#[test]
+fn catalog_statements() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server);
+    db.query("CREATE TABLE perf_test (id SERIAL PRIMARY KEY, data TEXT NOT NULL);").unwrap();
+
+    let tables: Vec<(String,)> = db.query_as("SHOW TABLES;").unwrap();
+    assert_eq!(tables, [("perf_test".to_string(),), ("users".to_string(),)]);
+    let schema = rows(&mut db, "DESCRIBE perf_test;");
+    assert_eq!(schema.columns(), ["column", "type", "nullable", "primary_key", "default", "references"]);
+    let columns: Vec<(String, String, bool, bool)> = schema.iter()
+        .map(|row| (row.try_get(0).unwrap(), row.try_get(1).unwrap(), row.try_get(2).unwrap(), row.try_get(3).unwrap()))
+        .collect();
+    assert_eq!(columns, [
+        ("id".to_string(), "SERIAL".to_string(), false, true),
+        ("data".to_string(), "TEXT".to_string(), false, false),
+    ]);
+
+    db.query("DROP TABLE perf_test;").unwrap();
+    db.query("CREATE USER john PASSWORD 'secret123';").unwrap();
+    db.query("BACKUP 'test_backup';").unwrap();
+
+    let databases: Vec<(String,)> = db.query_as("SHOW DATABASES;").unwrap();
+    assert_eq!(databases, [("system".to_string(),), ("testdb".to_string(),)]);
+    db.query("USE system;").unwrap();
+    db.query("DROP DATABASE testdb;").unwrap();
+    db.query("DROP DATABASE IF EXISTS testdb;").unwrap();
+}
+
+#[test]
+fn transactions_roll_back() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server);
+
+    {
+        let mut tx = db.transaction().unwrap();
+        tx.query("DELETE FROM users WHERE age > 30;").unwrap();
+        {
+            let mut sp = tx.transaction().unwrap();
+            sp.query("DELETE FROM users;").unwrap();
+            // Dropped: rolls back to the savepoint.
+        }
+        let left: Vec<(i64,)> = tx.query_as("SELECT COUNT(*) FROM users;").unwrap();
+        assert_eq!(left, [(3,)]);
+        // Dropped: rolls back the whole transaction.
+    }
+    let count: Vec<(i64,)> = db.query_as("SELECT COUNT(*) FROM users;").unwrap();
+    assert_eq!(count, [(5,)]);
+
+    let mut tx = db.transaction().unwrap();
+    tx.query("UPDATE users SET age = age + 1;").unwrap();
+    tx.commit().unwrap();
+    let ages: Vec<(i64,)> = db.query_as("SELECT SUM(age) FROM users;").unwrap();
+    assert_eq!(ages, [(155,)]);
+}
+
+#[test]
+fn expectations_take_precedence() {
+    let server = MockServer::with_engine();
+    server.expect_query("SHOW DATABASES;").respond_error("Permission denied");
+
+    let mut db = server.connect().unwrap();
+    assert_eq!(category(db.query("SHOW DATABASES;")), ErrorCategory::PermissionDenied);
+    assert!(db.query("SHOW DATABASES;").is_ok());
+    server.verify();
+}
@@ -0,0 +1,213 @@
+// The client against `testing::MockServer`: scripted replies, auth failures and malformed packets.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use maazdb_rs::protocol::Packet;
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ConnectOptions, ConnectionState, MaazDB, MaazDBError, QueryResult, TlsMode, Value};
+
+#[test]
+fn scripted_rows_and_messages() {
+    let server = MockServer::start();
+    server.expect_query("SELECT id, name FROM users;").respond_rows(
+        &["id", "name"],
+        vec![
+            vec![Value::Int(1), Value::Text("Maaz".into())],
+            vec![Value::Int(2), Value::Null],
+        ],
+    );
+    server.expect_query("INSERT INTO users (name) VALUES ('Ali');").respond_message("Inserted 1 row");
+
+    let mut db = server.connect().unwrap();
+    let rs = db.execute("SELECT id,  name FROM users").unwrap().into_rows().unwrap();
+    assert_eq!(rs.columns(), ["id", "name"]);
+    assert_eq!(rs.len(), 2);
+    let names: Vec<(i64, Option<String>)> = rs.decode().unwrap();
+    assert_eq!(names, [(1, Some("Maaz".to_string())), (2, None)]);
+
+    let reply = db.execute("INSERT INTO users (name) VALUES ('Ali');").unwrap();
+    assert!(matches!(reply, QueryResult::CommandComplete(ref m) if m == "Inserted 1 row"));
+
+    server.verify();
+    assert_eq!(server.received(), ["SELECT id, name FROM users", "INSERT INTO users (name) VALUES ('Ali')"]);
+}
+
+#[test]
+fn expectations_are_used_up() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").times(2).respond_rows(&["1"], vec![vec![Value::Int(1)]]);
+
+    let mut db = server.connect().unwrap();
+    db.ping().unwrap();
+    db.ping().unwrap();
+    match db.ping() {
+        Err(MaazDBError::ServerError(e)) => assert!(e.message.contains("no expectation")),
+        other => panic!("expected a server error, got {:?}", other),
+    }
+    assert_eq!(db.state(), ConnectionState::Ready);
+}
+
+#[test]
+#[should_panic(expected = "were not received")]
+fn verify_reports_missing_queries() {
+    let server = MockServer::start();
+    server.expect_query("DROP TABLE users;").respond_message("Table dropped");
+    server.verify();
+}
+
+#[test]
+fn server_errors_are_classified() {
+    let server = MockServer::start();
+    server.expect_query("SELECT * FROM missing;").respond_error("Table 'missing' not found");
+
+    let mut db = server.connect().unwrap();
+    match db.query("SELECT * FROM missing;") {
+        Err(MaazDBError::ServerError(e)) => {
+            assert_eq!(e.category, maazdb_rs::ErrorCategory::UnknownTable);
+            assert_eq!(e.statement, "SELECT * FROM missing;");
+        }
+        other => panic!("expected a server error, got {:?}", other),
+    }
+}
+
+#[test]
+fn auth_failures() {
+    let server = MockServer::start();
+    server.credentials("admin", "secret");
+    assert!(server.connect().is_ok());
+
+    let wrong = ConnectOptions::new("127.0.0.1", server.port(), "admin", "nope")
+        .tls_mode(TlsMode::Pinned(server.fingerprint()));
+    assert!(matches!(MaazDB::connect_with(&wrong), Err(MaazDBError::AuthError(_))));
+
+    server.reject_auth("Account locked");
+    match server.connect() {
+        Err(MaazDBError::AuthError(msg)) => assert_eq!(msg, "Account locked"),
+        other => panic!("expected an auth error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn malformed_replies_break_the_connection() {
+    let server = MockServer::start();
+    // Header promises 100 bytes, but only 3 arrive.
+    server.expect_query("SELECT truncated;").respond_raw(vec![0x03, 0, 0, 0, 100, b'{', b'"', b'h']);
+    server.expect_query("SELECT handshake;").respond_packets(vec![Packet::AuthOk("ok".into())]);
+    server.expect_query("SELECT bytes;").respond_packets(vec![Packet::Msg(vec![0xC3, 0x28].into())]);
+
+    let mut db = server.connect().unwrap();
+    assert!(matches!(db.query("SELECT handshake;"), Err(MaazDBError::ProtocolError(_))));
+    assert!(matches!(db.query("SELECT bytes;"), Err(MaazDBError::Utf8Error(_))));
+    assert_eq!(db.state(), ConnectionState::Ready);
+
+    let opts = server.connect_options().read_timeout(Some(Duration::from_millis(200)));
+    let mut db = MaazDB::connect_with(&opts).unwrap();
+    let reply = db.query("SELECT truncated;");
+    assert!(matches!(reply, Err(MaazDBError::IoError(_))), "{:?}", reply);
+    assert_eq!(db.state(), ConnectionState::Broken);
+}
+
+#[test]
+fn invalid_utf8_in_data_and_error_replies() {
+    let mut data = br#"{"headers":["name"],"data":[[""#.to_vec();
+    data.extend_from_slice(&[0xFF, 0xFE]);
+    data.extend_from_slice(br#""]]}"#);
+    let server = MockServer::start();
+    server.expect_query("SELECT name FROM t;").times(4).respond_packets(vec![Packet::Data(data.clone().into())]);
+    server.expect_query("DROP TABLE t;").respond_packets(vec![Packet::AuthErr(vec![b'n', b'o', 0xC3, 0x28].into())]);
+
+    let mut db = server.connect().unwrap();
+    assert!(matches!(db.query("SELECT name FROM t;"), Err(MaazDBError::Utf8Error(_))));
+    assert!(matches!(db.execute("SELECT name FROM t;"), Err(MaazDBError::Utf8Error(_))));
+    assert!(matches!(db.query_as::<(String,)>("SELECT name FROM t;"), Err(MaazDBError::Utf8Error(_))));
+    // The raw bytes are still available, unchanged.
+    assert_eq!(db.query_bytes(b"SELECT name FROM t;").unwrap(), data);
+
+    // An error message is not replaced lossily either.
+    assert!(matches!(db.query("DROP TABLE t;"), Err(MaazDBError::Utf8Error(_))));
+    assert_eq!(db.state(), ConnectionState::Ready);
+    server.verify();
+}
+
+#[test]
+fn closed_connections_are_noticed() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").close_connection();
+
+    let mut db = server.connect().unwrap();
+    assert!(matches!(db.ping(), Err(MaazDBError::IoError(_))));
+    assert_eq!(db.state(), ConnectionState::Broken);
+    assert_eq!(server.connections(), 1);
+}
+
+#[test]
+fn is_alive_notices_a_dropped_connection() {
+    let server = MockServer::start();
+    let mut db = server.connect().unwrap();
+    assert!(db.is_alive());
+    assert_eq!(db.state(), ConnectionState::Ready);
+
+    server.drop_connections();
+    // Nothing is sent: the closed socket alone marks the connection broken.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while db.is_alive() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!db.is_alive());
+    assert_eq!(db.state(), ConnectionState::Broken);
+    assert!(server.received().is_empty());
+}
+
+#[test]
+fn split_data_frames() {
+    let server = MockServer::start();
+    server.expect_query("SELECT n FROM big;").respond_packets(vec![
+        Packet::DataPart(r#"{"headers":["n"],"da"#.into()),
+        Packet::DataPart(r#"ta":[[1],[2]"#.into()),
+        Packet::Data(r#",[3]]}"#.into()),
+    ]);
+    server.expect_query("SELECT n FROM big;").respond_packets(vec![
+        Packet::DataPart(r#"{"headers":["n"],"data":[[1],"#.into()),
+        Packet::Data(r#"[2]]}"#.into()),
+    ]);
+
+    let mut db = server.connect().unwrap();
+    assert_eq!(db.query_as::<(i64,)>("SELECT n FROM big;").unwrap(), [(1,), (2,), (3,)]);
+    let streamed: Vec<i64> = db.query_iter("SELECT n FROM big;").unwrap()
+        .map(|row| row.unwrap().try_get(0).unwrap())
+        .collect();
+    assert_eq!(streamed, [1, 2]);
+}
+
+#[tokio::test]
+async fn async_client() {
+    let server = MockServer::start();
+    server.expect_query("SELECT name FROM users;").always()
+        .respond_rows(&["name"], vec![vec![Value::Text("Maaz".into())]]);
+
+    let mut db = server.connect_async().await.unwrap();
+    for _ in 0..3 {
+        let names: Vec<(String,)> = db.query_as("SELECT name FROM users;").await.unwrap();
+        assert_eq!(names, [("Maaz".to_string(),)]);
+    }
+    db.close().await;
+    server.verify();
+}
+
+#[tokio::test]
+async fn async_is_alive_notices_a_dropped_connection() {
+    let server = MockServer::start();
+    let mut db = server.connect_async().await.unwrap();
+    assert!(db.is_alive());
+
+    server.drop_connections();
+    // The runtime has to see the socket become readable before `is_alive` can tell.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while db.is_alive() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!db.is_alive());
+    assert_eq!(db.state(), ConnectionState::Broken);
+    assert!(server.received().is_empty());
+}
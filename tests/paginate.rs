@@ -0,0 +1,118 @@
+// `paginate` against `MockServer::with_engine`: OFFSET and keyset paging.
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{MaazDB, MaazDBError, PageMode, ResultSet};
+
+/// A `users` table with ids 1..=`count`; every third user is inactive.
+fn users_db(server: &MockServer, count: i64) -> MaazDB {
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE app;").unwrap();
+    db.query("USE app;").unwrap();
+    db.query("CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT, active BOOL);").unwrap();
+    for i in 1..=count {
+        db.query(&format!("INSERT INTO users (name, active) VALUES ('user{:02}', {});", i, i % 3 != 0)).unwrap();
+    }
+    db
+}
+
+fn ids(page: &ResultSet) -> Vec<i64> {
+    page.rows().iter().map(|row| row.try_get("id").unwrap()).collect()
+}
+
+/// The page statements the server received, without the setup.
+fn page_queries(server: &MockServer) -> Vec<String> {
+    server.received().into_iter().filter(|sql| sql.starts_with("SELECT")).collect()
+}
+
+fn parameter_error(result: Option<Result<ResultSet, MaazDBError>>) -> String {
+    match result {
+        Some(Err(MaazDBError::ParameterError(msg))) => msg,
+        other => panic!("expected a ParameterError, got {:?}", other),
+    }
+}
+
+#[test]
+fn offset_pages() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server, 7);
+
+    let pages = db.paginate("SELECT id, name FROM users ORDER BY name DESC;", 3);
+    assert_eq!(pages.mode(), &PageMode::Offset);
+    let pages: Vec<Vec<i64>> = pages.map(|page| ids(&page.unwrap())).collect();
+    assert_eq!(pages, [vec![7, 6, 5], vec![4, 3, 2], vec![1]]);
+    // A short page is the last one, so no empty page is fetched after it.
+    assert_eq!(page_queries(&server), [
+        "SELECT id, name FROM users ORDER BY name DESC LIMIT 3 OFFSET 0",
+        "SELECT id, name FROM users ORDER BY name DESC LIMIT 3 OFFSET 3",
+        "SELECT id, name FROM users ORDER BY name DESC LIMIT 3 OFFSET 6",
+    ]);
+}
+
+#[test]
+fn keyset_pages_keep_the_where_clause() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server, 10);
+
+    let pages: Vec<Vec<i64>> = db.paginate("SELECT * FROM users WHERE active = TRUE -- live only\n;", 3)
+        .keyset("id")
+        .map(|page| ids(&page.unwrap()))
+        .collect();
+    assert_eq!(pages, [vec![1, 2, 4], vec![5, 7, 8], vec![10]]);
+    assert_eq!(page_queries(&server), [
+        "SELECT * FROM users WHERE active = TRUE ORDER BY id LIMIT 3",
+        "SELECT * FROM users WHERE (active = TRUE) AND id > 4 ORDER BY id LIMIT 3",
+        "SELECT * FROM users WHERE (active = TRUE) AND id > 8 ORDER BY id LIMIT 3",
+    ]);
+}
+
+#[test]
+fn a_full_last_page_is_followed_by_an_empty_one() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server, 4);
+
+    let mut pages = db.paginate("SELECT users.id, name FROM users", 2).keyset("users.id");
+    assert_eq!(ids(&pages.next_page().unwrap().unwrap()), [1, 2]);
+    assert_eq!(ids(&pages.next_page().unwrap().unwrap()), [3, 4]);
+    assert!(pages.next_page().unwrap().is_none());
+    assert!(pages.next_page().unwrap().is_none());
+    assert_eq!(page_queries(&server).last().unwrap(), "SELECT users.id, name FROM users WHERE users.id > 4 ORDER BY users.id LIMIT 2");
+
+    let mut pages = db.paginate("SELECT * FROM users WHERE id > 100", 2);
+    assert!(pages.next().is_none());
+}
+
+#[test]
+fn keyset_misuse_is_rejected() {
+    let server = MockServer::with_engine();
+    let mut db = users_db(&server, 4);
+    let sent = server.received().len();
+
+    let msg = parameter_error(db.paginate("SELECT * FROM users", 2).keyset("id; DROP TABLE users").next());
+    assert!(msg.contains("not a valid keyset column name"), "{}", msg);
+    let msg = parameter_error(db.paginate("SELECT * FROM users ORDER BY name", 2).keyset("id").next());
+    assert!(msg.contains("ORDER BY"), "{}", msg);
+    let msg = parameter_error(db.paginate("SELECT * FROM users LIMIT 5", 2).next());
+    assert!(msg.contains("LIMIT"), "{}", msg);
+    assert_eq!(server.received().len(), sent);
+
+    // The key is read back from each page, so it has to be selected.
+    let mut pages = db.paginate("SELECT name FROM users", 2).keyset("id");
+    let msg = parameter_error(pages.next());
+    assert!(msg.contains("must be in the SELECT list"), "{}", msg);
+    assert!(pages.next().is_none());
+}
+
+#[tokio::test]
+async fn async_keyset_pages() {
+    let server = MockServer::with_engine();
+    users_db(&server, 5);
+
+    let mut db = server.connect_async().await.unwrap();
+    db.query("USE app;").await.unwrap();
+    let mut pages = db.paginate("SELECT id FROM users", 2).keyset("id");
+    let mut all = Vec::new();
+    while let Some(page) = pages.next_page().await.unwrap() {
+        all.push(ids(&page));
+    }
+    assert_eq!(all, [vec![1, 2], vec![3, 4], vec![5]]);
+}
@@ -0,0 +1,79 @@
+// Client-side parameter binding: `bind` and the `ToSql` literals it splices in.
+
+use maazdb_rs::{bind, MaazDBError, ToSql};
+
+fn rejected(result: Result<String, MaazDBError>) -> String {
+    match result {
+        Err(MaazDBError::ParameterError(msg)) => msg,
+        other => panic!("expected a ParameterError, got {:?}", other),
+    }
+}
+
+#[test]
+fn quotes_in_values_are_doubled() {
+    assert_eq!(bind("SELECT $1;", &[&"O'Brien"]).unwrap(), "SELECT 'O''Brien';");
+    assert_eq!(bind("SELECT ?;", &[&"''"]).unwrap(), "SELECT '''''';");
+    assert_eq!(bind("SELECT ?;", &[&"'; DROP TABLE users; --"]).unwrap(), "SELECT '''; DROP TABLE users; --';");
+    // Double quotes only delimit identifiers, so they pass through a string value unchanged.
+    assert_eq!(bind("SELECT ?;", &[&"say \"hi\""]).unwrap(), "SELECT 'say \"hi\"';");
+}
+
+#[test]
+fn placeholders_in_literals_and_identifiers_are_ignored() {
+    assert_eq!(bind("SELECT '?', ? FROM t;", &[&1]).unwrap(), "SELECT '?', 1 FROM t;");
+    assert_eq!(bind("SELECT '$1', $1 FROM t;", &[&1]).unwrap(), "SELECT '$1', 1 FROM t;");
+    assert_eq!(bind("SELECT 'it''s ?', ? FROM t;", &[&2]).unwrap(), "SELECT 'it''s ?', 2 FROM t;");
+    assert_eq!(bind("SELECT \"odd?col\", \"a\"\"$1\" FROM t WHERE x = $1;", &[&3]).unwrap(),
+        "SELECT \"odd?col\", \"a\"\"$1\" FROM t WHERE x = 3;");
+}
+
+#[test]
+fn placeholders_in_comments_are_ignored() {
+    assert_eq!(bind("SELECT ? -- really?\nFROM t;", &[&1]).unwrap(), "SELECT 1 -- really?\nFROM t;");
+    assert_eq!(bind("SELECT /* $2 or ? */ $1;", &[&1]).unwrap(), "SELECT /* $2 or ? */ 1;");
+    assert_eq!(bind("SELECT 1 - ?;", &[&-1]).unwrap(), "SELECT 1 - -1;");
+    // Binding must not create a comment either.
+    assert_eq!(bind("SELECT 1 -? FROM t;", &[&-1]).unwrap(), "SELECT 1 - -1 FROM t;");
+    assert_eq!(bind("SELECT 1 -$1 FROM t;", &[&-1.5]).unwrap(), "SELECT 1 - -1.5 FROM t;");
+}
+
+#[test]
+fn numbered_placeholders_can_repeat() {
+    assert_eq!(bind("SELECT $2, $1, $2;", &[&"a", &true]).unwrap(), "SELECT TRUE, 'a', TRUE;");
+    // `$10` is the tenth parameter, not `$1` followed by `0`.
+    let params: [&dyn ToSql; 10] = [&0, &1, &2, &3, &4, &5, &6, &7, &8, &9];
+    assert_eq!(bind("SELECT $10, $9, $8, $7, $6, $5, $4, $3, $2, $1;", &params).unwrap(), "SELECT 9, 8, 7, 6, 5, 4, 3, 2, 1, 0;");
+}
+
+#[test]
+fn mismatches_are_rejected() {
+    assert!(rejected(bind("SELECT ?, $1;", &[&1])).contains("mix"));
+    assert!(rejected(bind("SELECT $1, ?;", &[&1])).contains("mix"));
+    assert!(rejected(bind("SELECT ?, ?;", &[&1])).contains("more placeholders"));
+    assert!(rejected(bind("SELECT $2;", &[&1])).contains("$2"));
+    assert!(rejected(bind("SELECT $0;", &[&1])).contains("$0"));
+    assert!(rejected(bind("SELECT ?;", &[&1, &2])).contains("Parameter 2 is never used"));
+    assert!(rejected(bind("SELECT $2;", &[&1, &2])).contains("Parameter 1 is never used"));
+    assert!(rejected(bind("SELECT 1;", &[&1])).contains("never used"));
+}
+
+#[test]
+fn unterminated_literal_at_end_swallows_placeholders() {
+    // The rest of the input is one literal, so the placeholder is not bound...
+    assert!(rejected(bind("SELECT 'abc ?", &[&1])).contains("never used"));
+    // ...and without parameters the text is passed on for the server to reject.
+    assert_eq!(bind("SELECT 'abc", &[]).unwrap(), "SELECT 'abc");
+    assert_eq!(bind("SELECT ? /* open", &[&1]).unwrap(), "SELECT 1 /* open");
+}
+
+#[test]
+fn literals() {
+    assert_eq!(0.1f32.to_sql().unwrap(), "0.1");
+    assert_eq!(2f32.to_sql().unwrap(), "2.0");
+    assert_eq!(0.1f64.to_sql().unwrap(), "0.1");
+    assert!(f32::INFINITY.to_sql().is_err());
+    assert!(f64::NAN.to_sql().is_err());
+    assert_eq!(None::<i64>.to_sql().unwrap(), "NULL");
+    assert_eq!(vec![0xde_u8, 0xad].to_sql().unwrap(), "X'DEAD'");
+    assert_eq!(false.to_sql().unwrap(), "FALSE");
+}
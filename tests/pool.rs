@@ -0,0 +1,183 @@
+// `Pool` and `AsyncPool` against `MockServer`: sizing, recycling and session reset.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{AsyncPool, MaazDBError, Pool, PoolOptions};
+
+fn pool(server: &MockServer, options: PoolOptions) -> Pool {
+    Pool::new(server.connect_options(), options).unwrap()
+}
+
+/// Two databases, each with a table only it has.
+fn two_databases() -> MockServer {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+    db.execute_script("
+        CREATE DATABASE a; USE a; CREATE TABLE only_a (id INT);
+        CREATE DATABASE b; USE b; CREATE TABLE only_b (id INT);
+    ", maazdb_rs::OnError::Stop).into_result().unwrap();
+    server
+}
+
+#[test]
+fn connections_return_on_drop_and_are_reused() {
+    let server = MockServer::with_engine();
+    let pool = pool(&server, PoolOptions::new().min_size(1).max_size(2));
+    assert_eq!((pool.size(), pool.idle(), server.connections()), (1, 1, 1));
+
+    let mut a = pool.get().unwrap();
+    let b = pool.get().unwrap();
+    assert_eq!((pool.size(), pool.idle()), (2, 0));
+    a.ping().unwrap();
+    drop(a);
+    drop(b);
+    assert_eq!((pool.size(), pool.idle()), (2, 2));
+
+    for _ in 0..3 {
+        pool.get().unwrap().ping().unwrap();
+    }
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn checkout_waits_then_times_out_at_max_size() {
+    let server = MockServer::with_engine();
+    let pool = pool(&server, PoolOptions::new().max_size(1).checkout_timeout(Duration::from_millis(100)));
+
+    let held = pool.get().unwrap();
+    let start = Instant::now();
+    assert!(matches!(pool.get(), Err(MaazDBError::PoolTimeout)));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // A waiting checkout gets the connection as soon as it is returned.
+    let pool2 = pool.clone();
+    let waiter = thread::spawn(move || pool2.get().map(|mut c| c.ping()));
+    thread::sleep(Duration::from_millis(30));
+    drop(held);
+    waiter.join().unwrap().unwrap().unwrap();
+    assert_eq!((pool.size(), server.connections()), (1, 1));
+}
+
+#[test]
+fn broken_connections_are_discarded() {
+    let server = MockServer::with_engine();
+    server.expect_query("SELECT boom;").close_connection();
+    let pool = pool(&server, PoolOptions::new().max_size(1));
+
+    let mut conn = pool.get().unwrap();
+    assert!(conn.query("SELECT boom;").is_err());
+    drop(conn);
+    assert_eq!((pool.size(), pool.idle()), (0, 0));
+
+    pool.get().unwrap().ping().unwrap();
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn connections_left_in_a_transaction_are_discarded() {
+    let server = MockServer::with_engine();
+    let pool = pool(&server, PoolOptions::new().max_size(1));
+
+    let mut conn = pool.get().unwrap();
+    conn.query("BEGIN;").unwrap();
+    drop(conn);
+    assert_eq!((pool.size(), pool.idle()), (0, 0));
+
+    // A fresh connection: the engine would refuse a second `BEGIN` on the old one.
+    let mut conn = pool.get().unwrap();
+    conn.query("BEGIN;").unwrap();
+    conn.query("COMMIT;").unwrap();
+    drop(conn);
+    assert_eq!((pool.idle(), server.connections()), (1, 2));
+}
+
+#[test]
+fn idle_connections_expire() {
+    let server = MockServer::with_engine();
+    let pool = pool(&server, PoolOptions::new().idle_timeout(Some(Duration::from_millis(50))));
+
+    drop(pool.get().unwrap());
+    pool.get().unwrap();
+    assert_eq!(server.connections(), 1);
+
+    thread::sleep(Duration::from_millis(80));
+    pool.get().unwrap();
+    assert_eq!((server.connections(), pool.size()), (2, 1));
+}
+
+#[test]
+fn checkout_restores_the_configured_database() {
+    let server = two_databases();
+    let pool = Pool::new(server.connect_options().database("a").unwrap(), PoolOptions::new().max_size(1)).unwrap();
+
+    let mut conn = pool.get().unwrap();
+    conn.query("USE b;").unwrap();
+    conn.query("SELECT * FROM only_b;").unwrap();
+    drop(conn);
+
+    let mut conn = pool.get().unwrap();
+    conn.query("SELECT * FROM only_a;").unwrap();
+    drop(conn);
+    assert_eq!(server.connections(), 2, "the setup connection and one pooled connection");
+
+    // Without a configured database there is nothing to go back to.
+    let pool = Pool::new(server.connect_options(), PoolOptions::new().max_size(1)).unwrap();
+    pool.get().unwrap().query("USE b;").unwrap();
+    assert!(pool.get().unwrap().query("SELECT * FROM only_b;").is_err());
+    assert_eq!(server.connections(), 4);
+}
+
+#[tokio::test]
+async fn async_pool_recycles_and_limits() {
+    let server = MockServer::with_engine();
+    server.expect_query("SELECT boom;").close_connection();
+    let pool = AsyncPool::new(server.connect_options(), PoolOptions::new().max_size(1).checkout_timeout(Duration::from_millis(50)))
+        .await
+        .unwrap();
+
+    let mut conn = pool.get().await.unwrap();
+    assert!(matches!(pool.get().await, Err(MaazDBError::PoolTimeout)));
+    conn.ping().await.unwrap();
+    drop(conn);
+    assert_eq!((pool.size(), pool.idle()), (1, 1));
+    pool.get().await.unwrap().ping().await.unwrap();
+    assert_eq!(server.connections(), 1);
+
+    let mut conn = pool.get().await.unwrap();
+    assert!(conn.query("SELECT boom;").await.is_err());
+    drop(conn);
+    assert_eq!((pool.size(), pool.idle()), (0, 0));
+    pool.get().await.unwrap().ping().await.unwrap();
+    assert_eq!(server.connections(), 2);
+}
+
+#[tokio::test]
+async fn async_connections_left_in_a_transaction_are_discarded() {
+    let server = MockServer::with_engine();
+    let pool = AsyncPool::new(server.connect_options(), PoolOptions::new().max_size(1)).await.unwrap();
+
+    let mut conn = pool.get().await.unwrap();
+    conn.query("START TRANSACTION;").await.unwrap();
+    drop(conn);
+    assert_eq!((pool.size(), pool.idle()), (0, 0));
+
+    let mut conn = pool.get().await.unwrap();
+    conn.query("BEGIN;").await.unwrap();
+    conn.query("ROLLBACK;").await.unwrap();
+    drop(conn);
+    assert_eq!((pool.idle(), server.connections()), (1, 2));
+}
+
+#[tokio::test]
+async fn async_checkout_restores_the_configured_database() {
+    let server = two_databases();
+    let pool = AsyncPool::new(server.connect_options().database("a").unwrap(), PoolOptions::new().max_size(1))
+        .await
+        .unwrap();
+
+    pool.get().await.unwrap().query("USE b;").await.unwrap();
+    pool.get().await.unwrap().query("SELECT * FROM only_a;").await.unwrap();
+    assert_eq!(server.connections(), 2);
+}
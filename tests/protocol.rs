@@ -0,0 +1,99 @@
+// `PacketCodec` framing: round trips, partial frames and length limits.
+
+use std::io::{Cursor, ErrorKind};
+
+use bytes::BytesMut;
+use maazdb_rs::protocol::{Packet, PacketCodec, HEADER_LEN, PACKET_DATA, PACKET_QUERY};
+use tokio_util::codec::{Decoder, Encoder};
+
+fn packets() -> Vec<Packet> {
+    vec![
+        Packet::handshake("admin", "secret", Some("report")),
+        Packet::AuthOk("Welcome".into()),
+        Packet::query("SELECT * FROM users;"),
+        Packet::DataPart(r#"{"headers":["id"],"#.into()),
+        Packet::Data(r#""data":[[1]]}"#.into()),
+        Packet::Msg("".into()),
+        Packet::Other(0x42, vec![0, 0xFF, 7].into()),
+    ]
+}
+
+#[test]
+fn frames_round_trip() {
+    let mut codec = PacketCodec::new();
+    let mut wire = Vec::new();
+    for packet in packets() {
+        codec.write_to(&mut wire, &packet).unwrap();
+    }
+
+    // The codec as an `Encoder` writes exactly the same bytes.
+    let mut encoded = BytesMut::new();
+    for packet in packets() {
+        codec.encode(packet, &mut encoded).unwrap();
+    }
+    assert_eq!(&encoded[..], &wire[..]);
+    assert_eq!(&wire[..HEADER_LEN], [0x10, 0, 0, 0, 41]);
+
+    let mut reader = Cursor::new(&wire);
+    let read: Vec<Packet> = packets().iter().map(|_| codec.read_from(&mut reader).unwrap()).collect();
+    assert_eq!(read, packets());
+    assert_eq!(codec.read_from(&mut reader).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    let mut decoded = Vec::new();
+    while let Some(packet) = codec.decode(&mut encoded).unwrap() {
+        decoded.push(packet);
+    }
+    assert_eq!(decoded, packets());
+    assert!(encoded.is_empty());
+    assert_eq!(decoded[0].handshake_fields(), Some(("admin", "secret", "maazdb-rust-driver-v1", Some("report"))));
+}
+
+#[test]
+fn partial_frames_wait_for_more() {
+    let mut codec = PacketCodec::new();
+    let mut wire = BytesMut::new();
+    codec.encode(Packet::query("SELECT 1;"), &mut wire).unwrap();
+    codec.encode(Packet::new(PACKET_DATA, "{}"), &mut wire).unwrap();
+
+    // Fed one byte at a time, nothing comes out until a frame is complete.
+    let mut buf = BytesMut::new();
+    let mut decoded = Vec::new();
+    for (i, byte) in wire.iter().enumerate() {
+        buf.extend_from_slice(&[*byte]);
+        if let Some(packet) = codec.decode(&mut buf).unwrap() {
+            decoded.push((i, packet));
+        }
+    }
+    assert_eq!(decoded, [
+        (HEADER_LEN + 8, Packet::query("SELECT 1;")),
+        (wire.len() - 1, Packet::Data("{}".into())),
+    ]);
+
+    // A stream that ends inside a frame is an error for the blocking reader.
+    let mut reader = Cursor::new(&wire[..HEADER_LEN + 4]);
+    assert_eq!(codec.read_from(&mut reader).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn oversize_lengths_are_rejected() {
+    let mut codec = PacketCodec::new().max_packet_size(8);
+
+    // The header alone is enough to refuse a frame, before any of the payload arrives.
+    let mut header = BytesMut::from(&[PACKET_QUERY, 0, 0, 0, 9][..]);
+    assert_eq!(codec.decode(&mut header).unwrap_err().kind(), ErrorKind::InvalidData);
+    let mut reader = Cursor::new([PACKET_QUERY, 0xFF, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(codec.read_from(&mut reader).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    let big = Packet::query("SELECT 12345;");
+    let mut wire = Vec::new();
+    assert_eq!(codec.write_to(&mut wire, &big).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert!(wire.is_empty());
+    let mut encoded = BytesMut::new();
+    assert_eq!(codec.encode(big, &mut encoded).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert!(encoded.is_empty());
+
+    // Exactly at the limit is fine.
+    let fits = Packet::query("SELECT 1");
+    codec.write_to(&mut wire, &fits).unwrap();
+    assert_eq!(codec.read_from(&mut Cursor::new(&wire)).unwrap(), fits);
+}
@@ -0,0 +1,93 @@
+// The query builder: the SQL it emits and that the engine accepts it.
+
+use maazdb_rs::query::{col, Asc, Delete, Desc, Insert, Select, Update};
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{MaazDB, MaazDBError};
+
+fn people() -> (MockServer, MaazDB) {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE q;").unwrap();
+    db.query("USE q;").unwrap();
+    db.query("CREATE TABLE users (id SERIAL PRIMARY KEY, name TEXT, age INT, active BOOL);").unwrap();
+    let insert = Insert::into("users")
+        .value("name", "Ada").value("age", 36).value("active", true).row()
+        .value("name", "Bob").value("age", 25).value("active", true).row()
+        .value("name", "Cy").value("age", 41).value("active", false).row()
+        .value("name", "O'Neil").value("age", 30).value("active", true).row()
+        .value("name", None::<String>).value("age", 52).value("active", true)
+        .build()
+        .unwrap();
+    assert!(insert.starts_with("INSERT INTO users (name, age, active) VALUES ('Ada', 36, TRUE), ('Bob', 25, TRUE)"));
+    db.query(&insert).unwrap();
+    (server, db)
+}
+
+fn names(db: &mut MaazDB, select: Select) -> Vec<Option<String>> {
+    let sql = select.columns(["name"]).build().unwrap();
+    db.query_as::<(Option<String>,)>(&sql).unwrap().into_iter().map(|(n,)| n).collect()
+}
+
+#[test]
+fn select_from_the_request() {
+    let (_server, mut db) = people();
+    let select = Select::from("users")
+        .columns(["name", "age"])
+        .filter(col("age").gt(25).and(col("active").eq(true)))
+        .order_by("age", Desc)
+        .limit(2)
+        .offset(1);
+    let sql = select.build().unwrap();
+    assert_eq!(sql, "SELECT name, age FROM users WHERE age > 25 AND active = TRUE ORDER BY age DESC LIMIT 2 OFFSET 1;");
+    let rows: Vec<(Option<String>, i64)> = db.query_as(&sql).unwrap();
+    assert_eq!(rows, [(Some("Ada".into()), 36), (Some("O'Neil".into()), 30)]);
+}
+
+#[test]
+fn conditions_keep_their_grouping() {
+    let (_server, mut db) = people();
+    let either = col("age").gt(50).or(col("name").eq("Bob"));
+    let sql = Select::from("users").filter(either.and(col("active").eq(true))).build().unwrap();
+    assert_eq!(sql, "SELECT * FROM users WHERE (age > 50 OR name = 'Bob') AND active = TRUE;");
+
+    let all = |c| Select::from("users").filter(c).order_by("id", Asc);
+    assert_eq!(names(&mut db, all(col("age").gt(50).or(col("name").eq("Bob")).and(col("active").eq(true)))), [Some("Bob".into()), None]);
+    assert_eq!(names(&mut db, all(col("active").eq(true).and(col("age").gt(40).or(col("name").eq("Bob"))))), [Some("Bob".into()), None]);
+    // AND binds tighter than OR, so no parentheses are needed here.
+    let sql = all(col("age").gt(40).and(col("active").eq(false)).or(col("name").eq("Ada"))).build().unwrap();
+    assert_eq!(sql, "SELECT * FROM users WHERE age > 40 AND active = FALSE OR name = 'Ada' ORDER BY id;");
+    assert_eq!(names(&mut db, all(col("age").gt(40).and(col("active").eq(false)).or(col("name").eq("Ada")))), [Some("Ada".into()), Some("Cy".into())]);
+    // Two filters mean both.
+    assert_eq!(names(&mut db, all(col("age").gt(30)).filter(col("active").eq(true))), [Some("Ada".into()), None]);
+}
+
+#[test]
+fn update_and_delete() {
+    let (_server, mut db) = people();
+    let sql = Update::table("users").set("active", false).set("name", "Bobby").filter(col("name").eq("Bob")).build().unwrap();
+    assert_eq!(sql, "UPDATE users SET active = FALSE, name = 'Bobby' WHERE name = 'Bob';");
+    db.query(&sql).unwrap();
+    assert_eq!(names(&mut db, Select::from("users").filter(col("active").eq(false)).order_by("id", Asc)), [Some("Bobby".into()), Some("Cy".into())]);
+
+    let sql = Delete::from("users").filter(col("active").eq(false)).build().unwrap();
+    assert_eq!(sql, "DELETE FROM users WHERE active = FALSE;");
+    db.query(&sql).unwrap();
+    assert_eq!(names(&mut db, Select::from("users")).len(), 3);
+    assert_eq!(Delete::from("users").build().unwrap(), "DELETE FROM users;");
+}
+
+#[test]
+fn rejects_what_it_cannot_express_safely() {
+    let invalid = |r: Result<String, MaazDBError>| matches!(r, Err(MaazDBError::ParameterError(_)));
+    assert!(invalid(Select::from("users; DROP TABLE users").build()));
+    assert!(invalid(Select::from("users").columns(["name, password"]).build()));
+    assert!(invalid(Select::from("users").order_by("age; --", Desc).build()));
+    assert!(invalid(Select::from("users").filter(col("1=1 OR x").eq(1)).build()));
+    assert!(invalid(Select::from("users").filter(col("score").gt(f64::NAN)).build()));
+    assert!(invalid(Insert::into("users").build()));
+    assert!(invalid(Insert::into("users").value("name", "a").value("age", 1).row().value("age", 2).build()));
+    assert!(invalid(Insert::into("users").value("name", "a").value("age", 1).row().value("name", "b").build()));
+    assert!(invalid(Update::table("users").build()));
+    assert!(invalid(Update::table("users").set("bad col", 1).build()));
+    assert!(Select::from("q.users").build().is_ok());
+}
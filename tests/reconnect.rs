@@ -0,0 +1,159 @@
+// Reconnecting under a `ReconnectPolicy`: retries, the restored database, and transactions.
+
+use std::time::Duration;
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{AsyncMaazDB, ConnectionState, MaazDB, MaazDBError, ReconnectPolicy, Value};
+
+fn policy(max_retries: u32) -> ReconnectPolicy {
+    ReconnectPolicy::new().max_retries(max_retries).initial_backoff(Duration::from_millis(1))
+}
+
+fn connect(server: &MockServer, max_retries: u32) -> MaazDB {
+    MaazDB::connect_with(&server.connect_options().reconnect(policy(max_retries))).unwrap()
+}
+
+async fn connect_async(server: &MockServer, max_retries: u32) -> AsyncMaazDB {
+    AsyncMaazDB::connect_with(&server.connect_options().reconnect(policy(max_retries))).await.unwrap()
+}
+
+/// `USE shop` succeeds, then the connection drops on the first `SELECT n` and on the first
+/// `USE shop` after it, so the query only succeeds on the third connection.
+fn flaky_server() -> MockServer {
+    let server = MockServer::start();
+    server.expect_query("USE shop;").respond_message("Database changed");
+    server.expect_query("SELECT n FROM t;").close_connection();
+    server.expect_query("USE shop;").close_connection();
+    server.expect_query("USE shop;").always().respond_message("Database changed");
+    server.expect_query("SELECT n FROM t;").always().respond_rows(&["n"], vec![vec![Value::Int(1)]]);
+    server
+}
+
+const FLAKY_RECEIVED: [&str; 5] = ["USE shop", "SELECT n FROM t", "USE shop", "USE shop", "SELECT n FROM t"];
+
+#[test]
+fn reads_are_retried_on_the_same_database() {
+    let server = flaky_server();
+    let mut db = connect(&server, 3);
+    db.query("USE shop;").unwrap();
+
+    // A failed reconnect attempt must not forget the database for the next one.
+    let rows: Vec<(i64,)> = db.query_as("SELECT n FROM t;").unwrap();
+    assert_eq!(rows, [(1,)]);
+    assert_eq!(server.received(), FLAKY_RECEIVED);
+    assert_eq!(server.connections(), 3);
+}
+
+#[test]
+fn retries_stop_at_the_limit() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").always().close_connection();
+    let mut db = connect(&server, 2);
+
+    assert!(matches!(db.query("SELECT 1;"), Err(MaazDBError::IoError(_))));
+    // The first attempt and two retries, each on a fresh connection after the first.
+    assert_eq!(server.received(), ["SELECT 1"; 3]);
+    assert_eq!(server.connections(), 3);
+
+    // Writes are never re-sent; the next statement reconnects first.
+    server.expect_query("INSERT INTO t VALUES (1);").close_connection();
+    server.expect_query("INSERT INTO t VALUES (1);").respond_message("Inserted 1 row");
+    assert!(matches!(db.query("INSERT INTO t VALUES (1);"), Err(MaazDBError::IoError(_))));
+    assert_eq!(server.received().len(), 4);
+    assert_eq!(db.query("INSERT INTO t VALUES (1);").unwrap(), "Inserted 1 row");
+    server.verify();
+}
+
+#[test]
+fn transactions_are_not_reconnected() {
+    let server = MockServer::with_engine();
+    server.expect_query("SELECT 1;").close_connection();
+    let mut db = connect(&server, 3);
+
+    let mut tx = db.transaction().unwrap();
+    assert!(matches!(tx.query("SELECT 1;"), Err(MaazDBError::IoError(_))));
+    assert_eq!(tx.state(), ConnectionState::Broken);
+    drop(tx);
+    assert_eq!(server.connections(), 1);
+
+    // Once the guard is gone, the connection recovers.
+    db.ping().unwrap();
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn raw_begin_is_not_reconnected() {
+    let server = MockServer::with_engine();
+    server.expect_query("SELECT 1;").close_connection();
+    let mut db = connect(&server, 3);
+
+    db.query("BEGIN;").unwrap();
+    assert!(matches!(db.reconnect(), Err(MaazDBError::ProtocolError(_))));
+    assert!(matches!(db.close(), Err(MaazDBError::ProtocolError(_))));
+    assert!(matches!(db.query("SELECT 1;"), Err(MaazDBError::IoError(_))));
+    assert!(db.query("SELECT 2;").is_err());
+    assert_eq!(db.state(), ConnectionState::Broken);
+    assert_eq!(server.connections(), 1);
+
+    // The broken connection took the transaction with it, so reconnecting is allowed.
+    db.reconnect().unwrap();
+    db.query("SELECT 2;").unwrap();
+    assert_eq!(server.connections(), 2);
+
+    // Ending a transaction lets the policy apply again.
+    server.expect_query("SELECT 3;").close_connection();
+    db.query("START TRANSACTION;").unwrap();
+    db.query("COMMIT;").unwrap();
+    assert_eq!(db.query_as::<(i64,)>("SELECT 3;").unwrap(), [(3,)]);
+    assert_eq!(server.connections(), 3);
+}
+
+#[tokio::test]
+async fn async_reads_are_retried_on_the_same_database() {
+    let server = flaky_server();
+    let mut db = connect_async(&server, 3).await;
+    db.query("USE shop;").await.unwrap();
+
+    let rows: Vec<(i64,)> = db.query_as("SELECT n FROM t;").await.unwrap();
+    assert_eq!(rows, [(1,)]);
+    assert_eq!(server.received(), FLAKY_RECEIVED);
+    assert_eq!(server.connections(), 3);
+}
+
+#[tokio::test]
+async fn async_retries_stop_at_the_limit() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").always().close_connection();
+    let mut db = connect_async(&server, 1).await;
+
+    assert!(matches!(db.query("SELECT 1;").await, Err(MaazDBError::IoError(_))));
+    assert_eq!(server.received(), ["SELECT 1"; 2]);
+    assert_eq!(server.connections(), 2);
+}
+
+#[tokio::test]
+async fn async_transactions_are_not_reconnected() {
+    let server = MockServer::with_engine();
+    server.expect_query("SELECT 1;").close_connection();
+    let mut db = connect_async(&server, 3).await;
+
+    db.query("BEGIN;").await.unwrap();
+    assert!(matches!(db.reconnect().await, Err(MaazDBError::ProtocolError(_))));
+    assert!(matches!(db.query("SELECT 1;").await, Err(MaazDBError::IoError(_))));
+    assert!(db.query("SELECT 2;").await.is_err());
+    assert!(db.query_stream("SELECT 2;").await.is_err());
+    assert_eq!(db.state(), ConnectionState::Broken);
+    assert_eq!(server.connections(), 1);
+
+    // Once the connection is broken, an explicit reconnect gives up the transaction.
+    db.reconnect().await.unwrap();
+    db.query("SELECT 2;").await.unwrap();
+    assert_eq!(server.connections(), 2);
+
+    // Ending a transaction lets the policy apply again.
+    server.expect_query("SELECT 3;").close_connection();
+    db.query("START TRANSACTION;").await.unwrap();
+    db.query("ROLLBACK;").await.unwrap();
+    assert_eq!(db.query("SELECT 3;").await.unwrap(), r#"{"data":[[3]],"headers":["3"]}"#);
+    assert_eq!(server.connections(), 3);
+}
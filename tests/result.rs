@@ -0,0 +1,70 @@
+// `ResultSet` parsing and how `execute` tells rows from messages.
+
+use maazdb_rs::protocol::Packet;
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{MaazDBError, QueryResult, ResultSet, Value};
+
+fn protocol_error(payload: &str) -> String {
+    match ResultSet::from_json(payload) {
+        Err(MaazDBError::ProtocolError(msg)) => msg,
+        other => panic!("{}: expected a ProtocolError, got {:?}", payload, other),
+    }
+}
+
+#[test]
+fn rows_by_index_and_name() {
+    let rs = ResultSet::from_json(r#"{"headers":["id","Name","score"],"data":[[1,"Ada",9.5],[2,null,-3]]}"#).unwrap();
+    assert_eq!(rs.columns(), ["id", "Name", "score"]);
+    assert_eq!((rs.len(), rs.is_empty()), (2, false));
+
+    let row = rs.get(0).unwrap();
+    assert_eq!(row.len(), 3);
+    assert_eq!(row.get(0), Some(&Value::Int(1)));
+    assert_eq!(row.get_named("name"), Some(&Value::Text("Ada".into())));
+    assert_eq!(row.get_named("SCORE"), Some(&Value::Double(9.5)));
+    assert_eq!(row.get_named("missing"), None);
+    assert_eq!(rs.get(1).unwrap().values(), [Value::Int(2), Value::Null, Value::Int(-3)]);
+    assert!(rs.get(2).is_none());
+
+    let empty = ResultSet::from_json(r#"{"headers":["id"],"data":[]}"#).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.columns(), ["id"]);
+}
+
+#[test]
+fn malformed_result_sets() {
+    assert_eq!(protocol_error(r#"{"headers":["a","b"],"data":[[1,2],[3]]}"#), "Result row has 1 values but there are 2 columns");
+    assert_eq!(protocol_error(r#"{"headers":["a"],"data":[[1,2]]}"#), "Result row has 2 values but there are 1 columns");
+    assert_eq!(protocol_error(r#"{"headers":["a"],"data":[1]}"#), "Result row is not an array");
+    assert_eq!(protocol_error(r#"{"data":[[1]]}"#), "Result set is missing 'headers'");
+    assert_eq!(protocol_error(r#"{"headers":["a"]}"#), "Result set is missing 'data'");
+    assert_eq!(protocol_error(r#"{"headers":"a","data":[]}"#), "Result set is missing 'headers'");
+    assert!(protocol_error("Query OK").starts_with("Invalid result set"));
+}
+
+#[test]
+fn execute_distinguishes_rows_and_messages() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").respond_packets(vec![Packet::Data(r#"{"headers":["1"],"data":[[1]]}"#.into())]);
+    server.expect_query("INSERT INTO t VALUES (1);").respond_message("Inserted 1 row");
+    server.expect_query("BACKUP 'b';").respond_packets(vec![Packet::Data("Backup written to b".into())]);
+    server.expect_query("SELECT bad;").respond_packets(vec![Packet::Data(r#"{"headers":["a"],"data":[[1,2]]}"#.into())]);
+
+    let mut db = server.connect().unwrap();
+    let rows = db.execute("SELECT 1;").unwrap();
+    assert_eq!(rows.rows().map(ResultSet::len), Some(1));
+    assert_eq!(rows.message(), None);
+
+    let msg = db.execute("INSERT INTO t VALUES (1);").unwrap();
+    assert_eq!(msg.message(), Some("Inserted 1 row"));
+    assert!(msg.into_rows().is_none());
+
+    // Text that is not JSON at all is a message, even in a data frame.
+    match db.execute("BACKUP 'b';").unwrap() {
+        QueryResult::CommandComplete(msg) => assert_eq!(msg, "Backup written to b"),
+        other => panic!("expected a message, got {:?}", other),
+    }
+    // JSON that is not a valid table is not.
+    assert!(matches!(db.execute("SELECT bad;"), Err(MaazDBError::ProtocolError(_))));
+    server.verify();
+}
@@ -0,0 +1,102 @@
+// Introspection: `list_databases`, `list_tables` and `describe`.
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ColumnInfo, DataType, ForeignKey, MaazDBError, Value};
+
+#[test]
+fn describes_engine_tables() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE shop;").unwrap();
+    db.query("USE shop;").unwrap();
+    db.query("CREATE TABLE users (id SERIAL PRIMARY KEY, email TEXT NOT NULL UNIQUE, active BOOL DEFAULT TRUE);").unwrap();
+    db.query("CREATE TABLE orders (
+        id SERIAL PRIMARY KEY,
+        user_id INT,
+        total DOUBLE,
+        placed TIMESTAMP,
+        token UUID,
+        receipt BLOB,
+        FOREIGN KEY (user_id) REFERENCES users(id)
+    );").unwrap();
+
+    assert_eq!(db.list_databases().unwrap(), ["shop", "system"]);
+    assert_eq!(db.list_tables().unwrap(), ["orders", "users"]);
+
+    let users = db.describe("users").unwrap();
+    assert_eq!(users.name, "users");
+    assert_eq!(users.columns, [
+        ColumnInfo { name: "id".into(), data_type: DataType::Serial, nullable: false, primary_key: true, default: None, references: None },
+        ColumnInfo { name: "email".into(), data_type: DataType::Text, nullable: false, primary_key: false, default: None, references: None },
+        ColumnInfo { name: "active".into(), data_type: DataType::Bool, nullable: true, primary_key: false, default: Some("TRUE".into()), references: None },
+    ]);
+    assert_eq!(users.primary_key().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["id"]);
+
+    let orders = db.describe("orders").unwrap();
+    let types: Vec<&DataType> = orders.columns.iter().map(|c| &c.data_type).collect();
+    assert_eq!(types, [&DataType::Serial, &DataType::Int, &DataType::Double, &DataType::Timestamp, &DataType::Uuid, &DataType::Blob]);
+    let fk = orders.column("USER_ID").unwrap().references.as_ref().unwrap();
+    assert_eq!(fk, &ForeignKey { table: "users".into(), column: "id".into() });
+    assert_eq!(fk.to_string(), "users(id)");
+
+    // Same input, same schema: schemas can be diffed.
+    assert_eq!(db.describe("orders").unwrap(), orders);
+    assert!(matches!(db.describe("missing"), Err(MaazDBError::ServerError(_))));
+}
+
+#[test]
+fn reads_other_describe_layouts() {
+    let server = MockServer::start();
+    server.expect_query("DESCRIBE items;").respond_rows(
+        &["Field", "Type", "Null", "Key", "Default"],
+        vec![
+            vec![Value::Text("id".into()), Value::Text("integer".into()), Value::Text("NO".into()), Value::Text("PRI".into()), Value::Null],
+            vec![Value::Text("tags".into()), Value::Text("JSON".into()), Value::Text("YES".into()), Value::Text("".into()), Value::Text("'[]'".into())],
+        ],
+    );
+    server.expect_query("DESCRIBE bare;").respond_rows(
+        &["name", "type", "references"],
+        vec![vec![Value::Text("owner".into()), Value::Text("int".into()), Value::Text("people.id".into())]],
+    );
+
+    let mut db = server.connect().unwrap();
+    let items = db.describe("items").unwrap();
+    assert_eq!(items.columns[0].data_type, DataType::Int);
+    assert!(items.columns[0].primary_key && !items.columns[0].nullable);
+    assert_eq!(items.columns[1].data_type, DataType::Other("JSON".into()));
+    assert_eq!(items.columns[1].data_type.to_string(), "JSON");
+    assert!(items.columns[1].nullable && !items.columns[1].primary_key);
+    assert_eq!(items.columns[1].default.as_deref(), Some("'[]'"));
+
+    let bare = db.describe("bare").unwrap();
+    assert!(bare.columns[0].nullable);
+    assert_eq!(bare.columns[0].references, Some(ForeignKey { table: "people".into(), column: "id".into() }));
+    server.verify();
+}
+
+#[test]
+fn rejects_unsafe_table_names() {
+    let server = MockServer::start();
+    let mut db = server.connect().unwrap();
+    for name in ["", "users; DROP TABLE users", "1users", "a b", "db..t"] {
+        assert!(matches!(db.describe(name), Err(MaazDBError::ParameterError(_))), "{:?}", name);
+    }
+    assert!(server.received().is_empty());
+}
+
+#[tokio::test]
+async fn async_introspection() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect_async().await.unwrap();
+    db.query("CREATE DATABASE a;").await.unwrap();
+    db.query("USE a;").await.unwrap();
+    db.query("CREATE TABLE t (id INT NOT NULL);").await.unwrap();
+
+    assert_eq!(db.list_databases().await.unwrap(), ["a", "system"]);
+    assert_eq!(db.list_tables().await.unwrap(), ["t"]);
+    let t = db.describe("a.t").await.unwrap();
+    assert_eq!(t.name, "a.t");
+    assert_eq!(t.columns.len(), 1);
+    assert!(!t.columns[0].nullable);
+    db.close().await;
+}
@@ -0,0 +1,111 @@
+// Multi-statement scripts: splitting and `execute_script` / `execute_file` against the engine.
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{split_statements, ErrorCategory, OnError, QueryResult};
+
+fn sql(script: &str) -> Vec<String> {
+    split_statements(script).into_iter().map(|s| s.sql).collect()
+}
+
+#[test]
+fn splits_outside_quotes_and_comments() {
+    assert_eq!(sql("SELECT 1; SELECT 2;SELECT 3"), ["SELECT 1", "SELECT 2", "SELECT 3"]);
+    assert_eq!(
+        sql("INSERT INTO t VALUES ('a;b'); INSERT INTO t VALUES ('it''s; here');"),
+        ["INSERT INTO t VALUES ('a;b')", "INSERT INTO t VALUES ('it''s; here')"],
+    );
+    assert_eq!(sql(r#"SELECT "odd;name" FROM t;"#), [r#"SELECT "odd;name" FROM t"#]);
+    assert_eq!(
+        sql("-- setup; not a statement\nSELECT 1; -- trailing; comment\n/* block;\n comment */ SELECT/**/2;"),
+        ["SELECT 1", "SELECT 2"],
+    );
+    assert_eq!(sql("SELECT '-- not a comment', '/* nor this */';"), ["SELECT '-- not a comment', '/* nor this */'"]);
+    assert!(sql(" ; ;\n-- nothing\n/* here */").is_empty());
+}
+
+#[test]
+fn statements_know_their_line() {
+    let script = "CREATE TABLE a (id INT);\n\n-- second\nINSERT INTO a VALUES (1);\nSELECT 'x\ny'\n  FROM a; /*\n*/\nSELECT 1;";
+    let lines: Vec<usize> = split_statements(script).iter().map(|s| s.line).collect();
+    assert_eq!(lines, [1, 4, 5, 9]);
+}
+
+const SCRIPT: &str = "
+CREATE DATABASE shop;
+USE shop;
+CREATE TABLE notes (id SERIAL PRIMARY KEY, body TEXT);
+INSERT INTO notes (body) VALUES ('first; with a semicolon');
+INSERT INTO missing (body) VALUES ('nope');
+INSERT INTO notes (body) VALUES ('second');
+SELECT body FROM notes ORDER BY id;
+";
+
+#[test]
+fn stop_on_error() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+
+    let report = db.execute_script(SCRIPT, OnError::Stop);
+    assert!(!report.is_success());
+    assert_eq!(report.outcomes.len(), 5);
+    assert_eq!(report.skipped, 2);
+    let errors: Vec<_> = report.errors().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0.line, 6);
+    assert_eq!(errors[0].1.as_server_error().unwrap().category, ErrorCategory::UnknownTable);
+    assert!(report.into_result().is_err());
+
+    let bodies: Vec<(String,)> = db.query_as("SELECT body FROM notes;").unwrap();
+    assert_eq!(bodies, [("first; with a semicolon".to_string(),)]);
+}
+
+#[test]
+fn continue_on_error() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+
+    let report = db.execute_script(SCRIPT, OnError::Continue);
+    assert_eq!(report.outcomes.len(), 7);
+    assert_eq!(report.skipped, 0);
+    assert_eq!(report.errors().count(), 1);
+    let last = report.outcomes.last().unwrap();
+    let QueryResult::Rows(rs) = last.result.as_ref().unwrap() else { panic!("expected rows") };
+    assert_eq!(rs.len(), 2);
+}
+
+#[test]
+fn broken_connection_stops_the_script() {
+    let server = MockServer::start();
+    server.expect_query("SELECT 1;").respond_error("Table 'x' not found");
+    server.expect_query("SELECT 2;").close_connection();
+
+    let mut db = server.connect().unwrap();
+    let report = db.execute_script("SELECT 1; SELECT 2; SELECT 3;", OnError::Continue);
+    assert_eq!(report.outcomes.len(), 2);
+    assert_eq!(report.skipped, 1);
+}
+
+#[test]
+fn execute_file() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect().unwrap();
+    let path = std::env::temp_dir().join(format!("maazdb-script-{}.sql", std::process::id()));
+    std::fs::write(&path, "CREATE DATABASE files; USE files; CREATE TABLE t (id INT); INSERT INTO t VALUES (1), (2);").unwrap();
+
+    let results = db.execute_file(&path, OnError::Stop).unwrap().into_result().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[3].message(), Some("Inserted 2 rows"));
+
+    assert!(db.execute_file(&path, OnError::Stop).is_err());
+}
+
+#[tokio::test]
+async fn async_script() {
+    let server = MockServer::with_engine();
+    let mut db = server.connect_async().await.unwrap();
+    let report = db.execute_script("CREATE DATABASE a; USE a; CREATE TABLE t (id INT); SELECT * FROM t;", OnError::Stop).await;
+    assert!(report.is_success());
+    assert_eq!(report.outcomes.len(), 4);
+    db.close().await;
+}
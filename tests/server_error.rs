@@ -0,0 +1,122 @@
+// Server error classification and which reply packets count as server errors.
+
+use maazdb_rs::protocol::{Packet, PACKET_AUTH_ERR, PACKET_AUTH_OK, PACKET_HANDSHAKE};
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ErrorCategory, MaazDBError};
+
+#[test]
+fn categories() {
+    use ErrorCategory::*;
+    let cases = [
+        ("Syntax error: expected FROM, found 'FORM'", Syntax),
+        ("Parse error near ')'", Syntax),
+        ("Permission denied for table users", PermissionDenied),
+        ("User 'bob' is not authorized to DROP DATABASE", PermissionDenied),
+        ("Table 'users' already exists", AlreadyExists),
+        ("Database 'shop' already exists", AlreadyExists),
+        ("FOREIGN KEY constraint failed: users(id) has no row 999", ConstraintViolation),
+        ("Duplicate value 'a@b.c' for UNIQUE column email", ConstraintViolation),
+        ("Column 'name' cannot be NULL: NOT NULL constraint", ConstraintViolation),
+        ("No database selected", UnknownDatabase),
+        ("Database 'shop' does not exist", UnknownDatabase),
+        ("Unknown database 'shop'", UnknownDatabase),
+        ("Table 'orders' not found", UnknownTable),
+        ("No such table: orders", UnknownTable),
+        ("Column 'age' not found", UnknownColumn),
+        ("Unknown field 'age'", UnknownColumn),
+        ("Lock wait timeout exceeded; try again", Transient),
+        ("Server busy", Transient),
+        ("Something went wrong", Other),
+        ("Unknown error", Other),
+    ];
+    for (message, expected) in cases {
+        assert_eq!(ErrorCategory::classify(message), expected, "{}", message);
+    }
+}
+
+#[test]
+fn the_missing_object_decides_the_category() {
+    use ErrorCategory::*;
+    let cases = [
+        ("Column 'x' not found in table 'y'", UnknownColumn),
+        ("Table 'x' not found in database 'y'", UnknownTable),
+        ("Unknown column 'x' in table 'y'", UnknownColumn),
+        ("No such table 't' in database 'd'", UnknownTable),
+        ("In database 'd': table 't' does not exist", UnknownTable),
+        ("Error in table users: column 'agee' doesn't exist", UnknownColumn),
+        // Names are not object words, however they are spelled.
+        ("Table 'column_log' not found", UnknownTable),
+        ("Column \"table\" not found in table 'database'", UnknownColumn),
+    ];
+    for (message, expected) in cases {
+        assert_eq!(ErrorCategory::classify(message), expected, "{}", message);
+    }
+}
+
+#[test]
+fn quoted_names_are_ignored() {
+    use ErrorCategory::*;
+    let cases = [
+        ("Table 'unique_codes' not found", UnknownTable),
+        ("Table 'user_permissions' not found", UnknownTable),
+        ("Column 'syntax_ok' not found", UnknownColumn),
+        ("Database \"busy_days\" does not exist", UnknownDatabase),
+        ("Value 'not found' is too long for column note", Other),
+    ];
+    for (message, expected) in cases {
+        assert_eq!(ErrorCategory::classify(message), expected, "{}", message);
+    }
+}
+
+#[test]
+fn sqlstate_codes_are_distinct() {
+    use ErrorCategory::*;
+    let all = [Syntax, ConstraintViolation, AlreadyExists, UnknownDatabase, UnknownTable, UnknownColumn, PermissionDenied, Transient, Other];
+    let mut codes: Vec<_> = all.iter().map(|c| c.sqlstate()).collect();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), all.len());
+    assert!(codes.iter().all(|c| c.len() == 5));
+}
+
+#[test]
+fn reply_packet_types() {
+    let server = MockServer::start();
+    server.expect_query("SELECT a;").respond_error("Table 'a' not found");
+    server.expect_query("DROP DATABASE b;").respond_packets(vec![Packet::new(PACKET_AUTH_ERR, "Permission denied")]);
+    server.expect_query("SELECT c;").respond_packets(vec![Packet::Other(0x42, "Table 'c' not found".into())]);
+    server.expect_query("SELECT d;").respond_raw(vec![0x7F, 0, 0, 0, 0]);
+    server.expect_query("SELECT e;").respond_packets(vec![Packet::new(PACKET_HANDSHAKE, "admin\0admin")]);
+    server.expect_query("SELECT f;").respond_packets(vec![Packet::new(PACKET_AUTH_OK, "")]);
+
+    let mut db = server.connect().unwrap();
+    match db.query("SELECT a;") {
+        Err(MaazDBError::ServerError(e)) => {
+            assert_eq!(e.category, ErrorCategory::UnknownTable);
+            assert_eq!(e.statement, "SELECT a;");
+        }
+        other => panic!("expected a server error, got {:?}", other),
+    }
+    // Any reply other than a message or data carries the server's error text, whatever its type.
+    let expected = [
+        ("DROP DATABASE b;", PACKET_AUTH_ERR, ErrorCategory::PermissionDenied, "Permission denied"),
+        ("SELECT c;", 0x42, ErrorCategory::UnknownTable, "Table 'c' not found"),
+        ("SELECT d;", 0x7F, ErrorCategory::Other, ""),
+    ];
+    for (sql, packet_type, category, message) in expected {
+        match db.query(sql) {
+            Err(MaazDBError::ServerError(e)) => {
+                assert_eq!((e.packet_type, e.category, e.message.as_str()), (packet_type, category, message), "{}", sql);
+            }
+            other => panic!("{}: expected a server error, got {:?}", sql, other),
+        }
+    }
+    // Handshake frames have no place in a query reply.
+    for sql in ["SELECT e;", "SELECT f;"] {
+        match db.query(sql) {
+            Err(MaazDBError::ProtocolError(msg)) => assert!(msg.contains("Unexpected packet type"), "{}", msg),
+            other => panic!("{}: expected a protocol error, got {:?}", sql, other),
+        }
+    }
+    server.verify();
+}
@@ -0,0 +1,132 @@
+// Streaming result sets with `query_iter` and `query_stream` over frames split at awkward places.
+
+use futures_util::StreamExt;
+use maazdb_rs::protocol::Packet;
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ConnectionState, MaazDB, MaazDBError, Value};
+
+/// `payload` as `DATA_PART` frames cut at `cuts`, closed by a final `DATA` frame.
+fn frames(payload: &[u8], cuts: &[usize]) -> Vec<Packet> {
+    let mut bounds = vec![0];
+    bounds.extend_from_slice(cuts);
+    bounds.sort();
+    bounds.push(payload.len());
+    let mut packets: Vec<Packet> = bounds.windows(2)
+        .map(|w| Packet::DataPart(payload[w[0]..w[1]].to_vec().into()))
+        .collect();
+    let (_, last) = packets.pop().unwrap().into_parts();
+    packets.push(Packet::Data(last));
+    packets
+}
+
+fn streamed(db: &mut MaazDB, sql: &str) -> Vec<Vec<Value>> {
+    db.query_iter(sql).unwrap().map(|row| row.unwrap().values().to_vec()).collect()
+}
+
+#[test]
+fn row_split_across_many_frames() {
+    let payload = br#"{"headers":["id","note","ok"],"data":[[1,"say \"hi\", [then] go",true],[22,null,false]]}"#;
+    let text = |s: &str| Value::Text(s.to_string());
+    let expected = vec![
+        vec![Value::Int(1), text("say \"hi\", [then] go"), Value::Bool(true)],
+        vec![Value::Int(22), Value::Null, Value::Bool(false)],
+    ];
+
+    // Every single-byte frame, so each token and escape is cut somewhere.
+    let server = MockServer::start();
+    let every_byte: Vec<usize> = (1..payload.len()).collect();
+    server.expect_query("SELECT * FROM notes;").respond_packets(frames(payload, &every_byte));
+    // Cuts inside the number, the escape, the string and the literal `true`.
+    let at = |s: &str| payload.windows(s.len()).position(|w| w == s.as_bytes()).unwrap() + 1;
+    let cuts = [at("22"), at("\\\""), at("[then]"), at("true")];
+    server.expect_query("SELECT * FROM notes;").always().respond_packets(frames(payload, &cuts));
+
+    let mut db = server.connect().unwrap();
+    assert_eq!(streamed(&mut db, "SELECT * FROM notes;"), expected);
+    assert_eq!(streamed(&mut db, "SELECT * FROM notes;"), expected);
+    assert_eq!(db.state(), ConnectionState::Ready);
+
+    // A row may be larger than one frame, as long as it fits in `max_packet_size`.
+    let opts = server.connect_options().max_packet_size(40);
+    let mut small = MaazDB::connect_with(&opts).unwrap();
+    assert_eq!(streamed(&mut small, "SELECT * FROM notes;"), expected);
+}
+
+#[test]
+fn utf8_character_split_across_frames() {
+    let payload = r#"{"headers":["name"],"data":[["Zoë €5 🦀"]]}"#.as_bytes();
+    let cuts: Vec<usize> = ["ë", "€", "🦀"].iter()
+        .map(|c| payload.windows(c.len()).position(|w| w == c.as_bytes()).unwrap() + 1)
+        .collect();
+    let server = MockServer::start();
+    server.expect_query("SELECT name FROM people;").respond_packets(frames(payload, &cuts));
+    server.expect_query("SELECT name FROM people;").respond_packets(frames(payload, &cuts));
+
+    let mut db = server.connect().unwrap();
+    assert_eq!(streamed(&mut db, "SELECT name FROM people;"), [[Value::Text("Zoë €5 🦀".into())]]);
+    // Buffered, the frames are joined before decoding.
+    let names: Vec<(String,)> = db.query_as("SELECT name FROM people;").unwrap();
+    assert_eq!(names, [("Zoë €5 🦀".to_string(),)]);
+}
+
+#[test]
+fn invalid_utf8_breaks_the_connection() {
+    let server = MockServer::start();
+    let mut payload = br#"{"headers":["b"],"data":[[""#.to_vec();
+    payload.extend_from_slice(&[0xC3, b'(']);
+    payload.extend_from_slice(br#""]]}"#);
+    server.expect_query("SELECT b FROM t;").respond_packets(frames(&payload, &[payload.len() - 5]));
+
+    let mut db = server.connect().unwrap();
+    let rows: Vec<_> = db.query_iter("SELECT b FROM t;").unwrap().collect();
+    assert!(matches!(rows.as_slice(), [Err(_)]), "{:?}", rows);
+    assert_eq!(db.state(), ConnectionState::Broken);
+}
+
+#[test]
+fn dropping_an_iterator_part_way_drains_the_reply() {
+    let rows: Vec<String> = (0..1000).map(|i| format!("[{}]", i)).collect();
+    let payload = format!(r#"{{"headers":["n"],"data":[{}]}}"#, rows.join(","));
+    let cuts: Vec<usize> = (1..10).map(|i| i * payload.len() / 10).collect();
+
+    let server = MockServer::start();
+    server.expect_query("SELECT n FROM big;").respond_packets(frames(payload.as_bytes(), &cuts));
+    server.expect_query("SELECT 1;").respond_rows(&["1"], vec![vec![Value::Int(1)]]);
+
+    let mut db = server.connect().unwrap();
+    {
+        let mut iter = db.query_iter("SELECT n FROM big;").unwrap();
+        assert_eq!(iter.next().unwrap().unwrap().values(), [Value::Int(0)]);
+        assert_eq!(iter.next().unwrap().unwrap().values(), [Value::Int(1)]);
+    }
+    // The rest of the reply was read and thrown away, so the connection is still in step.
+    assert_eq!(db.state(), ConnectionState::Ready);
+    assert_eq!(db.query_as::<(i64,)>("SELECT 1;").unwrap(), [(1,)]);
+    assert_eq!(server.connections(), 1);
+    server.verify();
+}
+
+#[tokio::test]
+async fn async_stream_split_frames_and_early_drop() {
+    let payload = r#"{"headers":["n","s"],"data":[[1,"Zoë"],[2,"€"],[3,"x"]]}"#.as_bytes();
+    let cuts: Vec<usize> = (1..payload.len()).step_by(3).collect();
+    let server = MockServer::start();
+    server.expect_query("SELECT n, s FROM t;").times(2).respond_packets(frames(payload, &cuts));
+
+    let mut db = server.connect_async().await.unwrap();
+    let rows: Vec<Vec<Value>> = db.query_stream("SELECT n, s FROM t;").await.unwrap()
+        .map(|row| row.unwrap().values().to_vec())
+        .collect().await;
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1], [Value::Int(2), Value::Text("€".into())]);
+    assert_eq!(db.state(), ConnectionState::Ready);
+
+    // Unlike `RowIter`, a dropped stream does not drain the reply: the connection breaks.
+    {
+        let mut stream = db.query_stream("SELECT n, s FROM t;").await.unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+    }
+    assert!(matches!(db.query("SELECT 1;").await, Err(MaazDBError::IoError(_))));
+    assert_eq!(db.state(), ConnectionState::Broken);
+    server.verify();
+}
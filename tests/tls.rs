@@ -0,0 +1,345 @@
+// TLS against local rustls servers: certificate verification modes, pinning and mutual TLS.
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use maazdb_rs::{AsyncMaazDB, ClientIdentity, ConnectOptions, Fingerprint, FingerprintMismatch, MaazDB, MaazDBError, TlsMode, TlsOptions};
+use rustls::server::AllowAnyAuthenticatedClient;
+
+struct Pki {
+    ca: rcgen::Certificate,
+    server: rcgen::Certificate,
+    client: rcgen::Certificate,
+}
+
+impl Pki {
+    fn new() -> Self {
+        let mut ca_params = rcgen::CertificateParams::new(vec![]);
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        Pki {
+            ca: rcgen::Certificate::from_params(ca_params).unwrap(),
+            server: rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec!["localhost".into()])).unwrap(),
+            client: rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec!["maazdb-client".into()])).unwrap(),
+        }
+    }
+
+    fn client_identity(&self) -> ClientIdentity {
+        let cert_pem = self.client.serialize_pem_with_signer(&self.ca).unwrap();
+        ClientIdentity::from_pem(cert_pem.as_bytes(), self.client.serialize_private_key_pem().as_bytes()).unwrap()
+    }
+
+    fn tls(&self) -> TlsOptions {
+        self.tls_with(TlsMode::VerifyFull)
+    }
+
+    fn tls_with(&self, mode: TlsMode) -> TlsOptions {
+        TlsOptions::new(mode).ca_pem(self.ca.serialize_pem().unwrap())
+    }
+
+    /// A new leaf certificate for `name`, signed by this CA.
+    fn leaf(&self, name: &str) -> (rustls::Certificate, rustls::PrivateKey) {
+        let leaf = rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec![name.into()])).unwrap();
+        (
+            rustls::Certificate(leaf.serialize_der_with_signer(&self.ca).unwrap()),
+            rustls::PrivateKey(leaf.serialize_private_key_der()),
+        )
+    }
+}
+
+/// Starts a server that requires a client certificate signed by the test CA.
+fn start_server(pki: &Pki) -> u16 {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(&rustls::Certificate(pki.ca.serialize_der().unwrap())).unwrap();
+
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        .with_single_cert(
+            vec![rustls::Certificate(pki.server.serialize_der_with_signer(&pki.ca).unwrap())],
+            rustls::PrivateKey(pki.server.serialize_private_key_der()),
+        )
+        .unwrap();
+    serve(config)
+}
+
+/// Starts a server presenting `cert` that does not ask for a client certificate.
+fn start_plain_server((cert, key): (rustls::Certificate, rustls::PrivateKey)) -> u16 {
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+    serve(config)
+}
+
+/// Answers the MaazDB handshake and every query with a fixed message.
+fn serve(config: rustls::ServerConfig) -> u16 {
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for sock in listener.incoming() {
+            let config = config.clone();
+            thread::spawn(move || {
+                let conn = rustls::ServerConnection::new(config).unwrap();
+                let mut stream = rustls::StreamOwned::new(conn, sock.unwrap());
+                loop {
+                    let mut header = [0u8; 5];
+                    if stream.read_exact(&mut header).is_err() {
+                        send_alert_and_close(stream);
+                        return;
+                    }
+                    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+                    let mut payload = vec![0u8; len];
+                    if stream.read_exact(&mut payload).is_err() {
+                        return;
+                    }
+                    let (ptype, reply): (u8, &[u8]) = match header[0] {
+                        0x10 => (0x11, b"Welcome"),
+                        _ => (0x02, b"OK"),
+                    };
+                    let mut frame = vec![ptype];
+                    frame.extend_from_slice(&(reply.len() as u32).to_be_bytes());
+                    frame.extend_from_slice(reply);
+                    if stream.write_all(&frame).and_then(|_| stream.flush()).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    port
+}
+
+/// Flushes a pending TLS alert (e.g. a rejected client certificate) before closing,
+/// and drains what the client already sent so the close does not turn into a reset.
+fn send_alert_and_close(mut stream: rustls::StreamOwned<rustls::ServerConnection, TcpStream>) {
+    while stream.conn.wants_write() {
+        if stream.conn.write_tls(&mut stream.sock).is_err() {
+            return;
+        }
+    }
+    let _ = stream.sock.shutdown(Shutdown::Write);
+    let _ = stream.sock.set_read_timeout(Some(Duration::from_secs(1)));
+    let _ = io::copy(&mut stream.sock, &mut io::sink());
+}
+
+#[test]
+fn client_certificate_is_accepted() {
+    let pki = Pki::new();
+    let port = start_server(&pki);
+
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin")
+        .tls(pki.tls())
+        .client_identity(pki.client_identity());
+    let mut db = MaazDB::connect_with(&opts).unwrap();
+    assert_eq!(db.query("SELECT 1;").unwrap(), "OK");
+}
+
+#[test]
+fn missing_client_certificate_is_rejected() {
+    let pki = Pki::new();
+    let port = start_server(&pki);
+
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin").tls(pki.tls());
+    match MaazDB::connect_with(&opts) {
+        Err(MaazDBError::TlsError(_)) => {}
+        Err(e) => panic!("expected a TLS error, got {:?}", e),
+        Ok(_) => panic!("connection without a client certificate succeeded"),
+    }
+}
+
+#[test]
+fn untrusted_client_certificate_is_rejected() {
+    let pki = Pki::new();
+    let port = start_server(&pki);
+
+    // Same CA name, different key: the server cannot verify this chain.
+    let other = Pki::new();
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin")
+        .tls(pki.tls())
+        .client_identity(other.client_identity());
+    assert!(matches!(MaazDB::connect_with(&opts), Err(MaazDBError::TlsError(_))));
+}
+
+#[tokio::test]
+async fn async_client_presents_certificate() {
+    let pki = Pki::new();
+    let port = start_server(&pki);
+
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin")
+        .tls(pki.tls().client_identity(pki.client_identity()));
+    let mut db = AsyncMaazDB::connect_with(&opts).await.unwrap();
+    assert_eq!(db.query("SELECT 1;").await.unwrap(), "OK");
+}
+
+fn certificate_error(result: Result<MaazDB, MaazDBError>) -> rustls::Error {
+    match result {
+        Err(MaazDBError::CertificateError(e)) => e,
+        Err(e) => panic!("expected a certificate error, got {:?}", e),
+        Ok(_) => panic!("connection succeeded"),
+    }
+}
+
+#[test]
+fn verify_full_rejects_hostname_mismatch() {
+    let pki = Pki::new();
+    let port = start_plain_server(pki.leaf("db.example.com"));
+
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin").tls(pki.tls());
+    let err = certificate_error(MaazDB::connect_with(&opts));
+    assert_eq!(err, rustls::Error::InvalidCertificate(rustls::CertificateError::NotValidForName));
+}
+
+#[test]
+fn verify_ca_accepts_hostname_mismatch() {
+    let pki = Pki::new();
+    let port = start_plain_server(pki.leaf("db.example.com"));
+
+    let opts = ConnectOptions::new("localhost", port, "admin", "admin").tls(pki.tls_with(TlsMode::VerifyCa));
+    let mut db = MaazDB::connect_with(&opts).unwrap();
+    assert_eq!(db.query("SELECT 1;").unwrap(), "OK");
+}
+
+#[test]
+fn verify_ca_rejects_untrusted_ca() {
+    let pki = Pki::new();
+    let port = start_plain_server(Pki::new().leaf("localhost"));
+
+    for mode in [TlsMode::VerifyCa, TlsMode::VerifyFull] {
+        let opts = ConnectOptions::new("localhost", port, "admin", "admin").tls(pki.tls_with(mode));
+        // Both test CAs have the same name, so the chain is built and fails on the signature.
+        let err = certificate_error(MaazDB::connect_with(&opts));
+        assert!(matches!(
+            err,
+            rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer | rustls::CertificateError::BadSignature)
+        ), "{:?}", err);
+    }
+}
+
+fn fingerprint_mismatch(err: &rustls::Error) -> &FingerprintMismatch {
+    match err {
+        rustls::Error::InvalidCertificate(rustls::CertificateError::Other(inner)) => {
+            inner.downcast_ref::<FingerprintMismatch>().expect("a FingerprintMismatch")
+        }
+        other => panic!("expected a fingerprint mismatch, got {:?}", other),
+    }
+}
+
+fn pinned(port: u16, fp: Fingerprint) -> ConnectOptions {
+    ConnectOptions::new("localhost", port, "admin", "admin").tls(TlsOptions::new(TlsMode::Pinned(fp)))
+}
+
+#[test]
+fn fingerprints_parse_and_format() {
+    let hex = "00112233445566778899AABBCCDDEEFF00112233445566778899aabbccddeeff";
+    let fp = Fingerprint::certificate(hex).unwrap();
+    assert_eq!(Fingerprint::certificate(&fp.to_string()["cert-sha256:".len()..]).unwrap(), fp);
+    assert_eq!(Fingerprint::certificate(&hex.to_uppercase()).unwrap(), fp);
+    assert_eq!(
+        fp.to_string(),
+        "cert-sha256:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF"
+    );
+    assert_eq!(fp.to_string().parse::<Fingerprint>().unwrap(), fp);
+
+    let spki = Fingerprint::public_key(hex).unwrap();
+    assert_ne!(spki, fp);
+    assert!(spki.to_string().starts_with("spki-sha256:00:11:"));
+    assert_eq!(spki.to_string().parse::<Fingerprint>().unwrap(), spki);
+
+    for bad in ["", "0011", &hex[1..], &format!("{}00", hex), &hex.replace('A', "G")] {
+        assert!(matches!(Fingerprint::certificate(bad), Err(MaazDBError::TlsError(_))), "{:?}", bad);
+    }
+    assert!("sha256:00:11".parse::<Fingerprint>().is_err());
+}
+
+#[test]
+fn public_key_fingerprint_reads_the_spki() {
+    let pki = Pki::new();
+    let leaf = rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec!["localhost".into()])).unwrap();
+    let spki = ring::digest::digest(&ring::digest::SHA256, &leaf.get_key_pair().public_key_der());
+    let expected = Fingerprint::public_key(&spki.as_ref().iter().map(|b| format!("{:02x}", b)).collect::<String>()).unwrap();
+
+    // The same key gives the same fingerprint, self-signed or issued by a CA.
+    let self_signed = leaf.serialize_der().unwrap();
+    let issued = leaf.serialize_der_with_signer(&pki.ca).unwrap();
+    assert_eq!(Fingerprint::of_public_key(&self_signed).unwrap(), expected);
+    assert_eq!(Fingerprint::of_public_key(&issued).unwrap(), expected);
+    assert!(expected.matches(&issued));
+    assert_ne!(Fingerprint::of_certificate(&self_signed), Fingerprint::of_certificate(&issued));
+
+    for garbage in [&b""[..], b"\x30", b"\x30\x85\x00\x00\x00\x00\x00", &issued[..issued.len() / 2]] {
+        assert!(Fingerprint::of_public_key(garbage).is_err());
+        assert!(!expected.matches(garbage));
+    }
+}
+
+#[test]
+fn pinned_accepts_only_the_pinned_certificate() {
+    let pki = Pki::new();
+    let (cert, key) = pki.leaf("localhost");
+    let port = start_plain_server((cert.clone(), key));
+
+    for fp in [Fingerprint::of_certificate(&cert.0), Fingerprint::of_public_key(&cert.0).unwrap()] {
+        let mut db = MaazDB::connect_with(&pinned(port, fp)).unwrap();
+        assert_eq!(db.query("SELECT 1;").unwrap(), "OK");
+    }
+
+    let (other, _) = pki.leaf("localhost");
+    for fp in [Fingerprint::of_certificate(&other.0), Fingerprint::of_public_key(&other.0).unwrap()] {
+        let err = certificate_error(MaazDB::connect_with(&pinned(port, fp)));
+        let mismatch = fingerprint_mismatch(&err);
+        assert_eq!(mismatch.expected, fp.to_string());
+        assert_ne!(mismatch.actual, mismatch.expected);
+    }
+}
+
+fn known_hosts(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("maazdb-known-hosts-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn tofu(port: u16, path: &std::path::Path) -> ConnectOptions {
+    ConnectOptions::new("localhost", port, "admin", "admin").tls(TlsOptions::new(TlsMode::TrustOnFirstUse(path.into())))
+}
+
+#[test]
+fn trust_on_first_use_records_then_refuses_a_new_key() {
+    let pki = Pki::new();
+    let (cert, key) = pki.leaf("localhost");
+    let port = start_plain_server((cert.clone(), key));
+    let path = known_hosts("tofu");
+
+    MaazDB::connect_with(&tofu(port, &path)).unwrap();
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    let fp = Fingerprint::of_public_key(&cert.0).unwrap();
+    assert_eq!(recorded, format!("localhost:{} {}\n", port, fp));
+
+    // Known now: the same key is accepted without another entry.
+    MaazDB::connect_with(&tofu(port, &path)).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), recorded);
+
+    // The host comes back with a different key.
+    let new_port = start_plain_server(pki.leaf("localhost"));
+    std::fs::write(&path, format!("# comment\nlocalhost:{} {}\n", new_port, fp)).unwrap();
+    let err = certificate_error(MaazDB::connect_with(&tofu(new_port, &path)));
+    assert_eq!(fingerprint_mismatch(&err).expected, fp.to_string());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn trust_on_first_use_does_not_pin_a_server_without_the_key() {
+    let pki = Pki::new();
+    let (cert, _) = pki.leaf("localhost");
+    let (_, wrong_key) = pki.leaf("localhost");
+    let port = start_plain_server((cert, wrong_key));
+    let path = known_hosts("impostor");
+
+    assert!(MaazDB::connect_with(&tofu(port, &path)).is_err());
+    assert!(!path.exists(), "the impostor's certificate was recorded");
+}
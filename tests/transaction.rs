@@ -0,0 +1,144 @@
+// `Transaction`: commit, rollback on drop, savepoints, and what the guard refuses to do.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use maazdb_rs::testing::MockServer;
+use maazdb_rs::{ConnectionState, MaazDB, MaazDBError};
+
+fn accounts_db(server: &MockServer) -> MaazDB {
+    let mut db = server.connect().unwrap();
+    db.query("CREATE DATABASE bank;").unwrap();
+    db.query("USE bank;").unwrap();
+    db.query("CREATE TABLE accounts (id INT PRIMARY KEY, owner TEXT UNIQUE);").unwrap();
+    db
+}
+
+fn owners(db: &mut MaazDB) -> Vec<String> {
+    let rows: Vec<(String,)> = db.query_as("SELECT owner FROM accounts ORDER BY id;").unwrap();
+    rows.into_iter().map(|(owner,)| owner).collect()
+}
+
+/// The statements received after the setup in [`accounts_db`].
+fn statements(server: &MockServer) -> Vec<String> {
+    server.received().into_iter().skip(3).filter(|s| !s.starts_with("SELECT")).collect()
+}
+
+#[test]
+fn commit_keeps_the_changes() {
+    let server = MockServer::with_engine();
+    let mut db = accounts_db(&server);
+
+    let mut tx = db.transaction().unwrap();
+    assert_eq!(tx.depth(), 0);
+    tx.query("INSERT INTO accounts (id, owner) VALUES (1, 'ann');").unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(owners(&mut db), ["ann"]);
+    assert_eq!(statements(&server), ["BEGIN", "INSERT INTO accounts (id, owner) VALUES (1, 'ann')", "COMMIT"]);
+}
+
+#[test]
+fn dropping_rolls_back() {
+    let server = MockServer::with_engine();
+    let mut db = accounts_db(&server);
+
+    {
+        let mut tx = db.transaction().unwrap();
+        tx.query("INSERT INTO accounts (id, owner) VALUES (1, 'ann');").unwrap();
+    }
+    assert!(owners(&mut db).is_empty());
+
+    // Also while unwinding from a panic.
+    let unwound = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut tx = db.transaction().unwrap();
+        tx.query("INSERT INTO accounts (id, owner) VALUES (2, 'bob');").unwrap();
+        panic!("transfer failed");
+    }));
+    assert!(unwound.is_err());
+    assert!(owners(&mut db).is_empty());
+    assert_eq!(statements(&server).iter().filter(|s| *s == "ROLLBACK").count(), 2);
+
+    // The connection is usable outside a transaction again.
+    db.query("INSERT INTO accounts (id, owner) VALUES (3, 'cy');").unwrap();
+    assert_eq!(owners(&mut db), ["cy"]);
+}
+
+#[test]
+fn nested_savepoint_rolls_back_alone() {
+    let server = MockServer::with_engine();
+    let mut db = accounts_db(&server);
+
+    let mut tx = db.transaction().unwrap();
+    tx.query("INSERT INTO accounts (id, owner) VALUES (1, 'ann');").unwrap();
+    {
+        let mut sp = tx.transaction().unwrap();
+        assert_eq!(sp.depth(), 1);
+        sp.query("INSERT INTO accounts (id, owner) VALUES (2, 'bob');").unwrap();
+        let mut inner = sp.transaction().unwrap();
+        inner.query("INSERT INTO accounts (id, owner) VALUES (3, 'cy');").unwrap();
+        inner.commit().unwrap();
+        sp.rollback().unwrap();
+    }
+    {
+        let mut sp = tx.transaction().unwrap();
+        sp.query("INSERT INTO accounts (id, owner) VALUES (4, 'dee');").unwrap();
+        sp.commit().unwrap();
+    }
+    tx.commit().unwrap();
+
+    assert_eq!(owners(&mut db), ["ann", "dee"]);
+    assert_eq!(statements(&server), [
+        "BEGIN",
+        "INSERT INTO accounts (id, owner) VALUES (1, 'ann')",
+        "SAVEPOINT maazdb_sp_1",
+        "INSERT INTO accounts (id, owner) VALUES (2, 'bob')",
+        "SAVEPOINT maazdb_sp_2",
+        "INSERT INTO accounts (id, owner) VALUES (3, 'cy')",
+        "RELEASE SAVEPOINT maazdb_sp_2",
+        "ROLLBACK TO SAVEPOINT maazdb_sp_1",
+        "SAVEPOINT maazdb_sp_1",
+        "INSERT INTO accounts (id, owner) VALUES (4, 'dee')",
+        "RELEASE SAVEPOINT maazdb_sp_1",
+        "COMMIT",
+    ]);
+}
+
+#[test]
+fn commit_after_a_failed_statement() {
+    let server = MockServer::with_engine();
+    let mut db = accounts_db(&server);
+
+    let mut tx = db.transaction().unwrap();
+    tx.query("INSERT INTO accounts (id, owner) VALUES (1, 'ann');").unwrap();
+    let err = tx.query("INSERT INTO accounts (id, owner) VALUES (2, 'ann');").unwrap_err();
+    assert!(err.is_constraint_violation(), "{:?}", err);
+    // A failed statement does not end the transaction: the rest can still be committed.
+    tx.query("INSERT INTO accounts (id, owner) VALUES (2, 'bob');").unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(owners(&mut db), ["ann", "bob"]);
+}
+
+#[test]
+fn reconnect_and_close_are_refused_inside() {
+    let server = MockServer::with_engine();
+    let mut db = accounts_db(&server);
+
+    let mut tx = db.transaction().unwrap();
+    tx.query("INSERT INTO accounts (id, owner) VALUES (1, 'ann');").unwrap();
+    for result in [tx.reconnect(), tx.close()] {
+        match result {
+            Err(MaazDBError::ProtocolError(msg)) => assert!(msg.contains("inside a transaction"), "{}", msg),
+            other => panic!("expected a ProtocolError, got {:?}", other),
+        }
+    }
+    // Neither touched the connection, so the transaction is intact.
+    assert_eq!(tx.state(), ConnectionState::Ready);
+    tx.commit().unwrap();
+    assert_eq!(server.connections(), 1);
+
+    db.reconnect().unwrap();
+    assert_eq!(owners(&mut db), ["ann"]);
+    db.close().unwrap();
+    assert_eq!(db.state(), ConnectionState::Closed);
+}